@@ -23,17 +23,21 @@
 //! and optionally signed by the creator with certain id and send over to a
 //! remote party.
 
+use std::collections::BTreeMap;
 use std::fmt::Display;
+use std::str::FromStr;
 
-use amplify::confinement::TinyVec;
+use amplify::confinement::{self, Confined, TinyVec};
 use baid58::ToBaid58;
 use rgb::{ContractId, Schema, SchemaId, SchemaRoot};
 use strict_encoding::{
-    StrictDecode, StrictDeserialize, StrictDumb, StrictEncode, StrictSerialize, StrictType,
+    DeserializeError, StrictDecode, StrictDeserialize, StrictDumb, StrictEncode, StrictReader,
+    StrictSerialize, StrictType,
 };
 
+use crate::containers::migration::{self, Migrated, MigrationError};
 use crate::containers::transfer::TransferId;
-use crate::containers::{Cert, Contract, Transfer};
+use crate::containers::{Cert, Contract, PaymentReceipt, Transfer};
 use crate::interface::{Iface, IfaceId, IfaceImpl, ImplId};
 use crate::LIB_NAME_RGB_STD;
 
@@ -47,7 +51,22 @@ pub trait BindleContent: StrictSerialize + StrictDeserialize + StrictDumb {
     type Id: ToBaid58<32> + Display + StrictType + StrictDumb + StrictEncode + StrictDecode;
 
     fn bindle_id(&self) -> Self::Id;
+    /// Additional human-readable headers put into the ASCII armored block.
+    fn bindle_headers(&self) -> BTreeMap<&'static str, String> { none!() }
     fn bindle(self) -> Bindle<Self> { Bindle::new(self) }
+    /// Decodes serialized bindle data, migrating them from the legacy layouts
+    /// of versioned containers.
+    fn decode_bindle(data: &[u8]) -> Result<Migrated<Bindle<Self>>, MigrationError> {
+        let bindle = Bindle::strict_decode(&mut StrictReader::with(usize::MAX, data))?;
+        Ok(Migrated::current(bindle))
+    }
+    /// Decodes serialized content of the bindle, like the one from the ASCII
+    /// armored block, migrating it from the legacy layouts of versioned
+    /// containers. The data slice is advanced past the decoded content.
+    fn decode_content(data: &mut &[u8]) -> Result<Migrated<Self>, MigrationError> {
+        let content = Self::strict_decode(&mut StrictReader::with(usize::MAX, data))?;
+        Ok(Migrated::current(content))
+    }
 }
 
 impl<Root: SchemaRoot> BindleContent for Schema<Root> {
@@ -62,6 +81,12 @@ impl BindleContent for Contract {
     const PLATE_TITLE: &'static str = "RGB CONTRACT";
     type Id = ContractId;
     fn bindle_id(&self) -> Self::Id { self.contract_id() }
+    fn decode_bindle(data: &[u8]) -> Result<Migrated<Bindle<Self>>, MigrationError> {
+        migration::decode_consignment(data)
+    }
+    fn decode_content(data: &mut &[u8]) -> Result<Migrated<Self>, MigrationError> {
+        migration::decode_consignment_content(data)
+    }
 }
 
 impl BindleContent for Transfer {
@@ -69,6 +94,24 @@ impl BindleContent for Transfer {
     const PLATE_TITLE: &'static str = "RGB STATE TRANSFER";
     type Id = TransferId;
     fn bindle_id(&self) -> Self::Id { self.transfer_id() }
+    fn decode_bindle(data: &[u8]) -> Result<Migrated<Bindle<Self>>, MigrationError> {
+        migration::decode_consignment(data)
+    }
+    fn decode_content(data: &mut &[u8]) -> Result<Migrated<Self>, MigrationError> {
+        migration::decode_consignment_content(data)
+    }
+}
+
+impl BindleContent for PaymentReceipt {
+    const MAGIC: [u8; 4] = *b"RCPT";
+    const PLATE_TITLE: &'static str = "RGB PAYMENT RECEIPT";
+    type Id = TransferId;
+    fn bindle_id(&self) -> Self::Id { self.transfer_id }
+    fn bindle_headers(&self) -> BTreeMap<&'static str, String> {
+        bmap! {
+            "Contract" => self.contract_id.to_string()
+        }
+    }
 }
 
 impl BindleContent for Iface {
@@ -76,6 +119,11 @@ impl BindleContent for Iface {
     const PLATE_TITLE: &'static str = "RGB INTERFACE";
     type Id = IfaceId;
     fn bindle_id(&self) -> Self::Id { self.iface_id() }
+    fn bindle_headers(&self) -> BTreeMap<&'static str, String> {
+        bmap! {
+            "Name" => self.name.to_string()
+        }
+    }
 }
 
 impl BindleContent for IfaceImpl {
@@ -83,6 +131,12 @@ impl BindleContent for IfaceImpl {
     const PLATE_TITLE: &'static str = "RGB INTERFACE IMPLEMENTATION";
     type Id = ImplId;
     fn bindle_id(&self) -> Self::Id { self.impl_id() }
+    fn bindle_headers(&self) -> BTreeMap<&'static str, String> {
+        bmap! {
+            "Interface" => self.iface_id.to_string(),
+            "Schema" => self.schema_id.to_string()
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -107,6 +161,20 @@ impl<C: BindleContent> Bindle<C> {
         }
     }
 
+    /// Id of the content as it is stored in the bindle, which may differ from
+    /// the actual content id if the bindle was tampered with.
+    pub fn id(&self) -> &C::Id { &self.id }
+
+    /// Checks that the id stored in the bindle matches its content.
+    pub fn is_consistent(&self) -> bool
+    where C::Id: PartialEq {
+        self.id == self.data.bindle_id()
+    }
+
+    pub fn add_cert(&mut self, cert: Cert) -> Result<(), confinement::Error> {
+        self.sigs.push(cert)
+    }
+
     pub fn into_split(self) -> (C, TinyVec<Cert>) { (self.data, self.sigs) }
     pub fn unbindle(self) -> C { self.data }
 }
@@ -118,6 +186,9 @@ impl<C: BindleContent> Display for Bindle<C> {
         writeln!(f, "----- BEGIN {} -----", C::PLATE_TITLE)?;
         writeln!(f, "Id: {}", self.id)?;
         writeln!(f, "Checksum: {}", self.id.to_baid58().mnemonic())?;
+        for (header, value) in self.data.bindle_headers() {
+            writeln!(f, "{header}: {value}")?;
+        }
         for cert in &self.sigs {
             writeln!(f, "Signed-By: {}", cert.signer)?;
         }
@@ -143,13 +214,98 @@ impl<C: BindleContent> Display for Bindle<C> {
     }
 }
 
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum BindleParseError {
+    /// the provided text doesn't represent a recognizable ASCII-armored RGB
+    /// bindle encoding.
+    WrongStructure,
+
+    /// bindle id provided in the header doesn't match the bindle content.
+    IdMismatch,
+
+    #[from]
+    #[display(inner)]
+    Base64(base64::DecodeError),
+
+    #[from]
+    #[display(inner)]
+    Confinement(confinement::Error),
+
+    #[from]
+    #[display(inner)]
+    Deserialize(DeserializeError),
+
+    #[from]
+    #[display(inner)]
+    Migration(MigrationError),
+}
+
+/// Parses ASCII-armored bindle. Since the armored form contains only signer
+/// identities and not the signatures, the parsed bindle has no signatures.
+/// Content saved in the legacy container layouts is migrated to the current
+/// one, like when the bindle is loaded from a file.
+impl<C: BindleContent> FromStr for Bindle<C> {
+    type Err = BindleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use base64::Engine;
+
+        let first = format!("----- BEGIN {} -----", C::PLATE_TITLE);
+        let last = format!("----- END {} -----", C::PLATE_TITLE);
+        let mut lines = s.lines();
+        if lines.next() != Some(first.as_str()) {
+            return Err(BindleParseError::WrongStructure);
+        }
+
+        let mut id = None;
+        for line in lines.by_ref() {
+            if line.is_empty() {
+                break;
+            }
+            if let Some(header_id) = line.strip_prefix("Id: ") {
+                id = Some(header_id);
+            }
+        }
+
+        let mut data = String::new();
+        let mut ended = false;
+        for line in lines.by_ref() {
+            if line == last {
+                ended = true;
+                break;
+            }
+            data.push_str(line.trim());
+        }
+        if !ended {
+            return Err(BindleParseError::WrongStructure);
+        }
+
+        let engine = base64::engine::general_purpose::STANDARD;
+        let data = Confined::<Vec<u8>, 0, 0xFFFFFF>::try_from(engine.decode(data)?)?;
+        let mut rest = data.as_slice();
+        let content = C::decode_content(&mut rest)?.into_inner();
+        if !rest.is_empty() {
+            return Err(DeserializeError::DataNotEntirelyConsumed.into());
+        }
+        let bindle = Bindle::new(content);
+        if matches!(id, Some(id) if id != bindle.id.to_string()) {
+            return Err(BindleParseError::IdMismatch);
+        }
+        Ok(bindle)
+    }
+}
+
+#[cfg(feature = "fs")]
+pub use _fs::LoadError;
+
 #[cfg(feature = "fs")]
 mod _fs {
     use std::io::{Read, Write};
     use std::path::Path;
     use std::{fs, io};
 
-    use strict_encoding::{DecodeError, StrictEncode, StrictReader, StrictWriter};
+    use strict_encoding::{DecodeError, StrictEncode, StrictWriter};
 
     use super::*;
 
@@ -163,10 +319,22 @@ mod _fs {
         #[from]
         #[from(io::Error)]
         Decode(DecodeError),
+
+        #[display(inner)]
+        #[from]
+        Migration(MigrationError),
     }
 
     impl<C: BindleContent> Bindle<C> {
-        pub fn load(&self, path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        /// Loads bindle from a file, transparently migrating data saved in the
+        /// legacy container formats.
+        pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+            Self::load_migrated(path).map(Migrated::into_inner)
+        }
+
+        /// Loads bindle from a file, reporting which migrations from the legacy
+        /// container formats were run.
+        pub fn load_migrated(path: impl AsRef<Path>) -> Result<Migrated<Self>, LoadError> {
             let mut rgb = [0u8; 3];
             let mut magic = [0u8; 4];
             let mut file = fs::File::open(path)?;
@@ -175,9 +343,9 @@ mod _fs {
             if rgb != *b"RGB" || magic != C::MAGIC {
                 return Err(LoadError::InvalidMagic);
             }
-            let mut reader = StrictReader::with(usize::MAX, file);
-            let me = Self::strict_decode(&mut reader)?;
-            Ok(me)
+            let mut data = vec![];
+            file.read_to_end(&mut data)?;
+            Ok(C::decode_bindle(&data)?)
         }
 
         pub fn save(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
@@ -19,21 +19,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use amplify::confinement::{Confined, TinyOrdMap, U8};
+use amplify::confinement::{Confined, SmallBlob, TinyOrdMap, TinyOrdSet, TinyString, U8};
 use amplify::{confinement, Wrapper};
-use bp::secp256k1::rand::thread_rng;
+use bp::seals::txout::{CloseMethod, TxPtr};
+use bp::secp256k1::rand::RngCore;
 use bp::{Chain, Outpoint};
+use commit_verify::CommitVerify;
 use rgb::{
-    fungible, Assign, Assignments, AssignmentsType, FungibleType, Genesis, GlobalState,
-    StateSchema, SubSchema, TypedAssigns,
+    attachment, fungible, Assign, Assignments, AssignmentsType, AttachId, ConfidentialState,
+    ContractId, ExposedSeal, Extension, ExtensionType, FungibleType, Genesis, GenesisSeal,
+    GlobalState, GlobalStateType, GraphSeal, Occurrences, OpId, Operation, Opout,
+    PedersenCommitment, Redeemed, StateSchema, SubSchema, Transition, TransitionType,
+    TypedAssigns, Valencies, ValencyType,
 };
-use strict_encoding::{SerializeError, StrictSerialize, TypeName};
-use strict_types::reify;
+use strict_encoding::{SerializeError, StrictDumb, StrictEncode, StrictSerialize, TypeName};
+use strict_types::typelib::LibBuilder;
+use strict_types::typesys::SystemBuilder;
+use strict_types::{reify, SemId, TypeSystem};
 
-use crate::containers::Contract;
-use crate::interface::{Iface, IfaceImpl, IfacePair};
+use crate::containers::validate::{check_redeemed, ValencyError};
+use crate::containers::{assign_views, Contract, SealSpec};
+use crate::interface::{
+    ExtensionIface, GenesisIface, Iface, IfaceId, IfaceImpl, IfacePair, ImplErrors, NamedType,
+    OwnedIface, OwnedKind, Req, SemTypeInfo, TransitionIface, TypeReqMap,
+};
+use crate::resolvers::OperationProvider;
+use crate::rng::BlindingRng;
+use crate::stl::{
+    ContractTerms, ContractText, Expiry, MediaType, Precision, StandardTypes, TermsSig,
+};
 
 #[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
 #[display(doc_comments)]
@@ -45,6 +66,28 @@ pub enum ForgeError {
     /// interface implementation references different schema that the one
     /// provided to the forge.
     SchemaMismatch,
+
+    /// state transition {0} is not known to the interface implementation.
+    TransitionNotFound(TypeName),
+
+    /// state extension {0} is not known to the interface implementation.
+    ExtensionNotFound(TypeName),
+
+    /// contract already has implementation of interface {0} of the same or
+    /// newer version.
+    IfaceConflict(IfaceId),
+
+    /// contract genesis doesn't contain state `{0}` required by the
+    /// interface.
+    GenesisStateAbsent(TypeName),
+
+    #[from]
+    #[display(inner)]
+    Semantics(ImplErrors),
+
+    #[from]
+    #[display(inner)]
+    Confinement(confinement::Error),
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
@@ -53,9 +96,111 @@ pub enum BuilderError {
     /// type `{0}` is not known to the schema.
     TypeNotFound(TypeName),
 
+    /// state type with id {0} is not known to the schema.
+    TypeIdNotFound(u16),
+
     /// state `{0}` provided to the builder has invalid type
     InvalidStateType(TypeName),
 
+    /// value of the global state has strict type {got}, while the schema
+    /// requires type {expected}.
+    TypeMismatch { expected: SemId, got: SemId },
+
+    /// global state `{name}` has size of {size} bytes, exceeding the maximum
+    /// allowed size of 65535 bytes.
+    GlobalTooLarge { name: TypeName, size: usize },
+
+    /// global state `{0}` is not encoded canonically: the value contains
+    /// trailing bytes or differs from its re-serialized form.
+    NonCanonicalState(TypeName),
+
+    /// global state `{b}` can't be set together with `{a}`, since the
+    /// interface declares them mutually exclusive.
+    MutuallyExclusive { a: TypeName, b: TypeName },
+
+    /// global state `{name}` can't have more than {max} items.
+    TooManyGlobalItems { name: TypeName, max: u16 },
+
+    /// block height {1} is not plausible for {0} chain.
+    ImplausibleHeight(Chain, u32),
+
+    /// unknown chain name '{0}'.
+    UnknownChain(String),
+
+    /// chain must be set before the anchor height.
+    ChainNotSet,
+
+    /// interface doesn't define global state {0} for storing the contract
+    /// metadata.
+    MetadataNotSupported(TypeName),
+
+    /// schema defines global state {0} as a plain text, which can't keep the
+    /// contract terms signature.
+    SigNotSupported(TypeName),
+
+    /// contract expiry at {0} is not in the future.
+    ExpiryInPast(Expiry),
+
+    /// previous operation output {0} doesn't exist.
+    UnknownInput(Opout),
+
+    /// previous operation output {0} has state of a kind which doesn't match
+    /// the schema.
+    InputKindMismatch(Opout),
+
+    /// amount of the fungible state spent by input {0} is not known to the
+    /// builder.
+    UnknownInputAmount(Opout),
+
+    /// sum of `{0}` amounts overflows 64-bit integer.
+    AmountOverflow(TypeName),
+
+    /// transition assigns fungible state of total amount {outputs}, which
+    /// doesn't match the total amount {inputs} spent by its inputs.
+    BalanceMismatch { inputs: u64, outputs: u64 },
+
+    /// inputs have insufficient amount of `{name}` to cover the assigned
+    /// state; {deficit} more is required.
+    InsufficientInputs { name: TypeName, deficit: u64 },
+
+    /// operation doesn't assign exactly one fungible state, thus it is not
+    /// known which state must be paid.
+    NoFungibleState,
+
+    /// malformed CSV row at {line}:{col}: {details}
+    Csv {
+        line: usize,
+        col: usize,
+        details: String,
+    },
+
+    /// revealed state `{0}` doesn't match the provided commitment.
+    InvalidCommitment(TypeName),
+
+    /// media type {media_type} is not allowed for the attachments of `{name}`.
+    MediaTypeNotAllowed { name: TypeName, media_type: MediaType },
+
+    /// genesis state can't be assigned to the blinded seal {0}.
+    BlindedSeal(SealSpec),
+
+    /// seal {0} is specified with a close method different from {1}.
+    CloseMethodMismatch(SealSpec, CloseMethod),
+
+    /// seal {seal} belongs to {found}, while the contract is issued on
+    /// {expected}.
+    SealWrongNetwork {
+        seal: SealSpec,
+        expected: Chain,
+        found: Chain,
+    },
+
+    /// seal close method {0} is not allowed by the interface implementation.
+    CloseMethodNotAllowed(CloseMethod),
+
+    /// global state `{0}` is not declared by the operation schema; the
+    /// operation may contain only {1}.
+    StateNotDeclaredByOperation(TypeName, String),
+
     #[from]
     #[display(inner)]
     StrictEncode(SerializeError),
@@ -67,143 +212,3579 @@ pub enum BuilderError {
     #[from]
     #[display(inner)]
     Confinement(confinement::Error),
+
+    #[from]
+    #[display(inner)]
+    Valency(ValencyError),
+
+    #[from]
+    #[display(inner)]
+    Issue(IssueError),
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
 #[display(doc_comments)]
-pub enum IssueError {}
+pub enum IssueError {
+    /// owned state type {0} used by the contract is not known to the schema.
+    UnknownAssignmentType(AssignmentsType),
 
-#[derive(Clone, Debug)]
-pub struct ContractBuilder {
-    schema: SubSchema,
+    /// interface implementation doesn't allow issuing contracts on {0} chain.
+    ChainNotAllowed(Chain),
+
+    /// chain of the contract is not set.
+    ChainNotSet,
+
+    /// global state type {0} used by the contract is not known to the schema
+    /// or is not mapped by the interface implementation.
+    UnmappedGlobal(GlobalStateType),
+
+    /// global state type {0} is required by the schema, but the contract
+    /// doesn't have enough values of it.
+    GlobalStateAbsent(GlobalStateType),
+
+    /// value of the global state `{name}` doesn't match its type in the
+    /// schema type system.
+    GlobalReifyFailed { name: TypeName },
+
+    /// owned state type {0} has no assignments.
+    NoAssignments(AssignmentsType),
+
+    /// owned state type {0} has too many assignments.
+    TooManyAssignments(AssignmentsType),
+
+    /// operation has too many owned state types.
+    TooManyAssignmentTypes,
+
+    /// owned state type {0} is assigned to a seal pointing to the witness
+    /// transaction, which is not possible for genesis and state extensions.
+    WitnessSeal(AssignmentsType),
+
+    #[from]
+    #[display(inner)]
+    Forge(ForgeError),
+
+    #[display(inner)]
+    Builder(Box<BuilderError>),
+}
+
+impl From<BuilderError> for IssueError {
+    fn from(err: BuilderError) -> Self {
+        match err {
+            BuilderError::Issue(err) => err,
+            err => IssueError::Builder(Box::new(err)),
+        }
+    }
+}
+
+/// Issues contract in a single call, adding global state with the provided
+/// strict-serialized values and allocating fungible state to the outpoints.
+///
+/// This is a shortcut for the [`ContractBuilder`], which should be used when
+/// more control over the issued contract is required.
+pub fn issue(
     iface: Iface,
+    schema: SubSchema,
     iimpl: IfaceImpl,
-
     chain: Chain,
-    global: GlobalState,
-    // rights: TinyOrdMap<AssignmentsType, Confined<BTreeSet<Outpoint>, 1, U8>>,
-    fungible: TinyOrdMap<AssignmentsType, Confined<BTreeMap<Outpoint, fungible::Revealed>, 1, U8>>,
-    // data: TinyOrdMap<AssignmentsType, Confined<BTreeMap<Outpoint, SmallBlob>, 1, U8>>,
-    // TODO: add attachments
-    // TODO: add valencies
+    globals: &[(TypeName, SmallBlob)],
+    allocations: &[(TypeName, Outpoint, u64)],
+) -> Result<Contract, IssueError> {
+    let mut builder = ContractBuilder::with(iface, schema, iimpl)?.set_chain(chain);
+    for (name, data) in globals {
+        builder.push_global_state(name.clone(), data.clone())?;
+    }
+    for (name, outpoint, value) in allocations {
+        builder.push_fungible_state(name.clone(), SealSpec::from(*outpoint), *value)?;
+    }
+    builder.issue_contract()
 }
 
-impl ContractBuilder {
-    pub fn with(iface: Iface, schema: SubSchema, iimpl: IfaceImpl) -> Result<Self, ForgeError> {
-        if iimpl.iface_id != iface.iface_id() {
-            return Err(ForgeError::InterfaceMismatch);
-        }
-        if iimpl.schema_id != schema.schema_id() {
-            return Err(ForgeError::SchemaMismatch);
-        }
+type FungibleAssignments =
+    TinyOrdMap<AssignmentsType, Confined<BTreeMap<GraphSeal, fungible::Revealed>, 1, U8>>;
+type AttachAssignments =
+    TinyOrdMap<AssignmentsType, Confined<BTreeMap<GraphSeal, attachment::Revealed>, 1, U8>>;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Typestate of a [`ContractBuilder`], defining which operations are available
+/// on the builder at compile time.
+pub trait BuilderState: sealed::Sealed + Clone + Debug {
+    /// State of the builder after the chain is set.
+    type WithChain: IssueState;
+}
+
+/// [`BuilderState`] which allows issuing contracts.
+pub trait IssueState: BuilderState {}
+
+/// State of a [`ContractBuilder`] which doesn't know the chain of the contract
+/// yet. The builder must be moved into the [`Ready`] state with
+/// [`ContractBuilder::set_chain`] before the contract can be issued.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct NeedsChain;
+
+/// State of a [`ContractBuilder`] which has the chain set and can issue the
+/// contract.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Ready;
+
+/// State of a [`ContractBuilder`] for dynamic flows, where it is not known at
+/// compile time whether the chain will be provided. Issuing contract from a
+/// dynamic builder without a chain fails at runtime with
+/// [`IssueError::ChainNotSet`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Dynamic;
+
+impl sealed::Sealed for NeedsChain {}
+impl sealed::Sealed for Ready {}
+impl sealed::Sealed for Dynamic {}
+
+impl BuilderState for NeedsChain {
+    type WithChain = Ready;
+}
+impl BuilderState for Ready {
+    type WithChain = Ready;
+}
+impl BuilderState for Dynamic {
+    type WithChain = Dynamic;
+}
+
+impl IssueState for Ready {}
+impl IssueState for Dynamic {}
+
+/// Builder for contract genesis.
+///
+/// The builder tracks at compile time whether the chain of the contract was
+/// set, such that a contract can't be issued on a chain chosen by mistake:
+///
+/// ```ignore
+/// let builder = ContractBuilder::with(iface, schema, iimpl)? // ContractBuilder<NeedsChain>
+///     .add_global_state("Nominal", nominal)?
+///     .add_fungible_state("Assets", outpoint, 100_000)?;
+/// // builder.issue_contract() doesn't compile here
+/// let contract = builder
+///     .set_chain(Chain::Testnet3) // ContractBuilder<Ready>
+///     .issue_contract()?;
+/// ```
+///
+/// Flows where the chain is known only at runtime may convert the builder
+/// into [`Dynamic`] state with [`ContractBuilder::into_dynamic`]:
+///
+/// ```ignore
+/// let mut builder = ContractBuilder::with(iface, schema, iimpl)?.into_dynamic();
+/// if let Some(name) = args.chain {
+///     builder = builder.set_chain_str(&name)?;
+/// }
+/// let contract = builder.issue_contract()?; // may fail with IssueError::ChainNotSet
+/// ```
+#[derive(Clone, Debug)]
+pub struct ContractBuilder<S: BuilderState = NeedsChain> {
+    builder: OperationBuilder,
+    chain: Option<Chain>,
+    anchor_height: Option<u32>,
+    category: Option<TinyString>,
+    /// Issuer-side tags of the fungible allocations, which are not committed
+    /// to by the genesis.
+    tags: BTreeMap<GraphSeal, String>,
+    /// Builder is constructed with [`ContractBuilder::with_schema_only`], and
+    /// the issued contract must not contain its internal interface.
+    schema_only: bool,
+    seal_network: Option<SealNetwork>,
+    /// Seals are not checked against the chain of the contract, see
+    /// [`ContractBuilder::allow_foreign_seals`].
+    foreign_seals: bool,
+    state: PhantomData<S>,
+}
 
-        // TODO: check schema internal consistency
-        // TODO: check interface internal consistency
-        // TODO: check implmenetation internal consistency
+/// Event emitted by the [`ContractBuilder`] to the handler set with
+/// [`ContractBuilder::on_event`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum BuilderEvent {
+    /// Value of the global state was added.
+    GlobalAdded(TypeName),
+    /// Owned state was assigned to the seal.
+    AllocationAdded { name: TypeName, seal: GraphSeal },
+    /// Valency was added.
+    ValencyAdded(TypeName),
+    /// Contract genesis was issued.
+    Issued(ContractId),
+}
+
+/// Handler of the [`BuilderEvent`]s, which is shared by the clones of the
+/// builder.
+#[derive(Clone)]
+struct EventHandler(Arc<Mutex<dyn FnMut(BuilderEvent) + Send>>);
+
+impl Debug for EventHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventHandler").finish_non_exhaustive()
+    }
+}
+
+/// Function detecting the chain of the transaction outputs used as seals, set
+/// with [`ContractBuilder::with_seal_network`].
+#[derive(Clone)]
+struct SealNetwork(Arc<dyn Fn(Outpoint) -> Option<Chain> + Send + Sync>);
+
+impl Debug for SealNetwork {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SealNetwork").finish_non_exhaustive()
+    }
+}
+
+/// Genesis composed from the builder state together with its contract id,
+/// which is reused until the state is modified. Unlike the event handler, the
+/// cache is not shared by the clones of the builder.
+#[derive(Default)]
+struct GenesisCache(Mutex<Option<(ContractId, Genesis)>>);
+
+impl Clone for GenesisCache {
+    fn clone(&self) -> Self { GenesisCache(Mutex::new(self.lock().clone())) }
+}
+
+impl Debug for GenesisCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GenesisCache").field(&self.contract_id()).finish()
+    }
+}
+
+impl GenesisCache {
+    fn lock(&self) -> MutexGuard<Option<(ContractId, Genesis)>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn contract_id(&self) -> Option<ContractId> { self.lock().as_ref().map(|(id, _)| *id) }
+
+    fn set(&self, contract_id: ContractId, genesis: Genesis) {
+        *self.lock() = Some((contract_id, genesis));
+    }
+
+    fn take(&mut self) -> Option<(ContractId, Genesis)> { self.lock().take() }
+
+    fn invalidate(&mut self) { *self.lock() = None; }
+}
+
+/// Fungible allocation of the issued genesis together with its secrets and
+/// the issuer-side information which is not a part of the genesis.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct AllocationSecret {
+    pub assignment_type: AssignmentsType,
+    pub seal: GraphSeal,
+    pub state: fungible::Revealed,
+    /// Tag provided with [`ContractBuilder::add_fungible_state_tagged`].
+    pub tag: Option<String>,
+}
 
+impl ContractBuilder<NeedsChain> {
+    pub fn with(iface: Iface, schema: SubSchema, iimpl: IfaceImpl) -> Result<Self, ForgeError> {
         Ok(ContractBuilder {
-            schema,
-            iface,
-            iimpl,
+            builder: OperationBuilder::with(iface, schema, iimpl)?,
+            chain: None,
+            anchor_height: None,
+            category: None,
+            tags: none!(),
+            schema_only: false,
+            seal_network: None,
+            foreign_seals: false,
+            state: PhantomData,
+        })
+    }
 
-            chain: default!(),
-            global: none!(),
-            fungible: none!(),
+    /// Constructs builder issuing contract under the schema without any
+    /// interface. The state is added with the `*_by_id` methods taking the
+    /// numeric state types defined by the schema, and is checked exactly as
+    /// the state added via an interface.
+    ///
+    /// The issued [`Contract`] has no interfaces; they may be attached later
+    /// with [`Contract::attach_iface`].
+    pub fn with_schema_only(schema: SubSchema) -> Result<Self, ForgeError> {
+        let (iface, iimpl) = schema_iface(&schema);
+        let mut builder = Self::with(iface, schema, iimpl)?;
+        builder.schema_only = true;
+        Ok(builder)
+    }
+}
+
+/// Constructs interface and its implementation exposing all state and
+/// operations defined by the schema under names derived from their type ids,
+/// which is used by the builder in the schema-only mode.
+fn schema_iface(schema: &SubSchema) -> (Iface, IfaceImpl) {
+    fn name(prefix: &str, id: u16) -> TypeName {
+        TypeName::try_from(format!("{prefix}{id}")).expect("valid type name")
+    }
+
+    let global_state = schema
+        .global_types
+        .iter()
+        .map(|(id, ty)| (name("Global", *id), Req::optional(ty.sem_id)));
+    let owned_state = schema.owned_types.iter().map(|(id, ty)| {
+        let owned = match ty {
+            StateSchema::Declarative => OwnedIface::Rights,
+            StateSchema::Fungible(_) => OwnedIface::Amount,
+            StateSchema::Structured(sem_id) => OwnedIface::Data(*sem_id),
+            StateSchema::Attachment => OwnedIface::AnyAttach,
+        };
+        (name("Owned", *id), owned)
+    });
+    let valencies = schema.valency_types.iter().map(|id| {
+        (name("Valency", *id), Req {
+            info: (),
+            required: false,
+        })
+    });
+    let typed = |prefix: &str, types: &TinyOrdMap<u16, Occurrences>| -> TypeReqMap {
+        Confined::try_from_iter(types.iter().map(|(id, occ)| (name(prefix, *id), occ.clone())))
+            .expect("same size as schema")
+    };
+    let valency = |types: &TinyOrdSet<ValencyType>| -> TypeReqMap {
+        Confined::try_from_iter(types.iter().map(|id| (name("Valency", *id), Occurrences::Once)))
+            .expect("same size as schema")
+    };
+    let transitions = schema.transitions.iter().map(|(id, transition)| {
+        (name("Transition", *id), TransitionIface {
+            metadata: transition.metadata,
+            globals: typed("Global", &transition.globals),
+            inputs: typed("Owned", &transition.inputs),
+            assignments: typed("Owned", &transition.assignments),
+            valencies: valency(&transition.valencies),
         })
+    });
+    let extensions = schema.extensions.iter().map(|(id, extension)| {
+        (name("Extension", *id), ExtensionIface {
+            metadata: extension.metadata,
+            globals: typed("Global", &extension.globals),
+            redeems: valency(&extension.redeems),
+            assignments: typed("Owned", &extension.assignments),
+            valencies: valency(&extension.valencies),
+        })
+    });
+    let iface = Iface {
+        name: tn!("SchemaState"),
+        global_state: Confined::try_from_iter(global_state).expect("same size as schema"),
+        owned_state: Confined::try_from_iter(owned_state).expect("same size as schema"),
+        valencies: Confined::try_from_iter(valencies).expect("same size as schema"),
+        genesis: GenesisIface {
+            metadata: None,
+            global: none!(),
+            assignments: none!(),
+            valencies: none!(),
+        },
+        transitions: Confined::try_from_iter(transitions).expect("same size as schema"),
+        extensions: Confined::try_from_iter(extensions).expect("same size as schema"),
+    };
+
+    let global_state = schema
+        .global_types
+        .keys()
+        .map(|id| NamedType::with(*id, name("Global", *id)));
+    let owned_state = schema
+        .owned_types
+        .keys()
+        .map(|id| NamedType::with(*id, name("Owned", *id)));
+    let valencies = schema
+        .valency_types
+        .iter()
+        .map(|id| NamedType::with(*id, name("Valency", *id)));
+    let transitions = schema
+        .transitions
+        .keys()
+        .map(|id| NamedType::with(*id, name("Transition", *id)));
+    let extensions = schema
+        .extensions
+        .keys()
+        .map(|id| NamedType::with(*id, name("Extension", *id)));
+    let iimpl = IfaceImpl {
+        version: 0,
+        schema_id: schema.schema_id(),
+        iface_id: iface.iface_id(),
+        global_state: Confined::try_from_iter(global_state).expect("same size as schema"),
+        owned_state: Confined::try_from_iter(owned_state).expect("same size as schema"),
+        valencies: Confined::try_from_iter(valencies).expect("same size as schema"),
+        transitions: Confined::try_from_iter(transitions).expect("same size as schema"),
+        extensions: Confined::try_from_iter(extensions).expect("same size as schema"),
+        chains: none!(),
+        close_methods: none!(),
+        media_types: none!(),
+    };
+    (iface, iimpl)
+}
+
+impl<S: BuilderState> ContractBuilder<S> {
+    fn into_state<S2: BuilderState>(self) -> ContractBuilder<S2> {
+        ContractBuilder {
+            builder: self.builder,
+            chain: self.chain,
+            anchor_height: self.anchor_height,
+            category: self.category,
+            tags: self.tags,
+            schema_only: self.schema_only,
+            seal_network: self.seal_network,
+            foreign_seals: self.foreign_seals,
+            state: PhantomData,
+        }
     }
 
-    pub fn set_chain(mut self, chain: Chain) -> Self {
-        self.chain = chain;
-        self
+    /// Converts builder into [`Dynamic`] state, where presence of the chain
+    /// is checked at runtime.
+    pub fn into_dynamic(self) -> ContractBuilder<Dynamic> { self.into_state() }
+
+    pub fn set_chain(mut self, chain: Chain) -> ContractBuilder<S::WithChain> {
+        self.chain = Some(chain);
+        self.builder.genesis.invalidate();
+        self.into_state()
+    }
+
+    /// Sets the chain from its human-readable name, as it is provided by
+    /// command-line tools. Names are case-insensitive and include common
+    /// aliases, like "mainnet" or "testnet3".
+    pub fn set_chain_str(self, s: &str) -> Result<ContractBuilder<S::WithChain>, BuilderError> {
+        let chain = match s.to_lowercase().as_str() {
+            "bitcoin" | "mainnet" | "main" | "bc" => Chain::Bitcoin,
+            "testnet" | "testnet3" | "test" | "tb" => Chain::Testnet3,
+            "signet" => Chain::Signet,
+            "regtest" | "bcrt" => Chain::Regtest,
+            _ => return Err(BuilderError::UnknownChain(s.to_owned())),
+        };
+        Ok(self.set_chain(chain))
+    }
+
+    /// Sets category of the contract, which is used by wallets for grouping
+    /// contracts in the user interface (like "stablecoins" or "collectibles").
+    ///
+    /// The category is a wallet-side metadata: it is kept in the
+    /// [`Contract`] container, but is not committed to by the genesis and
+    /// doesn't affect the contract id.
+    pub fn set_category(mut self, category: &str) -> Result<Self, BuilderError> {
+        self.category = Some(TinyString::try_from(category.to_owned())?);
+        Ok(self)
     }
 
+    /// Adds value of the global state. Repeated calls with the same name
+    /// append values in the order of the calls, up to the maximum number of
+    /// items allowed by the schema for the state type.
     pub fn add_global_state(
         mut self,
         name: impl Into<TypeName>,
         value: impl StrictSerialize,
     ) -> Result<Self, BuilderError> {
         let name = name.into();
-        let serialized = value.to_strict_serialized::<{ u16::MAX as usize }>()?;
+        let data = OperationBuilder::serialize_global(&name, value)?;
+        self.push_global_state(name, data)?;
+        Ok(self)
+    }
 
-        // Check value matches type requirements
-        let Some(id) = self.iimpl.global_state.iter().find(|t| t.name == name).map(|t| t.id) else {
+    /// Adds value of the global state like [`Self::add_global_state`], first
+    /// checking that the strict type of the value matches the type required
+    /// by the schema. Values of types which semantic id can't be computed
+    /// (like primitive types without a name) are checked only when the
+    /// contract is issued.
+    pub fn add_global_typed<T: StrictSerialize + StrictDumb>(
+        self,
+        name: impl Into<TypeName>,
+        value: T,
+    ) -> Result<Self, BuilderError> {
+        let name = name.into();
+        let Some(id) = self.builder.iimpl.global_type(&name) else {
             return Err(BuilderError::TypeNotFound(name));
         };
-        let ty_id = self
-            .schema
-            .global_types
-            .get(&id)
-            .expect("schema should match interface: must be checked by the constructor")
-            .sem_id;
-        self.schema.type_system.reify(ty_id, &serialized)?;
+        let Some(expected) = self.builder.schema.global_types.get(&id).map(|ty| ty.sem_id) else {
+            return Err(BuilderError::TypeIdNotFound(id));
+        };
+        match Self::strict_sem_id::<T>() {
+            Some(got) if got != expected => Err(BuilderError::TypeMismatch { expected, got }),
+            _ => self.add_global_state(name, value),
+        }
+    }
 
-        self.global.add_state(id, serialized.into())?;
+    fn strict_sem_id<T: StrictEncode + StrictDumb>() -> Option<SemId> {
+        let name = T::strict_name()?;
+        let lib = LibBuilder::new(libname!(T::STRICT_LIB_NAME))
+            .process::<T>()
+            .ok()?
+            .compile(none!())
+            .ok()?;
+        let sys = SystemBuilder::new().import(lib).ok()?.finalize().ok()?;
+        sys.id_by_name(&format!("{}.{name}", T::STRICT_LIB_NAME))
+    }
 
-        Ok(self)
+    /// Non-consuming version of [`Self::add_global_state`], taking the value
+    /// of the state in strict-serialized form.
+    pub fn push_global_state(
+        &mut self,
+        name: TypeName,
+        data: SmallBlob,
+    ) -> Result<(), BuilderError> {
+        self.check_global_declared(&name)?;
+        self.builder.add_global_data(name, data)
     }
 
-    pub fn add_fungible_state(
-        mut self,
-        name: impl Into<TypeName>,
-        seal: impl Into<Outpoint>,
-        value: u64,
-    ) -> Result<Self, BuilderError> {
-        let name = name.into();
+    fn check_global_declared(&self, name: &TypeName) -> Result<(), BuilderError> {
+        let declared = self.builder.schema.genesis.globals.keys();
+        self.builder.check_global_declared(name, declared)
+    }
 
-        let Some(id) = self.iimpl.owned_state.iter().find(|t| t.name == name).map(|t| t.id) else {
+    /// Describes type of the global state, such that a user interface can
+    /// generate a form for entering the state value.
+    pub fn global_type(&self, name: impl Into<TypeName>) -> Result<SemTypeInfo, BuilderError> {
+        let name = name.into();
+        let Some(id) = self.builder.iimpl.global_type(&name) else {
             return Err(BuilderError::TypeNotFound(name));
         };
         let ty = self
+            .builder
             .schema
-            .owned_types
+            .global_types
             .get(&id)
             .expect("schema should match interface: must be checked by the constructor");
-        if *ty != StateSchema::Fungible(FungibleType::Unsigned64Bit) {
-            return Err(BuilderError::InvalidStateType(name));
-        }
+        Ok(SemTypeInfo::with(&self.builder.schema.type_system, ty.sem_id))
+    }
 
-        let state = fungible::Revealed::new(value, &mut thread_rng());
-        match self.fungible.get_mut(&id) {
-            Some(assignments) => {
-                assignments.insert(seal.into(), state)?;
-            }
-            None => {
-                self.fungible
-                    .insert(id, Confined::with((seal.into(), state)))?;
+    /// Adds fungible state assigned to the explicit seal, which can be given
+    /// as a [`bp::Outpoint`] or a parsed [`SealSpec`]. Seals without close
+    /// method use the method preferred by the interface implementation.
+    pub fn add_fungible_state(
+        mut self,
+        name: impl Into<TypeName>,
+        seal: impl Into<SealSpec>,
+        value: u64,
+    ) -> Result<Self, BuilderError> {
+        self.push_fungible_state(name.into(), seal.into(), value)?;
+        Ok(self)
+    }
+
+    /// Adds fungible state allocations from CSV data with `seal,amount` rows.
+    /// Seals use the [`SealSpec`] format and amounts are decimal numbers of
+    /// the asset units with the given precision. The first row is skipped if
+    /// it is a header; empty lines are ignored.
+    pub fn add_fungible_from_csv(
+        mut self,
+        name: impl Into<TypeName>,
+        csv: &str,
+        precision: Precision,
+    ) -> Result<Self, BuilderError> {
+        let name = name.into();
+        let mut header_allowed = true;
+        for (no, line) in csv.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
             }
+            let malformed = |col: usize, details: String| BuilderError::Csv {
+                line: no + 1,
+                col,
+                details,
+            };
+            let fields = csv_fields(line);
+            let [(seal_col, seal), (amount_col, amount)] = fields[..] else {
+                let col = fields.get(2).map(|(col, _)| *col).unwrap_or(line.len() + 1);
+                let details = format!("expected two fields, found {}", fields.len());
+                return Err(malformed(col, details));
+            };
+            let (seal, amount) = match (seal.parse::<SealSpec>(), precision.parse_amount(amount)) {
+                (Ok(seal), Ok(amount)) => (seal, amount),
+                (Err(_), Err(_)) if header_allowed => {
+                    header_allowed = false;
+                    continue;
+                }
+                (Err(err), _) => return Err(malformed(seal_col, err.to_string())),
+                (_, Err(err)) => return Err(malformed(amount_col, err.to_string())),
+            };
+            header_allowed = false;
+            self.push_fungible_state(name.clone(), seal, amount)?;
         }
         Ok(self)
     }
 
-    pub fn issue_contract(self) -> Result<Contract, IssueError> {
-        let owned_state = self.fungible.into_iter().map(|(id, vec)| {
-            let vec = vec.into_iter().map(|(seal, value)| Assign::Revealed {
-                seal: seal.into(),
-                state: value,
-            });
-            let state = Confined::try_from_iter(vec).expect("at least one element");
-            let state = TypedAssigns::Fungible(state);
-            (id, state)
-        });
-        let owned_state = Confined::try_from_iter(owned_state).expect("same size");
-        let assignments = Assignments::from_inner(owned_state);
+    /// Non-consuming version of [`Self::add_fungible_state`].
+    pub fn push_fungible_state(
+        &mut self,
+        name: TypeName,
+        seal: SealSpec,
+        value: u64,
+    ) -> Result<(), BuilderError> {
+        // Seal blinding is defined at the moment the state is added, such that
+        // the genesis assembled from the builder is always the same.
+        let seal = self.graph_seal(seal, None)?;
+        self.builder.add_fungible_state(name, seal, value)
+    }
 
-        let genesis = Genesis {
-            ffv: none!(),
-            schema_id: self.schema.schema_id(),
-            chain: self.chain,
-            metadata: None,
-            globals: self.global,
-            assignments,
-            valencies: none!(),
-        };
+    /// Adds fungible state like [`Self::add_fungible_state`], labelling the
+    /// allocation with a tag for the issuer bookkeeping. The tag is not
+    /// committed to by the genesis and is returned only by
+    /// [`ContractBuilder::issue_contract_with_secrets`].
+    pub fn add_fungible_state_tagged(
+        mut self,
+        name: impl Into<TypeName>,
+        seal: impl Into<SealSpec>,
+        value: u64,
+        tag: &str,
+    ) -> Result<Self, BuilderError> {
+        self.push_fungible_state_tagged(name.into(), seal.into(), value, tag)?;
+        Ok(self)
+    }
 
-        // TODO: Validate against schema
+    /// Non-consuming version of [`Self::add_fungible_state_tagged`].
+    pub fn push_fungible_state_tagged(
+        &mut self,
+        name: TypeName,
+        seal: SealSpec,
+        value: u64,
+        tag: &str,
+    ) -> Result<(), BuilderError> {
+        let seal = self.graph_seal(seal, None)?;
+        self.builder.add_fungible_state(name, seal, value)?;
+        self.tags.insert(seal, tag.to_owned());
+        Ok(())
+    }
+
+    /// Adds fungible state assigned to the explicit seal which must be closed
+    /// with the specific method.
+    pub fn add_fungible_state_with_method(
+        mut self,
+        name: impl Into<TypeName>,
+        seal: impl Into<SealSpec>,
+        value: u64,
+        method: CloseMethod,
+    ) -> Result<Self, BuilderError> {
+        self.push_fungible_state_with_method(name.into(), seal.into(), value, method)?;
+        Ok(self)
+    }
+
+    /// Non-consuming version of [`Self::add_fungible_state_with_method`].
+    pub fn push_fungible_state_with_method(
+        &mut self,
+        name: TypeName,
+        seal: SealSpec,
+        value: u64,
+        method: CloseMethod,
+    ) -> Result<(), BuilderError> {
+        let seal = self.graph_seal(seal, Some(method))?;
+        self.builder.add_fungible_state(name, seal, value)
+    }
+
+    /// Adds fungible state with the blinding factor provided externally,
+    /// together with the Pedersen commitment the state must open. The state
+    /// is not accepted if it doesn't match the commitment.
+    pub fn add_fungible_revealed(
+        mut self,
+        name: impl Into<TypeName>,
+        seal: impl Into<SealSpec>,
+        state: fungible::Revealed,
+        commitment: PedersenCommitment,
+    ) -> Result<Self, BuilderError> {
+        self.push_fungible_revealed(name.into(), seal.into(), state, commitment)?;
+        Ok(self)
+    }
+
+    /// Non-consuming version of [`Self::add_fungible_revealed`].
+    pub fn push_fungible_revealed(
+        &mut self,
+        name: TypeName,
+        seal: SealSpec,
+        state: fungible::Revealed,
+        commitment: PedersenCommitment,
+    ) -> Result<(), BuilderError> {
+        let seal = self.graph_seal(seal, None)?;
+        self.builder.add_fungible_verified(name, seal, state, commitment)
+    }
+
+    /// Adds fungible state with both the seal and the state blinding factors
+    /// provided by the caller, which is used to issue reproducible contracts.
+    #[cfg(any(test, feature = "fixtures"))]
+    pub(crate) fn add_fungible_seal(
+        mut self,
+        name: impl Into<TypeName>,
+        seal: GraphSeal,
+        state: fungible::Revealed,
+    ) -> Result<Self, BuilderError> {
+        self.builder.add_fungible_revealed(name, seal, state)?;
+        Ok(self)
+    }
+
+    /// Adds attachment of the data with the given id and media type to the
+    /// explicit seal. The media type must be allowed for the attachment
+    /// state by the interface implementation.
+    pub fn add_attachment(
+        mut self,
+        name: impl Into<TypeName>,
+        seal: impl Into<SealSpec>,
+        attach_id: AttachId,
+        media_type: MediaType,
+    ) -> Result<Self, BuilderError> {
+        self.push_attachment(name.into(), seal.into(), attach_id, media_type)?;
+        Ok(self)
+    }
 
-        Ok(Contract::new(
-            self.schema.clone(),
-            IfacePair::with(self.iface.clone(), self.iimpl),
-            genesis,
-        ))
+    /// Non-consuming version of [`Self::add_attachment`].
+    pub fn push_attachment(
+        &mut self,
+        name: TypeName,
+        seal: SealSpec,
+        attach_id: AttachId,
+        media_type: MediaType,
+    ) -> Result<(), BuilderError> {
+        let seal = self.graph_seal(seal, None)?;
+        self.builder.add_attachment(name, seal, attach_id, media_type)
+    }
+
+    /// Returns number of seals to which the owned state `name` can still be
+    /// assigned, or `None` if the state is not known to the schema or the
+    /// interface implementation.
+    pub fn remaining_capacity(&self, name: impl Into<TypeName>) -> Option<usize> {
+        let id = self.builder.iimpl.assignments_type(&name.into())?;
+        if !self.builder.schema.owned_types.contains_key(&id) {
+            return None;
+        }
+        let used = self
+            .builder
+            .fungible
+            .get(&id)
+            .map(|assignments| assignments.len())
+            .or_else(|| self.builder.attachments.get(&id).map(|assignments| assignments.len()))
+            .unwrap_or_default();
+        Some(U8 - used)
+    }
+
+    fn graph_seal(
+        &self,
+        seal: SealSpec,
+        method: Option<CloseMethod>,
+    ) -> Result<GraphSeal, BuilderError> {
+        let method = match (seal.close_method(), method) {
+            (Some(specified), Some(method)) if specified != method => {
+                return Err(BuilderError::CloseMethodMismatch(seal, method));
+            }
+            (specified, method) => specified
+                .or(method)
+                .unwrap_or_else(|| self.builder.iimpl.preferred_method()),
+        };
+        if let SealSpec::Explicit { outpoint, .. } = seal {
+            self.check_seal_network(seal, outpoint)?;
+        }
+        seal.to_graph_seal(method).ok_or(BuilderError::BlindedSeal(seal))
+    }
+
+    fn check_seal_network(&self, seal: SealSpec, outpoint: Outpoint) -> Result<(), BuilderError> {
+        let (Some(expected), Some(network)) = (self.chain, &self.seal_network) else {
+            return Ok(());
+        };
+        if self.foreign_seals {
+            return Ok(());
+        }
+        match (network.0)(outpoint) {
+            Some(found) if found != expected => Err(BuilderError::SealWrongNetwork {
+                seal,
+                expected,
+                found,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sets function detecting the chain of the transaction outputs, for
+    /// instance from the addresses of the wallet they belong to. Outpoints
+    /// don't carry any information about their network, thus without such
+    /// function the seals are not checked.
+    ///
+    /// Once the chain of the builder is set, explicit seals of all state
+    /// added to the builder are checked to belong to the same chain, failing
+    /// with [`BuilderError::SealWrongNetwork`] otherwise. Seals for which the
+    /// function returns `None` are not checked.
+    pub fn with_seal_network(
+        mut self,
+        f: impl Fn(Outpoint) -> Option<Chain> + Send + Sync + 'static,
+    ) -> Self {
+        self.seal_network = Some(SealNetwork(Arc::new(f)));
+        self
+    }
+
+    /// Disables checks of the seal chain set with [`Self::with_seal_network`],
+    /// for unusual setups where outputs of one network are used for the
+    /// contracts issued on another, like custom regtest deployments.
+    pub fn allow_foreign_seals(mut self) -> Self {
+        self.foreign_seals = true;
+        self
+    }
+
+    pub fn add_valency(mut self, name: impl Into<TypeName>) -> Result<Self, BuilderError> {
+        self.push_valency(name.into())?;
+        Ok(self)
+    }
+
+    /// Sets handler of the events emitted when state is added to the builder
+    /// and when the contract is issued. Clones of the builder share the same
+    /// handler. Without a handler no events are constructed.
+    pub fn on_event(mut self, f: impl FnMut(BuilderEvent) + Send + 'static) -> Self {
+        self.builder.events = Some(EventHandler(Arc::new(Mutex::new(f))));
+        self
+    }
+
+    /// Replaces type system of the schema, for instance with one extending it
+    /// with the types required by a new version of the interface. Since the
+    /// type system is a part of the schema, the contract is issued under a
+    /// different schema id. Global state added before is checked against the
+    /// new type system when the contract is issued.
+    pub fn with_type_system(mut self, type_system: TypeSystem) -> Self {
+        self.builder.schema.type_system = type_system;
+        self.builder.reified.clear();
+        self.builder.genesis.invalidate();
+        self
+    }
+
+    /// Declares group of the interface global state which are mutually
+    /// exclusive, like the ones describing fixed and inflatable supply. Once
+    /// one global state of the group is set, setting any other of them fails
+    /// with [`BuilderError::MutuallyExclusive`].
+    ///
+    /// The groups express business rules of the interface which can't be
+    /// expressed by the schema; they are not a part of the issued contract.
+    pub fn with_exclusive_globals(
+        mut self,
+        names: impl IntoIterator<Item = impl Into<TypeName>>,
+    ) -> Result<Self, BuilderError> {
+        let group = names.into_iter().map(Into::into).collect::<BTreeSet<_>>();
+        if let Some(name) = group
+            .iter()
+            .find(|name| !self.builder.iface.global_state.contains_key(*name))
+        {
+            return Err(BuilderError::TypeNotFound(name.clone()));
+        }
+        self.builder.exclusive.push(group);
+        Ok(self)
+    }
+
+    /// Non-consuming version of [`Self::add_valency`].
+    pub fn push_valency(&mut self, name: TypeName) -> Result<(), BuilderError> {
+        self.builder.add_valency(name)
+    }
+
+    /// Adds value of the global state of the given schema type, like
+    /// [`Self::add_global_state`] does for the interface state names.
+    pub fn add_global_state_by_id(
+        self,
+        ty: GlobalStateType,
+        value: impl StrictSerialize,
+    ) -> Result<Self, BuilderError> {
+        let name = OperationBuilder::type_name(&self.builder.iimpl.global_state, ty)?;
+        self.add_global_state(name, value)
+    }
+
+    /// Adds fungible state of the given schema type, like
+    /// [`Self::add_fungible_state`] does for the interface state names.
+    pub fn add_fungible_state_by_id(
+        self,
+        ty: AssignmentsType,
+        seal: impl Into<SealSpec>,
+        value: u64,
+    ) -> Result<Self, BuilderError> {
+        let name = OperationBuilder::type_name(&self.builder.iimpl.owned_state, ty)?;
+        self.add_fungible_state(name, seal, value)
+    }
+
+    /// Adds attachment of the given schema type, like [`Self::add_attachment`]
+    /// does for the interface state names.
+    pub fn add_attachment_by_id(
+        self,
+        ty: AssignmentsType,
+        seal: impl Into<SealSpec>,
+        attach_id: AttachId,
+        media_type: MediaType,
+    ) -> Result<Self, BuilderError> {
+        let name = OperationBuilder::type_name(&self.builder.iimpl.owned_state, ty)?;
+        self.add_attachment(name, seal, attach_id, media_type)
+    }
+
+    /// Adds valency of the given schema type, like [`Self::add_valency`] does
+    /// for the interface valency names.
+    pub fn add_valency_by_id(self, ty: ValencyType) -> Result<Self, BuilderError> {
+        let name = OperationBuilder::type_name(&self.builder.iimpl.valencies, ty)?;
+        self.add_valency(name)
+    }
+
+    /// Sets the contract terms document, keeping it in the `ContractText`
+    /// global state of the interface.
+    ///
+    /// If the schema defines the state as [`ContractTerms`], the terms are
+    /// stored together with the optional signature; otherwise they are stored
+    /// as a plain [`ContractText`] and the signature is not allowed.
+    pub fn set_terms(mut self, terms: &str, sig: Option<TermsSig>) -> Result<Self, BuilderError> {
+        let name = tn!("ContractText");
+        let Some(id) = self.builder.iimpl.global_type(&name) else {
+            return Err(BuilderError::MetadataNotSupported(name));
+        };
+        let sem_id = self
+            .builder
+            .schema
+            .global_types
+            .get(&id)
+            .expect("schema should match interface: must be checked by the constructor")
+            .sem_id;
+        self.check_global_declared(&name)?;
+        let text = ContractText::try_from(terms.to_owned())?;
+        if sem_id == StandardTypes::new().get("RGBContract.ContractTerms") {
+            self.builder.add_global_state(name, ContractTerms { text, sig })?;
+        } else if sig.is_none() {
+            self.builder.add_global_state(name, text)?;
+        } else {
+            return Err(BuilderError::SigNotSupported(name));
+        }
+        Ok(self)
+    }
+
+    /// Sets the moment after which the contract expires, keeping it in the
+    /// `Expiry` global state of the interface.
+    ///
+    /// The expiry must be in the future: timestamps are compared with the
+    /// current system time, which is the time of the contract issue, and
+    /// block heights with the anchor height, if it is already set.
+    pub fn set_expiry(mut self, expiry: Expiry) -> Result<Self, BuilderError> {
+        let name = tn!("Expiry");
+        if self.builder.iimpl.global_type(&name).is_none() {
+            return Err(BuilderError::MetadataNotSupported(name));
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default();
+        if expiry.has_passed(self.anchor_height.unwrap_or_default(), now) {
+            return Err(BuilderError::ExpiryInPast(expiry));
+        }
+        self.check_global_declared(&name)?;
+        self.builder.add_global_state(name, expiry)?;
+        Ok(self)
+    }
+
+    /// Issues the same contract, with the same global state and allocations,
+    /// on each of the provided chains. All blinding factors are re-generated
+    /// for each of the chains, such that the issued contracts can't be linked
+    /// to each other basing on their owned state.
+    ///
+    /// NB: This is an experimental API for the research purposes; issuing the
+    /// same asset multiple times on different chains doesn't make the issued
+    /// contracts to link to each other in any way.
+    pub fn issue_for_chains(self, chains: &[Chain]) -> Result<Vec<Contract>, IssueError> {
+        if let Some(chain) = chains
+            .iter()
+            .find(|chain| !self.builder.iimpl.is_chain_allowed(**chain))
+        {
+            return Err(IssueError::ChainNotAllowed(*chain));
+        }
+        self.check_consistency()?;
+        chains
+            .iter()
+            .map(|chain| {
+                let mut builder = self.clone().set_chain(*chain);
+                builder.builder.reblind();
+                builder.issue_contract()
+            })
+            .collect()
+    }
+
+    fn check_consistency(&self) -> Result<(), IssueError> {
+        if let Some(chain) = self.chain {
+            if !self.builder.iimpl.is_chain_allowed(chain) {
+                return Err(IssueError::ChainNotAllowed(chain));
+            }
+        }
+
+        let schema = &self.builder.schema;
+        if let Some(id) = self
+            .builder
+            .fungible
+            .keys()
+            .chain(self.builder.attachments.keys())
+            .find(|id| !schema.owned_types.contains_key(id))
+        {
+            return Err(IssueError::UnknownAssignmentType(*id));
+        }
+
+        // Ensures the contract is fully describable by the interface, even if
+        // global state was added bypassing the name-based methods
+        if let Some(id) = self.builder.global.keys().find(|id| {
+            !schema.global_types.contains_key(id) ||
+                !self.builder.iimpl.global_state.iter().any(|nt| nt.id == **id)
+        }) {
+            return Err(IssueError::UnmappedGlobal(*id));
+        }
+
+        // The type system may be replaced after the state was added
+        for (id, values) in self.builder.global.iter() {
+            let sem_id = schema.global_types.get(id).expect("checked above").sem_id;
+            if values
+                .iter()
+                .any(|value| schema.type_system.reify(sem_id, value.as_ref()).is_err())
+            {
+                let name = OperationBuilder::type_name(&self.builder.iimpl.global_state, *id)
+                    .expect("checked above");
+                return Err(IssueError::GlobalReifyFailed { name });
+            }
+        }
+
+        // Schemata which don't require any global state allow issuing
+        // contracts with owned state only
+        if let Some(id) = schema.genesis.globals.iter().find_map(|(id, occurrences)| {
+            let present = self
+                .builder
+                .global
+                .get(id)
+                .map(|values| values.len())
+                .unwrap_or_default();
+            (present < occurrences.min_value() as usize).then_some(*id)
+        }) {
+            return Err(IssueError::GlobalStateAbsent(id));
+        }
+
+        // TODO: Validate against schema
+
+        Ok(())
+    }
+}
+
+/// Upper bound of the block height which the chain may have reached at the
+/// `now` UNIX timestamp, assuming blocks are mined at most twice as fast as
+/// the 10-minute target. Returns `None` for chains without such bound.
+fn max_height(chain: Chain, now: u64) -> Option<u32> {
+    let genesis_time = match chain {
+        Chain::Bitcoin => 1_231_006_505,
+        Chain::Signet => 1_598_918_400,
+        Chain::Testnet3 | Chain::Regtest => return None,
+    };
+    Some(u32::try_from(now.saturating_sub(genesis_time) / 300).unwrap_or(u32::MAX))
+}
+
+impl<S: IssueState> ContractBuilder<S> {
+    /// Sets height of the block from which the contract is expected to get
+    /// its first anchors. The height is not committed to by the genesis and
+    /// is kept in the [`Contract`] container as a hint for the light clients.
+    ///
+    /// The height must be set after the chain, since it is checked against
+    /// the chain the contract is issued on: it can't be the genesis block, and
+    /// for the mainnet and signet it can't exceed the height the chain could
+    /// have reached by the current system time. Testnet and regtest block
+    /// rates are unbounded, so for them only the genesis block is rejected.
+    pub fn set_anchor_height(mut self, height: u32) -> Result<Self, BuilderError> {
+        let chain = self.chain.ok_or(BuilderError::ChainNotSet)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        // Genesis block of any chain can't contain anchors
+        if height == 0 || matches!(max_height(chain, now), Some(max) if height > max) {
+            return Err(BuilderError::ImplausibleHeight(chain, height));
+        }
+        self.anchor_height = Some(height);
+        Ok(self)
+    }
+
+    /// Computes id of the contract which will be produced by
+    /// [`Self::issue_contract`] from the current builder state, without
+    /// consuming the builder.
+    ///
+    /// All blinding factors are generated at the moment the state is added to
+    /// the builder, thus the returned id matches the id of the issued contract
+    /// as long as no further state is added.
+    ///
+    /// The composed genesis is cached by the builder and reused by the
+    /// subsequent previews and the issue, unless the builder state is
+    /// modified in between.
+    pub fn contract_id_preview(&self) -> Result<ContractId, IssueError> {
+        if let Some(contract_id) = self.builder.genesis.contract_id() {
+            return Ok(contract_id);
+        }
+        self.check_consistency()?;
+        let genesis = self.compose_genesis(
+            self.builder.global.clone(),
+            self.builder.fungible.clone(),
+            self.builder.attachments.clone(),
+        )?;
+        let contract_id = genesis.contract_id();
+        self.builder.genesis.set(contract_id, genesis);
+        Ok(contract_id)
+    }
+
+    pub fn issue_contract(self) -> Result<Contract, IssueError> {
+        let schema = self.builder.schema.clone();
+        let iface_pair = IfacePair::with(self.builder.iface.clone(), self.builder.iimpl.clone());
+        let anchor_height = self.anchor_height;
+        let category = self.category.clone();
+        let schema_only = self.schema_only;
+        let genesis = self.issue_genesis()?;
+
+        let mut contract = Contract::new(schema, iface_pair, genesis);
+        contract.anchor_height = anchor_height;
+        contract.category = category;
+        if schema_only {
+            contract.ifaces = none!();
+        }
+        Ok(contract)
+    }
+
+    /// Issues contract like [`Self::issue_contract`], returning also the
+    /// secrets of all fungible allocations, including their tags.
+    pub fn issue_contract_with_secrets(
+        mut self,
+    ) -> Result<(Contract, Vec<AllocationSecret>), IssueError> {
+        let tags = mem::take(&mut self.tags);
+        let secrets = self
+            .builder
+            .fungible
+            .iter()
+            .flat_map(|(ty, assignments)| {
+                assignments.iter().map(|(seal, state)| AllocationSecret {
+                    assignment_type: *ty,
+                    seal: *seal,
+                    state: *state,
+                    tag: tags.get(seal).cloned(),
+                })
+            })
+            .collect();
+        let contract = self.issue_contract()?;
+        Ok((contract, secrets))
+    }
+
+    /// Performs the same checks as [`Self::issue_contract`], but returns just
+    /// the contract genesis without wrapping it with schema and interface
+    /// information into a [`Contract`].
+    pub fn issue_genesis(mut self) -> Result<Genesis, IssueError> {
+        let (contract_id, genesis) = match self.builder.genesis.take() {
+            Some(cached) => cached,
+            None => {
+                self.check_consistency()?;
+                let global = mem::take(&mut self.builder.global);
+                let fungible = mem::take(&mut self.builder.fungible);
+                let attachments = mem::take(&mut self.builder.attachments);
+                let genesis = self.compose_genesis(global, fungible, attachments)?;
+                (genesis.contract_id(), genesis)
+            }
+        };
+        self.builder.emit(|| BuilderEvent::Issued(contract_id));
+        Ok(genesis)
+    }
+
+    fn compose_genesis(
+        &self,
+        global: GlobalState,
+        fungible: FungibleAssignments,
+        attachments: AttachAssignments,
+    ) -> Result<Genesis, IssueError> {
+        Ok(Genesis {
+            ffv: none!(),
+            schema_id: self.builder.schema.schema_id(),
+            chain: self.chain.ok_or(IssueError::ChainNotSet)?,
+            metadata: None,
+            globals: global,
+            assignments: OperationBuilder::compose_assignments(
+                fungible,
+                attachments,
+                genesis_seal,
+            )?,
+            valencies: self.builder.valencies.clone(),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TransitionBuilder {
+    builder: OperationBuilder,
+    transition_type: TransitionType,
+    inputs: TinyOrdSet<Opout>,
+    input_amounts: BTreeMap<Opout, u64>,
+    /// Transition issues new fungible state, thus its outputs may exceed the
+    /// inputs of the same state type.
+    inflationary: bool,
+}
+
+/// Converts the seal to a genesis seal, which can't point to the witness
+/// transaction.
+fn genesis_seal(seal: GraphSeal) -> Option<GenesisSeal> {
+    match seal.txid {
+        TxPtr::Txid(txid) => Some(GenesisSeal::with_blinding(
+            seal.method,
+            txid,
+            seal.vout,
+            seal.blinding,
+        )),
+        TxPtr::WitnessTx => None,
+    }
+}
+
+/// Splits CSV line into trimmed fields, each with the 1-based column where
+/// its content starts.
+fn csv_fields(line: &str) -> Vec<(usize, &str)> {
+    let mut start = 0;
+    line.split(',')
+        .map(|field| {
+            let col = start + field.len() - field.trim_start().len() + 1;
+            start += field.len() + 1;
+            (col, field.trim())
+        })
+        .collect()
+}
+
+impl TransitionBuilder {
+    pub fn with(
+        iface: Iface,
+        schema: SubSchema,
+        iimpl: IfaceImpl,
+        transition_name: impl Into<TypeName>,
+    ) -> Result<Self, ForgeError> {
+        let name = transition_name.into();
+        let Some(transition_type) = iimpl.transition_type(&name) else {
+            return Err(ForgeError::TransitionNotFound(name));
+        };
+        Ok(TransitionBuilder {
+            transition_type,
+            inputs: none!(),
+            input_amounts: none!(),
+            inflationary: iface.inflates(&name),
+            builder: OperationBuilder::with(iface, schema, iimpl)?,
+        })
+    }
+
+    /// Adds input without knowing the amount of the state it spends.
+    ///
+    /// The balance of the fungible state can't be checked without the amount,
+    /// thus if the input spends fungible state, completing the transition
+    /// fails with [`BuilderError::UnknownInputAmount`]. Use
+    /// [`Self::add_fungible_input`] or [`Self::add_checked_input`] for such
+    /// inputs.
+    pub fn add_input(mut self, opout: Opout) -> Result<Self, BuilderError> {
+        self.inputs.push(opout)?;
+        Ok(self)
+    }
+
+    /// Adds input after checking that the previous operation provided by the
+    /// `provider` has the output of the state kind defined by the schema. If
+    /// the output reveals fungible state, its amount is taken into account by
+    /// [`Self::complete_with_change`] and the balance check.
+    pub fn add_checked_input(
+        mut self,
+        opout: Opout,
+        provider: &impl OperationProvider,
+    ) -> Result<Self, BuilderError> {
+        let op = provider
+            .operation(opout.op)
+            .ok_or(BuilderError::UnknownInput(opout))?;
+        let view = assign_views(op.assignments())
+            .into_iter()
+            .find(|view| view.ty == opout.ty && view.no == opout.no)
+            .ok_or(BuilderError::UnknownInput(opout))?;
+        let Some(state_schema) = self.builder.schema.owned_types.get(&opout.ty) else {
+            return Err(BuilderError::TypeIdNotFound(opout.ty));
+        };
+        if view.commitment.state_type() != state_schema.state_type() {
+            return Err(BuilderError::InputKindMismatch(opout));
+        }
+        if let Some(amount) = view.amount() {
+            self.input_amounts.insert(opout, amount);
+        }
+        self.inputs.push(opout)?;
+        Ok(self)
+    }
+
+    /// Adds input spending fungible state with the known amount, which is
+    /// taken into account by [`Self::complete_with_change`].
+    pub fn add_fungible_input(mut self, opout: Opout, amount: u64) -> Result<Self, BuilderError> {
+        self.inputs.push(opout)?;
+        self.input_amounts.insert(opout, amount);
+        Ok(self)
+    }
+
+    /// Completes the transition, assigning the change of the fungible state
+    /// `name` to the `change_seal`.
+    ///
+    /// The change is computed as the sum of the amounts of all the inputs of
+    /// the state type, which must be added with [`Self::add_fungible_input`],
+    /// minus the sum of the amounts assigned by the transition so far. If the
+    /// change is zero, no change assignment is added.
+    pub fn complete_with_change(
+        mut self,
+        name: impl Into<TypeName>,
+        change_seal: impl Into<GraphSeal>,
+    ) -> Result<Transition, BuilderError> {
+        let name = name.into();
+        let Some(type_id) = self.builder.iimpl.assignments_type(&name) else {
+            return Err(BuilderError::TypeNotFound(name));
+        };
+
+        let (inputs, outputs) = self.fungible_sums(type_id, &name)?;
+        match inputs.checked_sub(outputs) {
+            None => {
+                return Err(BuilderError::InsufficientInputs {
+                    name,
+                    deficit: outputs - inputs,
+                })
+            }
+            Some(0) => {}
+            Some(change) => {
+                self.builder.add_fungible_state(name, change_seal.into(), change)?;
+            }
+        }
+        self.complete_transition()
+    }
+
+    /// Completes the transition paying `amount` to the recipient seal and
+    /// assigning the change to the `change_seal`, like
+    /// [`Self::complete_with_change`]. The paid state is the only fungible
+    /// state which the interface allows the operation to assign.
+    ///
+    /// If the inputs can't cover the amount, the transition is rejected with
+    /// [`BuilderError::InsufficientInputs`].
+    pub fn pay(
+        self,
+        recipient_seal: impl Into<GraphSeal>,
+        amount: u64,
+        change_seal: impl Into<GraphSeal>,
+    ) -> Result<Transition, BuilderError> {
+        let name = self.fungible_name()?;
+        self.add_fungible_state(name.clone(), recipient_seal, amount)?
+            .complete_with_change(name, change_seal)
+    }
+
+    fn fungible_name(&self) -> Result<TypeName, BuilderError> {
+        let iface = &self.builder.iface;
+        let mut names = self
+            .builder
+            .iimpl
+            .transitions
+            .iter()
+            .filter(|nt| nt.id == self.transition_type)
+            .filter_map(|nt| iface.transitions.get(&nt.name))
+            .flat_map(|transition| transition.assignments.keys())
+            .filter(|name| iface.owned_kind(name) == Some(OwnedKind::Spendable));
+        match (names.next(), names.next()) {
+            (Some(name), None) => Ok(name.clone()),
+            _ => Err(BuilderError::NoFungibleState),
+        }
+    }
+
+    /// Sums amounts of the fungible state of the given type spent by the
+    /// inputs and assigned by the transition. Amounts of all the inputs of the
+    /// type must be known.
+    fn fungible_sums(
+        &self,
+        type_id: AssignmentsType,
+        name: &TypeName,
+    ) -> Result<(u64, u64), BuilderError> {
+        let mut inputs = 0u64;
+        for opout in self.inputs.iter().filter(|opout| opout.ty == type_id) {
+            let amount = self
+                .input_amounts
+                .get(opout)
+                .ok_or(BuilderError::UnknownInputAmount(*opout))?;
+            inputs = inputs
+                .checked_add(*amount)
+                .ok_or_else(|| BuilderError::AmountOverflow(name.clone()))?;
+        }
+        let mut outputs = 0u64;
+        for state in self.builder.fungible.get(&type_id).into_iter().flat_map(|a| a.values()) {
+            outputs = outputs
+                .checked_add(state.value.as_u64())
+                .ok_or_else(|| BuilderError::AmountOverflow(name.clone()))?;
+        }
+        Ok((inputs, outputs))
+    }
+
+    /// Checks that the transition doesn't create or destroy fungible state:
+    /// for each fungible state type the assigned amount must be equal to the
+    /// spent one. Inflationary transitions may assign more state of a type
+    /// than they spend, as long as the total assigned amount of all fungible
+    /// types doesn't exceed the total spent amount, which includes the
+    /// consumed issuance allowance. The amounts of all fungible inputs must be
+    /// known.
+    fn check_balance(&self) -> Result<(), BuilderError> {
+        let schema = &self.builder.schema;
+        let types = self
+            .inputs
+            .iter()
+            .map(|opout| opout.ty)
+            .chain(self.builder.fungible.keys().copied())
+            .filter(|ty| matches!(schema.owned_types.get(ty), Some(StateSchema::Fungible(_))))
+            .collect::<BTreeSet<_>>();
+
+        let mut total_inputs = 0u64;
+        let mut total_outputs = 0u64;
+        for type_id in types {
+            let name = OperationBuilder::type_name(&self.builder.iimpl.owned_state, type_id)?;
+            let (inputs, outputs) = self.fungible_sums(type_id, &name)?;
+            if !self.inflationary && inputs != outputs {
+                return Err(BuilderError::BalanceMismatch { inputs, outputs });
+            }
+            total_inputs = total_inputs
+                .checked_add(inputs)
+                .ok_or_else(|| BuilderError::AmountOverflow(name.clone()))?;
+            total_outputs = total_outputs
+                .checked_add(outputs)
+                .ok_or(BuilderError::AmountOverflow(name))?;
+        }
+        if total_outputs > total_inputs {
+            return Err(BuilderError::BalanceMismatch {
+                inputs: total_inputs,
+                outputs: total_outputs,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn add_global_state(
+        mut self,
+        name: impl Into<TypeName>,
+        value: impl StrictSerialize,
+    ) -> Result<Self, BuilderError> {
+        let name = name.into();
+        let declared = &self
+            .builder
+            .schema
+            .transitions
+            .get(&self.transition_type)
+            .expect("schema should match interface: must be checked by the constructor")
+            .globals;
+        self.builder.check_global_declared(&name, declared.keys())?;
+        self.builder.add_global_state(name, value)?;
+        Ok(self)
+    }
+
+    pub fn add_fungible_state(
+        mut self,
+        name: impl Into<TypeName>,
+        seal: impl Into<GraphSeal>,
+        value: u64,
+    ) -> Result<Self, BuilderError> {
+        self.builder.add_fungible_state(name, seal.into(), value)?;
+        Ok(self)
+    }
+
+    /// Adds fungible state with the blinding factor provided externally,
+    /// together with the Pedersen commitment the state must open. The state
+    /// is not accepted if it doesn't match the commitment.
+    pub fn add_fungible_revealed(
+        mut self,
+        name: impl Into<TypeName>,
+        seal: impl Into<GraphSeal>,
+        state: fungible::Revealed,
+        commitment: PedersenCommitment,
+    ) -> Result<Self, BuilderError> {
+        self.builder.add_fungible_verified(name, seal.into(), state, commitment)?;
+        Ok(self)
+    }
+
+    pub fn add_attachment(
+        mut self,
+        name: impl Into<TypeName>,
+        seal: impl Into<GraphSeal>,
+        attach_id: AttachId,
+        media_type: MediaType,
+    ) -> Result<Self, BuilderError> {
+        self.builder.add_attachment(name, seal.into(), attach_id, media_type)?;
+        Ok(self)
+    }
+
+    /// Completes the transition, checking that the amounts of the fungible
+    /// state are balanced: all spent amounts must be known, and each fungible
+    /// state type must be assigned with the same amount as spent. Transitions
+    /// which the interface declares inflating, see [`Iface::inflates`], may
+    /// issue new state within the spent allowance.
+    pub fn complete_transition(self) -> Result<Transition, BuilderError> {
+        self.check_balance()?;
+        let mut inputs = BTreeMap::<OpId, BTreeMap<AssignmentsType, Vec<u16>>>::new();
+        for opout in self.inputs {
+            inputs
+                .entry(opout.op)
+                .or_default()
+                .entry(opout.ty)
+                .or_default()
+                .push(opout.no);
+        }
+        let inputs = inputs
+            .into_iter()
+            .map(|(opid, prev)| {
+                let prev = prev
+                    .into_iter()
+                    .map(|(ty, no)| Confined::try_from(no).map(|no| (ty, no)))
+                    .collect::<Result<BTreeMap<_, _>, _>>()?;
+                Confined::try_from(prev).map(|prev| (opid, prev))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        Ok(Transition {
+            ffv: none!(),
+            transition_type: self.transition_type,
+            metadata: None,
+            globals: self.builder.global,
+            inputs: Confined::try_from(inputs)?,
+            assignments: OperationBuilder::compose_assignments(
+                self.builder.fungible,
+                self.builder.attachments,
+                Some,
+            )?,
+            valencies: self.builder.valencies,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExtensionBuilder {
+    builder: OperationBuilder,
+    contract_id: ContractId,
+    extension_type: ExtensionType,
+    redeemed: TinyOrdMap<ValencyType, OpId>,
+    declared: BTreeMap<OpId, Valencies>,
+}
+
+impl ExtensionBuilder {
+    pub fn with(
+        iface: Iface,
+        schema: SubSchema,
+        iimpl: IfaceImpl,
+        contract_id: ContractId,
+        extension_name: impl Into<TypeName>,
+    ) -> Result<Self, ForgeError> {
+        let name = extension_name.into();
+        let Some(extension_type) = iimpl.extension_type(&name) else {
+            return Err(ForgeError::ExtensionNotFound(name));
+        };
+        Ok(ExtensionBuilder {
+            builder: OperationBuilder::with(iface, schema, iimpl)?,
+            contract_id,
+            extension_type,
+            redeemed: none!(),
+            declared: none!(),
+        })
+    }
+
+    /// Redeems valency `name` declared by the operation `prev`. Valencies
+    /// declared by the operation are kept by the builder, such that they can
+    /// be checked on completion.
+    pub fn redeem_valency(
+        mut self,
+        name: impl Into<TypeName>,
+        prev: &impl Operation,
+    ) -> Result<Self, BuilderError> {
+        let name = name.into();
+        let Some(valency) = self.builder.iimpl.valency_type(&name) else {
+            return Err(BuilderError::TypeNotFound(name));
+        };
+        self.redeemed.insert(valency, prev.id())?;
+        self.declared.insert(prev.id(), prev.valencies().clone());
+        Ok(self)
+    }
+
+    pub fn add_global_state(
+        mut self,
+        name: impl Into<TypeName>,
+        value: impl StrictSerialize,
+    ) -> Result<Self, BuilderError> {
+        let name = name.into();
+        let declared = &self
+            .builder
+            .schema
+            .extensions
+            .get(&self.extension_type)
+            .expect("schema should match interface: must be checked by the constructor")
+            .globals;
+        self.builder.check_global_declared(&name, declared.keys())?;
+        self.builder.add_global_state(name, value)?;
+        Ok(self)
+    }
+
+    pub fn add_fungible_state(
+        mut self,
+        name: impl Into<TypeName>,
+        seal: impl Into<GraphSeal>,
+        value: u64,
+    ) -> Result<Self, BuilderError> {
+        self.builder.add_fungible_state(name, seal.into(), value)?;
+        Ok(self)
+    }
+
+    pub fn add_valency(mut self, name: impl Into<TypeName>) -> Result<Self, BuilderError> {
+        self.builder.add_valency(name)?;
+        Ok(self)
+    }
+
+    /// Completes the extension, performing the same checks of the redeemed
+    /// valencies as are done during the consignment validation.
+    pub fn complete(self) -> Result<Extension, BuilderError> {
+        check_redeemed(
+            &self.builder.schema,
+            self.extension_type,
+            self.redeemed.iter().map(|(valency, opid)| (*valency, *opid)),
+            |opid| self.declared.get(&opid),
+        )?;
+
+        let mut grouped = BTreeMap::<OpId, BTreeSet<ValencyType>>::new();
+        for (valency, opid) in &self.redeemed {
+            grouped.entry(*opid).or_default().insert(*valency);
+        }
+        let redeemed = Redeemed::try_from_iter(grouped.into_iter().map(|(opid, valencies)| {
+            (opid, Confined::try_from(valencies).expect("at most 255 redeemed valencies"))
+        }))
+        .expect("at most 255 redeemed valencies");
+
+        Ok(Extension {
+            ffv: none!(),
+            extension_type: self.extension_type,
+            contract_id: self.contract_id,
+            metadata: None,
+            globals: self.builder.global,
+            assignments: OperationBuilder::compose_assignments(
+                self.builder.fungible,
+                self.builder.attachments,
+                genesis_seal,
+            )?,
+            redeemed,
+            valencies: self.builder.valencies,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct OperationBuilder {
+    schema: SubSchema,
+    iface: Iface,
+    iimpl: IfaceImpl,
+
+    global: GlobalState,
+    // rights: TinyOrdMap<AssignmentsType, Confined<BTreeSet<GraphSeal>, 1, U8>>,
+    fungible: FungibleAssignments,
+    // data: TinyOrdMap<AssignmentsType, Confined<BTreeMap<GraphSeal, SmallBlob>, 1, U8>>,
+    attachments: AttachAssignments,
+    valencies: TinyOrdSet<ValencyType>,
+
+    /// Global state values which were already checked against their types,
+    /// such that repeated values are not reified once again.
+    reified: BTreeSet<(SemId, SmallBlob)>,
+    /// Groups of the global state names, from each of which at most one
+    /// global state can be set.
+    exclusive: Vec<BTreeSet<TypeName>>,
+    /// Genesis composed by the contract builder, which must be invalidated by
+    /// any modification of the state.
+    genesis: GenesisCache,
+
+    events: Option<EventHandler>,
+}
+
+impl OperationBuilder {
+    fn with(iface: Iface, schema: SubSchema, iimpl: IfaceImpl) -> Result<Self, ForgeError> {
+        if iimpl.iface_id != iface.iface_id() {
+            return Err(ForgeError::InterfaceMismatch);
+        }
+        if iimpl.schema_id != schema.schema_id() {
+            return Err(ForgeError::SchemaMismatch);
+        }
+        iimpl.validate_interface_semantics(&iface, &schema)?;
+
+        Ok(OperationBuilder {
+            schema,
+            iface,
+            iimpl,
+
+            global: none!(),
+            fungible: none!(),
+            attachments: none!(),
+            valencies: none!(),
+
+            reified: none!(),
+            exclusive: none!(),
+            genesis: none!(),
+
+            events: None,
+        })
+    }
+
+    /// Passes event to the handler; the event is constructed only if the
+    /// handler is set.
+    fn emit(&self, event: impl FnOnce() -> BuilderEvent) {
+        if let Some(EventHandler(handler)) = &self.events {
+            let mut handler = handler.lock().unwrap_or_else(PoisonError::into_inner);
+            handler(event());
+        }
+    }
+
+    /// Resolves name under which the interface implementation exposes the
+    /// state type with the given id.
+    fn type_name(types: &TinyOrdSet<NamedType<u16>>, id: u16) -> Result<TypeName, BuilderError> {
+        types
+            .iter()
+            .find(|nt| nt.id == id)
+            .map(|nt| nt.name.clone())
+            .ok_or(BuilderError::TypeIdNotFound(id))
+    }
+
+    fn add_valency(&mut self, name: impl Into<TypeName>) -> Result<(), BuilderError> {
+        let name = name.into();
+        let Some(valency) = self.iimpl.valency_type(&name) else {
+            return Err(BuilderError::TypeNotFound(name));
+        };
+        if !self.schema.valency_types.contains(&valency) {
+            return Err(BuilderError::TypeNotFound(name));
+        }
+        self.valencies.push(valency)?;
+        self.genesis.invalidate();
+        self.emit(|| BuilderEvent::ValencyAdded(name));
+        Ok(())
+    }
+
+    fn add_global_state(
+        &mut self,
+        name: impl Into<TypeName>,
+        value: impl StrictSerialize,
+    ) -> Result<(), BuilderError> {
+        let name = name.into();
+        let serialized = Self::serialize_global(&name, value)?;
+        self.add_global_data(name, serialized)
+    }
+
+    fn serialize_global(
+        name: &TypeName,
+        value: impl StrictSerialize,
+    ) -> Result<SmallBlob, BuilderError> {
+        let serialized = value.to_strict_serialized::<{ usize::MAX }>()?;
+        let size = serialized.len();
+        SmallBlob::try_from(serialized.into_inner()).map_err(|_| BuilderError::GlobalTooLarge {
+            name: name.clone(),
+            size,
+        })
+    }
+
+    /// Checks that the global state is declared by the schema of the operation,
+    /// which declares global state of the provided types.
+    fn check_global_declared<'ty>(
+        &self,
+        name: &TypeName,
+        declared: impl IntoIterator<Item = &'ty GlobalStateType>,
+    ) -> Result<(), BuilderError> {
+        let Some(id) = self.iimpl.global_type(name) else {
+            return Err(BuilderError::TypeNotFound(name.clone()));
+        };
+        let declared = declared.into_iter().copied().collect::<Vec<_>>();
+        if declared.contains(&id) {
+            return Ok(());
+        }
+        let allowed = self
+            .iimpl
+            .global_state
+            .iter()
+            .filter(|nt| declared.contains(&nt.id))
+            .map(|nt| format!("`{}`", nt.name))
+            .collect::<Vec<_>>();
+        let allowed = if allowed.is_empty() {
+            s!("no global state")
+        } else {
+            allowed.join(", ")
+        };
+        Err(BuilderError::StateNotDeclaredByOperation(name.clone(), allowed))
+    }
+
+    /// Checks that no other global state from the exclusion groups of the
+    /// given state is already set.
+    fn check_exclusive(&self, name: &TypeName) -> Result<(), BuilderError> {
+        let conflict = self
+            .exclusive
+            .iter()
+            .filter(|group| group.contains(name))
+            .flatten()
+            .filter(|other| *other != name)
+            .find(|other| {
+                self.iimpl
+                    .global_type(other)
+                    .map(|id| self.global.contains_key(&id))
+                    .unwrap_or_default()
+            });
+        match conflict {
+            Some(other) => Err(BuilderError::MutuallyExclusive {
+                a: other.clone(),
+                b: name.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds global state which is already strict-serialized.
+    fn add_global_data(
+        &mut self,
+        name: TypeName,
+        serialized: SmallBlob,
+    ) -> Result<(), BuilderError> {
+        // Check value matches type requirements
+        let Some(id) = self.iimpl.global_type(&name) else {
+            return Err(BuilderError::TypeNotFound(name));
+        };
+        let ty = self
+            .schema
+            .global_types
+            .get(&id)
+            .expect("schema should match interface: must be checked by the constructor");
+        let key = (ty.sem_id, serialized);
+        if !self.reified.contains(&key) {
+            // Values with trailing data are not reified, and the rest of the
+            // non-canonical encodings is detected by checking that the value
+            // is re-serialized into exactly the same bytes
+            let val = match self.schema.type_system.reify(key.0, &key.1) {
+                Err(reify::Error::NotEntirelyConsumed) => {
+                    return Err(BuilderError::NonCanonicalState(name));
+                }
+                res => res?,
+            };
+            let reserialized = self
+                .schema
+                .type_system
+                .strict_serialize_type::<{ u16::MAX as usize }>(&val)
+                .and_then(|data| data.to_strict_serialized::<{ u16::MAX as usize }>())
+                .map_err(|_| BuilderError::NonCanonicalState(name.clone()))?;
+            if reserialized != key.1 {
+                return Err(BuilderError::NonCanonicalState(name));
+            }
+        }
+        let (sem_id, serialized) = key;
+        self.check_exclusive(&name)?;
+
+        let count = self.global.get(&id).map(|values| values.len()).unwrap_or_default();
+        if count >= ty.max_items as usize {
+            return Err(BuilderError::TooManyGlobalItems {
+                name,
+                max: ty.max_items,
+            });
+        }
+
+        self.global.add_state(id, serialized.clone().into())?;
+        self.reified.insert((sem_id, serialized));
+        self.genesis.invalidate();
+        self.emit(|| BuilderEvent::GlobalAdded(name));
+
+        Ok(())
+    }
+
+    fn add_fungible_state(
+        &mut self,
+        name: impl Into<TypeName>,
+        seal: GraphSeal,
+        value: u64,
+    ) -> Result<(), BuilderError> {
+        let state = fungible::Revealed::new(value, &mut BlindingRng);
+        self.add_fungible_revealed(name, seal, state)
+    }
+
+    /// Adds fungible state with externally provided blinding, checking that
+    /// the revealed state opens the provided Pedersen commitment.
+    fn add_fungible_verified(
+        &mut self,
+        name: impl Into<TypeName>,
+        seal: GraphSeal,
+        state: fungible::Revealed,
+        commitment: PedersenCommitment,
+    ) -> Result<(), BuilderError> {
+        let name = name.into();
+        if !commitment.verify(&state) {
+            return Err(BuilderError::InvalidCommitment(name));
+        }
+        self.add_fungible_revealed(name, seal, state)
+    }
+
+    fn add_fungible_revealed(
+        &mut self,
+        name: impl Into<TypeName>,
+        seal: GraphSeal,
+        state: fungible::Revealed,
+    ) -> Result<(), BuilderError> {
+        let name = name.into();
+
+        let Some(id) = self.iimpl.assignments_type(&name) else {
+            return Err(BuilderError::TypeNotFound(name));
+        };
+        let ty = self
+            .schema
+            .owned_types
+            .get(&id)
+            .expect("schema should match interface: must be checked by the constructor");
+        if *ty != StateSchema::Fungible(FungibleType::Unsigned64Bit) {
+            return Err(BuilderError::InvalidStateType(name));
+        }
+        if !self.iimpl.is_method_allowed(seal.method) {
+            return Err(BuilderError::CloseMethodNotAllowed(seal.method));
+        }
+
+        match self.fungible.get_mut(&id) {
+            Some(assignments) => {
+                assignments.insert(seal, state)?;
+            }
+            None => {
+                self.fungible.insert(id, Confined::with((seal, state)))?;
+            }
+        }
+        self.genesis.invalidate();
+        self.emit(|| BuilderEvent::AllocationAdded { name, seal });
+        Ok(())
+    }
+
+    /// Adds attachment of the data with the given id, checking that its media
+    /// type is allowed for the attachment state type by the interface
+    /// implementation.
+    fn add_attachment(
+        &mut self,
+        name: impl Into<TypeName>,
+        seal: GraphSeal,
+        attach_id: AttachId,
+        media_type: MediaType,
+    ) -> Result<(), BuilderError> {
+        let name = name.into();
+
+        let Some(id) = self.iimpl.assignments_type(&name) else {
+            return Err(BuilderError::TypeNotFound(name));
+        };
+        let ty = self
+            .schema
+            .owned_types
+            .get(&id)
+            .expect("schema should match interface: must be checked by the constructor");
+        if *ty != StateSchema::Attachment {
+            return Err(BuilderError::InvalidStateType(name));
+        }
+        if !self.iimpl.is_media_allowed(id, &media_type) {
+            return Err(BuilderError::MediaTypeNotAllowed { name, media_type });
+        }
+        if !self.iimpl.is_method_allowed(seal.method) {
+            return Err(BuilderError::CloseMethodNotAllowed(seal.method));
+        }
+
+        // Consensus layer doesn't validate media types, so the media type is
+        // checked by the builder only
+        let state = attachment::Revealed {
+            id: attach_id,
+            media_type: TinyString::try_from(media_type.to_string())?,
+            salt: BlindingRng.next_u64(),
+        };
+        match self.attachments.get_mut(&id) {
+            Some(assignments) => {
+                assignments.insert(seal, state)?;
+            }
+            None => {
+                self.attachments.insert(id, Confined::with((seal, state)))?;
+            }
+        }
+        self.genesis.invalidate();
+        self.emit(|| BuilderEvent::AllocationAdded { name, seal });
+        Ok(())
+    }
+
+    /// Re-generates all blinding factors for the owned state and its seals.
+    fn reblind(&mut self) {
+        let mut rng = BlindingRng;
+        let fungible = mem::take(&mut self.fungible).into_iter().map(|(id, assignments)| {
+            let assignments = assignments.into_iter().map(|(mut seal, state)| {
+                seal.blinding = rng.next_u64();
+                (seal, fungible::Revealed::new(state.value.as_u64(), &mut rng))
+            });
+            (id, Confined::try_from_iter(assignments).expect("same size"))
+        });
+        self.fungible = Confined::try_from_iter(fungible).expect("same size");
+        let attachments = mem::take(&mut self.attachments)
+            .into_iter()
+            .map(|(id, assignments)| {
+                let assignments = assignments.into_iter().map(|(mut seal, mut state)| {
+                    seal.blinding = rng.next_u64();
+                    state.salt = rng.next_u64();
+                    (seal, state)
+                });
+                (id, Confined::try_from_iter(assignments).expect("same size"))
+            });
+        self.attachments = Confined::try_from_iter(attachments).expect("same size");
+        self.genesis.invalidate();
+    }
+
+    /// Composes assignments of the operation, converting the seals with the
+    /// provided function, which returns `None` for the seals not supported
+    /// by the operation.
+    fn compose_assignments<Seal: ExposedSeal>(
+        fungible: FungibleAssignments,
+        attachments: AttachAssignments,
+        convert: impl Fn(GraphSeal) -> Option<Seal>,
+    ) -> Result<Assignments<Seal>, IssueError> {
+        let mut owned_state = BTreeMap::new();
+        for (id, vec) in fungible {
+            if vec.is_empty() {
+                return Err(IssueError::NoAssignments(id));
+            }
+            let vec = vec
+                .into_iter()
+                .map(|(seal, state)| {
+                    let seal = convert(seal).ok_or(IssueError::WitnessSeal(id))?;
+                    Ok(Assign::Revealed { seal, state })
+                })
+                .collect::<Result<Vec<_>, IssueError>>()?;
+            let state =
+                Confined::try_from(vec).map_err(|_| IssueError::TooManyAssignments(id))?;
+            owned_state.insert(id, TypedAssigns::Fungible(state));
+        }
+        for (id, vec) in attachments {
+            if vec.is_empty() {
+                return Err(IssueError::NoAssignments(id));
+            }
+            let vec = vec
+                .into_iter()
+                .map(|(seal, state)| {
+                    let seal = convert(seal).ok_or(IssueError::WitnessSeal(id))?;
+                    Ok(Assign::Revealed { seal, state })
+                })
+                .collect::<Result<Vec<_>, IssueError>>()?;
+            let state =
+                Confined::try_from(vec).map_err(|_| IssueError::TooManyAssignments(id))?;
+            owned_state.insert(id, TypedAssigns::Attachment(state));
+        }
+        let owned_state =
+            Confined::try_from(owned_state).map_err(|_| IssueError::TooManyAssignmentTypes)?;
+        Ok(Assignments::from_inner(owned_state))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::convert::Infallible;
+    use std::str::FromStr;
+
+    use bp::secp256k1::rand::rngs::StdRng;
+    use bp::secp256k1::rand::{Rng, SeedableRng};
+    use bp::{Txid, Vout};
+    use commit_verify::Conceal;
+    use rgb::{ExtensionSchema, GlobalStateSchema, Occurrences, TransitionSchema};
+
+    use amplify::confinement::{MediumBlob, TinyBlob};
+    use strict_encoding::{StrictDeserialize, StrictWriter};
+
+    use super::*;
+    pub use crate::fixtures::{outpoint, schema};
+    use crate::fixtures::{self, GS_CONTRACT, GS_NOMINAL, OS_ASSETS, TS_TRANSFER};
+    use crate::interface::{rgb20, ImplError, TransitionIface};
+    use crate::persistence::attach_id;
+    use crate::resolvers::ResolveHeight;
+    use crate::stl::{Nominal, Precision};
+    use crate::LIB_NAME_RGB_STD;
+
+    const VS_ISSUE: ValencyType = 1;
+    const ES_ISSUE: ExtensionType = 20000;
+
+    pub fn iimpl(schema: &SubSchema, iface: &Iface) -> IfaceImpl {
+        IfaceImpl {
+            schema_id: schema.schema_id(),
+            iface_id: iface.iface_id(),
+            ..fixtures::iimpl()
+        }
+    }
+
+    pub fn builder() -> ContractBuilder {
+        let schema = schema();
+        let iface = rgb20();
+        let iimpl = iimpl(&schema, &iface);
+        ContractBuilder::with(iface, schema, iimpl).unwrap()
+    }
+
+    pub struct NoHeights;
+
+    impl ResolveHeight for NoHeights {
+        type Error = Infallible;
+        fn resolve_height(&mut self, _txid: Txid) -> Result<u32, Self::Error> {
+            unreachable!("genesis-only contract has no witness transactions")
+        }
+    }
+
+    /// Builder for the schema allowing up to `max` items of the contract text.
+    pub fn texts_builder(max: u16) -> ContractBuilder<Ready> {
+        let mut schema = schema();
+        schema
+            .global_types
+            .get_mut(&GS_CONTRACT)
+            .expect("test schema has contract text")
+            .max_items = max;
+        schema
+            .genesis
+            .globals
+            .insert(GS_CONTRACT, Occurrences::NoneOrUpTo(max))
+            .unwrap();
+        let iface = rgb20();
+        let iimpl = iimpl(&schema, &iface);
+        ContractBuilder::with(iface, schema, iimpl)
+            .unwrap()
+            .set_chain(Chain::Bitcoin)
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap()
+    }
+
+    pub fn texts(count: usize) -> Vec<ContractText> {
+        (0..count)
+            .map(|no| ContractText::try_from(format!("text #{no}")).unwrap())
+            .collect()
+    }
+
+    /// Schema with a public issuance valency, declared by genesis and redeemed
+    /// by the issuance extension.
+    pub fn issuance_schema() -> SubSchema {
+        let mut schema = schema();
+        schema.valency_types = tiny_bset! { VS_ISSUE };
+        schema.genesis.valencies = tiny_bset! { VS_ISSUE };
+        schema.extensions = tiny_bmap! {
+            ES_ISSUE => ExtensionSchema {
+                metadata: None,
+                globals: none!(),
+                redeems: tiny_bset! { VS_ISSUE },
+                assignments: tiny_bmap! {
+                    OS_ASSETS => Occurrences::OnceOrMore,
+                },
+                valencies: none!(),
+            }
+        };
+        schema
+    }
+
+    /// RGB20 interface declaring the issuance extension, which makes it
+    /// inflatable.
+    fn issuance_iface() -> Iface {
+        let mut iface = rgb20();
+        iface.extensions = tiny_bmap! {
+            tn!("Issue") => ExtensionIface {
+                metadata: None,
+                globals: none!(),
+                redeems: tiny_bmap! {
+                    tn!("Issue") => Occurrences::Once,
+                },
+                assignments: tiny_bmap! {
+                    tn!("Assets") => Occurrences::OnceOrMore,
+                },
+                valencies: none!(),
+            }
+        };
+        iface
+    }
+
+    fn issuance_iimpl(schema: &SubSchema, iface: &Iface) -> IfaceImpl {
+        let mut iimpl = iimpl(schema, iface);
+        iimpl.valencies = tiny_bset! { NamedType::with(VS_ISSUE, tn!("Issue")) };
+        iimpl.extensions = tiny_bset! { NamedType::with(ES_ISSUE, tn!("Issue")) };
+        iimpl
+    }
+
+    pub fn issuance_contract(schema: SubSchema, declare: bool) -> Contract {
+        let iface = issuance_iface();
+        let iimpl = issuance_iimpl(&schema, &iface);
+        let mut builder = required_globals(ContractBuilder::with(iface, schema, iimpl).unwrap())
+            .set_chain(Chain::Bitcoin)
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap();
+        if declare {
+            builder = builder.add_valency("Issue").unwrap();
+        }
+        builder.issue_contract().unwrap()
+    }
+
+    pub fn issuance_extension(contract: &Contract) -> Result<Extension, BuilderError> {
+        let iface = issuance_iface();
+        let iimpl = issuance_iimpl(&contract.schema, &iface);
+        ExtensionBuilder::with(
+            iface,
+            contract.schema.clone(),
+            iimpl,
+            contract.contract_id(),
+            "Issue",
+        )
+        .unwrap()
+        .redeem_valency("Issue", &contract.genesis)?
+        .add_fungible_state("Assets", outpoint(1), 50)?
+        .complete()
+    }
+
+    /// Adds global state required by the test schema.
+    pub fn required_globals<S: BuilderState>(builder: ContractBuilder<S>) -> ContractBuilder<S> {
+        builder
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap()
+            .add_global_state("ContractText", ContractText::default())
+            .unwrap()
+    }
+
+    pub const OS_LOGO: AssignmentsType = 4001;
+
+    /// Builder for the schema with the `Logo` attachments, which the interface
+    /// implementation restricts to images.
+    pub fn logo_builder() -> ContractBuilder<Ready> {
+        let mut schema = schema();
+        schema.owned_types.insert(OS_LOGO, StateSchema::Attachment).unwrap();
+        schema
+            .genesis
+            .assignments
+            .insert(OS_LOGO, Occurrences::NoneOrMore)
+            .unwrap();
+        let mut iface = rgb20();
+        iface
+            .owned_state
+            .insert(tn!("Logo"), OwnedIface::AnyAttach)
+            .unwrap();
+        let mut iimpl = iimpl(&schema, &iface);
+        iimpl
+            .owned_state
+            .push(NamedType::with(OS_LOGO, tn!("Logo")))
+            .unwrap();
+        iimpl.media_types = tiny_bmap! { OS_LOGO => tiny_bset! { MediaType::from("image/*") } };
+        required_globals(ContractBuilder::with(iface, schema, iimpl).unwrap())
+            .set_chain(Chain::Bitcoin)
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap()
+    }
+
+    pub fn issuable() -> ContractBuilder<Ready> {
+        required_globals(builder())
+            .set_chain(Chain::Bitcoin)
+            .add_fungible_state("Assets", outpoint(0), 100_000)
+            .unwrap()
+    }
+
+    #[test]
+    fn contract_id_preview() {
+        let builder = issuable();
+        let preview = builder.contract_id_preview().unwrap();
+        assert_eq!(builder.contract_id_preview().unwrap(), preview);
+        assert_eq!(builder.builder.genesis.contract_id(), Some(preview));
+        let contract = builder.clone().issue_contract().unwrap();
+        assert_eq!(contract.contract_id(), preview);
+
+        let builder = builder.add_fungible_state("Assets", outpoint(1), 10).unwrap();
+        assert_eq!(builder.builder.genesis.contract_id(), None);
+        let updated = builder.contract_id_preview().unwrap();
+        assert_ne!(updated, preview);
+        let testnet = builder.clone().set_chain(Chain::Testnet3);
+        assert_ne!(testnet.contract_id_preview().unwrap(), updated);
+        assert_eq!(builder.issue_contract().unwrap().contract_id(), updated);
+    }
+
+    #[test]
+    fn unknown_assignment_type() {
+        let mut builder = issuable();
+        let mut owned_types = mem::take(&mut builder.builder.schema.owned_types).into_inner();
+        owned_types.remove(&OS_ASSETS);
+        builder.builder.schema.owned_types = Confined::try_from(owned_types).unwrap();
+        assert_eq!(
+            builder.issue_contract().unwrap_err(),
+            IssueError::UnknownAssignmentType(OS_ASSETS)
+        );
+    }
+
+    #[test]
+    fn issue_genesis() {
+        let builder = issuable();
+        let contract_id = builder.contract_id_preview().unwrap();
+        let genesis = builder.clone().issue_genesis().unwrap();
+        assert_eq!(genesis.contract_id(), contract_id);
+        assert_eq!(builder.issue_contract().unwrap().genesis, genesis);
+    }
+
+    #[test]
+    fn semantic_mismatch() {
+        let mut schema = schema();
+        schema
+            .owned_types
+            .insert(OS_ASSETS, StateSchema::Declarative)
+            .unwrap();
+        let iface = rgb20();
+        let iimpl = iimpl(&schema, &iface);
+        assert_eq!(
+            ContractBuilder::with(iface, schema, iimpl).unwrap_err(),
+            ForgeError::Semantics(ImplErrors(vec![ImplError::StateKindMismatch(tn!("Assets"))]))
+        );
+    }
+
+    #[test]
+    fn anchor_height() {
+        assert_eq!(
+            issuable().set_anchor_height(0).unwrap_err(),
+            BuilderError::ImplausibleHeight(Chain::Bitcoin, 0)
+        );
+        assert_eq!(
+            issuable().set_anchor_height(20_000_000).unwrap_err(),
+            BuilderError::ImplausibleHeight(Chain::Bitcoin, 20_000_000)
+        );
+        assert_eq!(max_height(Chain::Bitcoin, 1_231_006_505), Some(0));
+        assert_eq!(max_height(Chain::Bitcoin, 1_700_000_000), Some(1_563_311));
+        assert_eq!(max_height(Chain::Signet, 0), Some(0));
+        assert_eq!(max_height(Chain::Regtest, 1_700_000_000), None);
+
+        let contract = issuable()
+            .set_anchor_height(800_000)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        assert_eq!(contract.anchor_height, Some(800_000));
+
+        let data = contract.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let contract = Contract::from_strict_serialized::<{ usize::MAX }>(data).unwrap();
+        assert_eq!(contract.anchor_height, Some(800_000));
+    }
+
+    #[test]
+    fn seal_network() {
+        let testnet = |outpoint: Outpoint| {
+            if outpoint.vout == Vout::from(1u32) {
+                Some(Chain::Testnet3)
+            } else {
+                None
+            }
+        };
+        let builder = issuable().with_seal_network(testnet);
+        assert_eq!(
+            builder
+                .clone()
+                .add_fungible_state("Assets", outpoint(1), 10)
+                .unwrap_err(),
+            BuilderError::SealWrongNetwork {
+                seal: outpoint(1).into(),
+                expected: Chain::Bitcoin,
+                found: Chain::Testnet3
+            }
+        );
+        builder
+            .clone()
+            .add_fungible_state("Assets", outpoint(2), 10)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        builder
+            .allow_foreign_seals()
+            .add_fungible_state("Assets", outpoint(1), 10)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+    }
+
+    #[test]
+    fn exclusive_globals() {
+        assert_eq!(
+            builder()
+                .with_exclusive_globals(["Nominal", "Unknown"])
+                .unwrap_err(),
+            BuilderError::TypeNotFound(tn!("Unknown"))
+        );
+
+        let builder = builder()
+            .with_exclusive_globals(["Nominal", "ContractText"])
+            .unwrap()
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap();
+        assert_eq!(
+            builder
+                .add_global_state("ContractText", ContractText::default())
+                .unwrap_err(),
+            BuilderError::MutuallyExclusive {
+                a: tn!("Nominal"),
+                b: tn!("ContractText")
+            }
+        );
+    }
+
+    #[test]
+    fn category() {
+        let builder = issuable();
+        let contract_id = builder.contract_id_preview().unwrap();
+        let contract = builder
+            .set_category("stablecoins")
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        assert_eq!(contract.contract_id(), contract_id);
+
+        let data = contract.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let contract = Contract::from_strict_serialized::<{ usize::MAX }>(data).unwrap();
+        assert_eq!(contract.category.as_deref().map(String::as_str), Some("stablecoins"));
+        assert_eq!(contract.contract_id(), contract_id);
+
+        assert!(matches!(
+            issuable().set_category(&"x".repeat(256)).unwrap_err(),
+            BuilderError::Confinement(_)
+        ));
+    }
+
+    #[test]
+    fn chain_not_allowed() {
+        let schema = schema();
+        let iface = rgb20();
+        let mut iimpl = iimpl(&schema, &iface);
+        iimpl.chains = tiny_bset! { Chain::Testnet3, Chain::Signet };
+        let builder = required_globals(ContractBuilder::with(iface, schema, iimpl).unwrap())
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap();
+
+        assert_eq!(
+            builder
+                .clone()
+                .set_chain(Chain::Bitcoin)
+                .issue_contract()
+                .unwrap_err(),
+            IssueError::ChainNotAllowed(Chain::Bitcoin)
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .issue_for_chains(&[Chain::Signet, Chain::Regtest])
+                .unwrap_err(),
+            IssueError::ChainNotAllowed(Chain::Regtest)
+        );
+        let contract = builder.set_chain(Chain::Testnet3).issue_contract().unwrap();
+        assert_eq!(contract.genesis.chain, Chain::Testnet3);
+    }
+
+    #[test]
+    fn set_chain_str() {
+        for (name, chain) in [
+            ("bitcoin", Chain::Bitcoin),
+            ("mainnet", Chain::Bitcoin),
+            ("main", Chain::Bitcoin),
+            ("bc", Chain::Bitcoin),
+            ("testnet", Chain::Testnet3),
+            ("Testnet3", Chain::Testnet3),
+            ("test", Chain::Testnet3),
+            ("tb", Chain::Testnet3),
+            ("signet", Chain::Signet),
+            ("regtest", Chain::Regtest),
+            ("bcrt", Chain::Regtest),
+        ] {
+            assert_eq!(builder().set_chain_str(name).unwrap().chain, Some(chain));
+        }
+        assert_eq!(
+            builder().set_chain_str("liquid").unwrap_err(),
+            BuilderError::UnknownChain(s!("liquid"))
+        );
+    }
+
+    #[test]
+    fn state_progression() {
+        let builder = required_globals(builder())
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap();
+        assert_eq!(builder.chain, None);
+
+        let ready: ContractBuilder<Ready> = builder.clone().set_chain(Chain::Testnet3);
+        let ready: ContractBuilder<Ready> = ready.set_chain(Chain::Signet);
+        assert_eq!(ready.issue_contract().unwrap().genesis.chain, Chain::Signet);
+
+        let dynamic = builder.into_dynamic();
+        assert_eq!(dynamic.clone().issue_contract().unwrap_err(), IssueError::ChainNotSet);
+        assert_eq!(
+            dynamic.clone().set_anchor_height(800_000).unwrap_err(),
+            BuilderError::ChainNotSet
+        );
+        let dynamic: ContractBuilder<Dynamic> = dynamic.set_chain_str("regtest").unwrap();
+        assert_eq!(dynamic.issue_contract().unwrap().genesis.chain, Chain::Regtest);
+    }
+
+    #[test]
+    fn valency_redeemed() {
+        let mut contract = issuance_contract(issuance_schema(), true);
+        let extension = issuance_extension(&contract).unwrap();
+        assert_eq!(extension.redeemed.len(), 1);
+        contract.extensions.push(extension).unwrap();
+        assert_eq!(contract.valency_errors(), vec![]);
+    }
+
+    #[test]
+    fn valency_not_declared() {
+        let mut contract = issuance_contract(issuance_schema(), false);
+        let err = ValencyError::ValencyNotDeclared {
+            opid: contract.genesis.id(),
+            valency: VS_ISSUE,
+        };
+        assert_eq!(issuance_extension(&contract).unwrap_err(), BuilderError::Valency(err));
+
+        let declaring = issuance_contract(issuance_schema(), true);
+        let extension = issuance_extension(&declaring).unwrap();
+        contract.extensions.push(extension).unwrap();
+        assert_eq!(contract.valency_errors(), vec![ValencyError::ValencyNotDeclared {
+            opid: declaring.genesis.id(),
+            valency: VS_ISSUE,
+        }]);
+    }
+
+    #[test]
+    fn valency_unknown() {
+        let mut schema = issuance_schema();
+        schema
+            .extensions
+            .get_mut(&ES_ISSUE)
+            .unwrap()
+            .redeems
+            .clear();
+        let mut contract = issuance_contract(schema, true);
+        let err = ValencyError::UnknownValency(ES_ISSUE, VS_ISSUE);
+        assert_eq!(issuance_extension(&contract).unwrap_err(), BuilderError::Valency(err));
+
+        let declaring = issuance_contract(issuance_schema(), true);
+        let extension = issuance_extension(&declaring).unwrap();
+        contract.extensions.push(extension).unwrap();
+        assert_eq!(contract.valency_errors(), vec![err]);
+    }
+
+    #[test]
+    fn terms() {
+        let text = "Terms and conditions of the asset issue";
+        let contract = builder()
+            .set_chain(Chain::Bitcoin)
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap()
+            .set_terms(text, None)
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        let data = contract.genesis.globals.get(&GS_CONTRACT).unwrap()[0].as_ref();
+        let stored = ContractText::from_strict_serialized::<{ u16::MAX as usize }>(
+            Confined::try_from(data.to_vec()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stored.as_str(), text);
+
+        assert_eq!(
+            builder()
+                .set_terms(text, Some(TermsSig::from(TinyBlob::default())))
+                .unwrap_err(),
+            BuilderError::SigNotSupported(tn!("ContractText"))
+        );
+
+        let schema = schema();
+        let mut iface = rgb20();
+        iface.global_state.get_mut(&tn!("ContractText")).unwrap().required = false;
+        let mut iimpl = iimpl(&schema, &iface);
+        iimpl.global_state = tiny_bset! { NamedType::with(GS_NOMINAL, tn!("Nominal")) };
+        assert_eq!(
+            ContractBuilder::with(iface, schema, iimpl)
+                .unwrap()
+                .set_terms(text, None)
+                .unwrap_err(),
+            BuilderError::MetadataNotSupported(tn!("ContractText"))
+        );
+    }
+
+    #[test]
+    fn signed_terms() {
+        let types = StandardTypes::new();
+        let mut schema = schema();
+        schema.global_types.get_mut(&GS_CONTRACT).unwrap().sem_id =
+            types.get("RGBContract.ContractTerms");
+        let mut iface = rgb20();
+        *iface.global_state.get_mut(&tn!("ContractText")).unwrap() =
+            Req::require(types.get("RGBContract.ContractTerms"));
+        let iimpl = iimpl(&schema, &iface);
+
+        let terms = ContractTerms {
+            text: ContractText::try_from(s!("Signed terms")).unwrap(),
+            sig: Some(TermsSig::from(TinyBlob::try_from(vec![0xA5; 64]).unwrap())),
+        };
+        let genesis = ContractBuilder::with(iface, schema, iimpl)
+            .unwrap()
+            .set_chain(Chain::Bitcoin)
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap()
+            .set_terms("Signed terms", terms.sig.clone())
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap()
+            .issue_genesis()
+            .unwrap();
+        let data = genesis.globals.get(&GS_CONTRACT).unwrap()[0].as_ref();
+        let stored = ContractTerms::from_strict_serialized::<{ u16::MAX as usize }>(
+            Confined::try_from(data.to_vec()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stored, terms);
+    }
+
+    fn transfer(inputs: &[u64], outputs: &[u64]) -> TransitionBuilder {
+        let schema = schema();
+        let iface = rgb20();
+        let iimpl = iimpl(&schema, &iface);
+        let opid = issuable().issue_genesis().unwrap().id();
+        let mut builder = TransitionBuilder::with(iface, schema, iimpl, "Transfer").unwrap();
+        for (no, amount) in inputs.iter().enumerate() {
+            let opout = Opout::new(opid, OS_ASSETS, no as u16);
+            builder = builder.add_fungible_input(opout, *amount).unwrap();
+        }
+        for (vout, amount) in outputs.iter().enumerate() {
+            builder = builder
+                .add_fungible_state("Assets", outpoint(vout as u32 + 1), *amount)
+                .unwrap();
+        }
+        builder
+    }
+
+    fn assigned(transition: &Transition) -> Vec<u64> {
+        let Some(TypedAssigns::Fungible(assigns)) = transition.assignments.get(&OS_ASSETS) else {
+            return vec![];
+        };
+        let mut amounts = assigns
+            .iter()
+            .filter_map(|assign| match assign {
+                Assign::Revealed { state, .. } => Some(state.value.as_u64()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        amounts.sort();
+        amounts
+    }
+
+    #[test]
+    fn change() {
+        let transition = transfer(&[60, 40], &[30])
+            .complete_with_change("Assets", outpoint(0))
+            .unwrap();
+        assert_eq!(assigned(&transition), vec![30, 70]);
+    }
+
+    #[test]
+    fn change_exact_spend() {
+        let transition = transfer(&[60, 40], &[30, 70])
+            .complete_with_change("Assets", outpoint(0))
+            .unwrap();
+        assert_eq!(assigned(&transition), vec![30, 70]);
+    }
+
+    #[test]
+    fn change_insufficient() {
+        assert_eq!(
+            transfer(&[60], &[30, 70])
+                .complete_with_change("Assets", outpoint(0))
+                .unwrap_err(),
+            BuilderError::InsufficientInputs {
+                name: tn!("Assets"),
+                deficit: 40
+            }
+        );
+
+        let opid = issuable().issue_genesis().unwrap().id();
+        let opout = Opout::new(opid, OS_ASSETS, 5);
+        assert_eq!(
+            transfer(&[60], &[30])
+                .add_input(opout)
+                .unwrap()
+                .complete_with_change("Assets", outpoint(0))
+                .unwrap_err(),
+            BuilderError::UnknownInputAmount(opout)
+        );
+    }
+
+    #[test]
+    fn pay() {
+        let transition = transfer(&[60, 40], &[]).pay(outpoint(1), 100, outpoint(0)).unwrap();
+        assert_eq!(assigned(&transition), vec![100]);
+
+        let transition = transfer(&[60, 40], &[]).pay(outpoint(1), 30, outpoint(0)).unwrap();
+        assert_eq!(assigned(&transition), vec![30, 70]);
+
+        assert_eq!(
+            transfer(&[60], &[]).pay(outpoint(1), 100, outpoint(0)).unwrap_err(),
+            BuilderError::InsufficientInputs {
+                name: tn!("Assets"),
+                deficit: 40
+            }
+        );
+    }
+
+    /// Splits the amount into the given number of random parts.
+    fn split(rng: &mut impl Rng, total: u64, parts: usize) -> Vec<u64> {
+        let mut cuts = (1..parts).map(|_| rng.gen_range(0..=total)).collect::<Vec<_>>();
+        cuts.extend([0, total]);
+        cuts.sort();
+        cuts.windows(2).map(|pair| pair[1] - pair[0]).collect()
+    }
+
+    #[test]
+    fn balance_random_splits() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..32 {
+            let total = rng.gen_range(1..=1_000_000u64);
+            let (input_count, output_count) = (rng.gen_range(1..=4), rng.gen_range(1..=4));
+            let inputs = split(&mut rng, total, input_count);
+            let outputs = split(&mut rng, total, output_count);
+            assert!(transfer(&inputs, &outputs).complete_transition().is_ok());
+
+            let delta = rng.gen_range(1..=total);
+            let mut imbalanced = outputs.clone();
+            if rng.gen::<bool>() && imbalanced[0] >= delta {
+                imbalanced[0] -= delta;
+            } else {
+                imbalanced[0] += delta;
+            }
+            let outputs = imbalanced.iter().sum();
+            assert_eq!(
+                transfer(&inputs, &imbalanced).complete_transition().unwrap_err(),
+                BuilderError::BalanceMismatch {
+                    inputs: total,
+                    outputs
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn balance_conservation() {
+        assert!(transfer(&[60, 40], &[30, 70]).complete_transition().is_ok());
+        assert_eq!(
+            transfer(&[60, 40], &[30, 60]).complete_transition().unwrap_err(),
+            BuilderError::BalanceMismatch {
+                inputs: 100,
+                outputs: 90
+            }
+        );
+        assert_eq!(
+            transfer(&[60, 40], &[30, 80]).complete_transition().unwrap_err(),
+            BuilderError::BalanceMismatch {
+                inputs: 100,
+                outputs: 110
+            }
+        );
+    }
+
+    #[test]
+    fn balance_unknown_amounts() {
+        let opid = issuable().issue_genesis().unwrap().id();
+        let opout = Opout::new(opid, OS_ASSETS, 5);
+        assert_eq!(
+            transfer(&[60], &[30, 50])
+                .add_input(opout)
+                .unwrap()
+                .complete_transition()
+                .unwrap_err(),
+            BuilderError::UnknownInputAmount(opout)
+        );
+    }
+
+    #[test]
+    fn balance_overflow() {
+        assert_eq!(
+            transfer(&[u64::MAX, 1], &[1]).complete_transition().unwrap_err(),
+            BuilderError::AmountOverflow(tn!("Assets"))
+        );
+        assert_eq!(
+            transfer(&[1], &[u64::MAX, 1]).complete_transition().unwrap_err(),
+            BuilderError::AmountOverflow(tn!("Assets"))
+        );
+    }
+
+    #[test]
+    fn balance_inflationary() {
+        const OS_ALLOWANCE: AssignmentsType = 4010;
+        const TS_MINT: TransitionType = 10010;
+        let mut schema = schema();
+        schema
+            .owned_types
+            .insert(OS_ALLOWANCE, StateSchema::Fungible(FungibleType::Unsigned64Bit))
+            .unwrap();
+        schema
+            .transitions
+            .insert(TS_MINT, TransitionSchema {
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! { OS_ALLOWANCE => Occurrences::Once },
+                assignments: tiny_bmap! { OS_ASSETS => Occurrences::OnceOrMore },
+                valencies: none!(),
+            })
+            .unwrap();
+        let mut iface = rgb20();
+        iface.owned_state.insert(tn!("Allowance"), OwnedIface::Amount).unwrap();
+        iface
+            .transitions
+            .insert(tn!("Mint"), TransitionIface {
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! { tn!("Allowance") => Occurrences::Once },
+                assignments: tiny_bmap! { tn!("Assets") => Occurrences::OnceOrMore },
+                valencies: none!(),
+            })
+            .unwrap();
+        let mut mint_impl = iimpl(&schema, &iface);
+        mint_impl.owned_state.push(NamedType::with(OS_ALLOWANCE, tn!("Allowance"))).unwrap();
+        mint_impl.transitions.push(NamedType::with(TS_MINT, tn!("Mint"))).unwrap();
+
+        let opid = issuable().issue_genesis().unwrap().id();
+        let mint = TransitionBuilder::with(iface, schema.clone(), mint_impl, "Mint")
+            .unwrap()
+            .add_fungible_input(Opout::new(opid, OS_ALLOWANCE, 0), 100)
+            .unwrap();
+        assert!(mint
+            .clone()
+            .add_fungible_state("Assets", outpoint(1), 60)
+            .unwrap()
+            .complete_transition()
+            .is_ok());
+        assert_eq!(
+            mint.add_fungible_state("Assets", outpoint(1), 160)
+                .unwrap()
+                .complete_transition()
+                .unwrap_err(),
+            BuilderError::BalanceMismatch {
+                inputs: 100,
+                outputs: 160
+            }
+        );
+
+        // Non-inflating transition doesn't issue state whatever its name is
+        let mut iface = rgb20();
+        let transfer = iface.transitions.get(&TypeName::from("Transfer")).unwrap().clone();
+        iface.transitions.insert(tn!("Issue"), transfer).unwrap();
+        let mut issue_impl = iimpl(&schema, &iface);
+        issue_impl.transitions = tiny_bset! { NamedType::with(TS_TRANSFER, tn!("Issue")) };
+        assert_eq!(
+            TransitionBuilder::with(iface, schema, issue_impl, "Issue")
+                .unwrap()
+                .add_fungible_input(Opout::new(opid, OS_ASSETS, 0), 100)
+                .unwrap()
+                .add_fungible_state("Assets", outpoint(1), 60)
+                .unwrap()
+                .complete_transition()
+                .unwrap_err(),
+            BuilderError::BalanceMismatch {
+                inputs: 100,
+                outputs: 60
+            }
+        );
+    }
+
+    #[test]
+    fn fungible_revealed() {
+        let state = fungible::Revealed::new(100, &mut BlindingRng);
+        let commitment = PedersenCommitment::commit(&state);
+        let genesis = required_globals(builder())
+            .set_chain(Chain::Bitcoin)
+            .add_fungible_revealed("Assets", outpoint(0), state, commitment)
+            .unwrap()
+            .issue_genesis()
+            .unwrap();
+        let Some(TypedAssigns::Fungible(assigns)) = genesis.assignments.get(&OS_ASSETS) else {
+            panic!("no fungible state in genesis");
+        };
+        assert!(matches!(assigns[0], Assign::Revealed { state: ref s, .. } if *s == state));
+
+        let other = fungible::Revealed::new(100, &mut BlindingRng);
+        let commitment = PedersenCommitment::commit(&other);
+        assert_eq!(
+            builder()
+                .add_fungible_revealed("Assets", outpoint(0), state, commitment)
+                .unwrap_err(),
+            BuilderError::InvalidCommitment(tn!("Assets"))
+        );
+    }
+
+    fn genesis_method(genesis: &Genesis) -> CloseMethod {
+        let Some(TypedAssigns::Fungible(assigns)) = genesis.assignments.get(&OS_ASSETS) else {
+            panic!("no fungible state in genesis");
+        };
+        let Assign::Revealed { seal, .. } = assigns[0] else {
+            panic!("genesis seal is not revealed");
+        };
+        seal.method
+    }
+
+    fn builder_with_methods(methods: &[CloseMethod]) -> ContractBuilder<Ready> {
+        let schema = schema();
+        let iface = rgb20();
+        let mut iimpl = iimpl(&schema, &iface);
+        iimpl.close_methods = Confined::try_from_iter(methods.iter().copied()).unwrap();
+        let builder = ContractBuilder::with(iface, schema, iimpl).unwrap();
+        required_globals(builder).set_chain(Chain::Bitcoin)
+    }
+
+    #[test]
+    fn seal_method_tapret() {
+        let genesis = builder_with_methods(&[])
+            .add_fungible_state_with_method("Assets", outpoint(0), 100, CloseMethod::TapretFirst)
+            .unwrap()
+            .issue_genesis()
+            .unwrap();
+        assert_eq!(genesis_method(&genesis), CloseMethod::TapretFirst);
+
+        assert_eq!(
+            builder_with_methods(&[CloseMethod::OpretFirst])
+                .add_fungible_state_with_method(
+                    "Assets",
+                    outpoint(0),
+                    100,
+                    CloseMethod::TapretFirst
+                )
+                .unwrap_err(),
+            BuilderError::CloseMethodNotAllowed(CloseMethod::TapretFirst)
+        );
+    }
+
+    #[test]
+    fn seal_method_opret() {
+        let genesis = builder_with_methods(&[])
+            .add_fungible_state_with_method("Assets", outpoint(0), 100, CloseMethod::OpretFirst)
+            .unwrap()
+            .issue_genesis()
+            .unwrap();
+        assert_eq!(genesis_method(&genesis), CloseMethod::OpretFirst);
+
+        // Seals without explicit method default to the preferred one
+        let genesis = builder_with_methods(&[CloseMethod::OpretFirst])
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap()
+            .issue_genesis()
+            .unwrap();
+        assert_eq!(genesis_method(&genesis), CloseMethod::OpretFirst);
+
+        let spec = SealSpec::Explicit {
+            method: Some(CloseMethod::TapretFirst),
+            outpoint: outpoint(0),
+        };
+        assert_eq!(
+            builder_with_methods(&[])
+                .add_fungible_state_with_method("Assets", spec, 100, CloseMethod::OpretFirst)
+                .unwrap_err(),
+            BuilderError::CloseMethodMismatch(spec, CloseMethod::OpretFirst)
+        );
+    }
+
+    #[test]
+    fn global_collection() {
+        const MAX: u16 = 3;
+        for count in [0, 1, MAX as usize] {
+            let mut builder = texts_builder(MAX);
+            for text in texts(count) {
+                builder = builder.add_global_state("ContractText", text).unwrap();
+            }
+            let genesis = builder.issue_genesis().unwrap();
+            let values = genesis
+                .globals
+                .get(&GS_CONTRACT)
+                .map(|values| {
+                    values
+                        .iter()
+                        .map(|data| {
+                            ContractText::from_strict_serialized::<{ u16::MAX as usize }>(
+                                Confined::try_from(data.as_ref().to_vec()).unwrap(),
+                            )
+                            .unwrap()
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            assert_eq!(values, texts(count));
+        }
+
+        let mut builder = texts_builder(MAX);
+        for text in texts(MAX as usize) {
+            builder = builder.add_global_state("ContractText", text).unwrap();
+        }
+        assert_eq!(
+            builder
+                .add_global_state("ContractText", ContractText::default())
+                .unwrap_err(),
+            BuilderError::TooManyGlobalItems {
+                name: tn!("ContractText"),
+                max: MAX
+            }
+        );
+    }
+
+    #[test]
+    fn seal_spec() {
+        let spec = SealSpec::from_str(&format!("opret1st:{}:3", Txid::from([0xA5; 32]))).unwrap();
+        let genesis = required_globals(builder())
+            .set_chain(Chain::Bitcoin)
+            .add_fungible_state("Assets", spec, 100)
+            .unwrap()
+            .issue_genesis()
+            .unwrap();
+        let Some(TypedAssigns::Fungible(assigns)) = genesis.assignments.get(&OS_ASSETS) else {
+            panic!("no fungible state in genesis");
+        };
+        assert!(matches!(assigns[0], Assign::Revealed { seal, .. }
+            if seal.method == CloseMethod::OpretFirst && seal.vout == Vout::from(3u32)));
+
+        let blinded = SealSpec::Blinded(
+            spec.to_graph_seal(SealSpec::DEFAULT_METHOD)
+                .unwrap()
+                .conceal(),
+        );
+        assert_eq!(
+            builder()
+                .add_fungible_state("Assets", blinded, 100)
+                .unwrap_err(),
+            BuilderError::BlindedSeal(blinded)
+        );
+    }
+
+    #[test]
+    fn issue_for_chains() {
+        let contracts = issuable()
+            .issue_for_chains(&[Chain::Bitcoin, Chain::Testnet3])
+            .unwrap();
+        assert_eq!(contracts.len(), 2);
+        assert_eq!(contracts[0].genesis.chain, Chain::Bitcoin);
+        assert_eq!(contracts[1].genesis.chain, Chain::Testnet3);
+        assert_ne!(contracts[0].contract_id(), contracts[1].contract_id());
+        assert_ne!(contracts[0].genesis.assignments, contracts[1].genesis.assignments);
+    }
+
+    #[test]
+    fn global_too_large() {
+        #[derive(Clone, Debug, Default)]
+        #[derive(StrictType, StrictEncode)]
+        #[strict_type(lib = LIB_NAME_RGB_STD)]
+        struct Oversized(MediumBlob);
+        impl StrictSerialize for Oversized {}
+
+        let value = Oversized(Confined::try_from(vec![0u8; 70_000]).unwrap());
+        assert_eq!(
+            builder().add_global_state("ContractText", value).unwrap_err(),
+            BuilderError::GlobalTooLarge {
+                name: tn!("ContractText"),
+                size: 70_003
+            }
+        );
+    }
+
+    #[test]
+    fn attachment_media_type() {
+        let builder = logo_builder();
+        let logo = attach_id(b"logo");
+        let genesis = builder
+            .clone()
+            .add_attachment("Logo", outpoint(1), logo, MediaType::from(MediaType::PNG))
+            .unwrap()
+            .issue_genesis()
+            .unwrap();
+        let Some(TypedAssigns::Attachment(assigns)) = genesis.assignments.get(&OS_LOGO) else {
+            panic!("no attachment in genesis");
+        };
+        let Assign::Revealed { state, .. } = &assigns[0] else {
+            panic!("attachment is not revealed");
+        };
+        assert_eq!(state.id, logo);
+
+        let pdf = MediaType::from(MediaType::PDF);
+        assert_eq!(
+            builder
+                .add_attachment("Logo", outpoint(1), logo, pdf.clone())
+                .unwrap_err(),
+            BuilderError::MediaTypeNotAllowed {
+                name: tn!("Logo"),
+                media_type: pdf
+            }
+        );
+    }
+
+    #[test]
+    fn issue_single_call() {
+        let nominal = Nominal::new("TEST", "Test asset", Precision::CentiMicro);
+        let globals = [
+            (tn!("Nominal"), nominal.to_strict_serialized::<{ u16::MAX as usize }>().unwrap()),
+            (
+                tn!("ContractText"),
+                ContractText::default()
+                    .to_strict_serialized::<{ u16::MAX as usize }>()
+                    .unwrap(),
+            ),
+        ];
+        let allocations = [(tn!("Assets"), outpoint(0), 60), (tn!("Assets"), outpoint(1), 40)];
+
+        let schema = schema();
+        let iface = rgb20();
+        let iimpl = iimpl(&schema, &iface);
+        let contract = issue(
+            iface.clone(),
+            schema.clone(),
+            iimpl.clone(),
+            Chain::Bitcoin,
+            &globals,
+            &allocations,
+        )
+        .unwrap();
+        assert_eq!(contract.genesis.chain, Chain::Bitcoin);
+        assert_eq!(contract.genesis.globals.get(&GS_NOMINAL).unwrap().len(), 1);
+        let Some(TypedAssigns::Fungible(assigns)) = contract.genesis.assignments.get(&OS_ASSETS)
+        else {
+            panic!("no fungible state in genesis");
+        };
+        assert_eq!(assigns.len(), 2);
+
+        let unknown = [(tn!("Unknown"), outpoint(0), 1)];
+        assert_eq!(
+            issue(iface, schema, iimpl, Chain::Bitcoin, &globals, &unknown).unwrap_err(),
+            IssueError::Builder(Box::new(BuilderError::TypeNotFound(tn!("Unknown"))))
+        );
+    }
+
+    #[test]
+    fn global_not_declared() {
+        let mut schema = schema();
+        schema.genesis.globals.remove(&GS_CONTRACT).unwrap();
+        let iface = rgb20();
+        let iimpl = iimpl(&schema, &iface);
+        let builder = ContractBuilder::with(iface, schema, iimpl)
+            .unwrap()
+            .set_chain(Chain::Bitcoin)
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap();
+
+        let err = builder
+            .clone()
+            .add_global_state("ContractText", ContractText::default())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            BuilderError::StateNotDeclaredByOperation(tn!("ContractText"), s!("`Nominal`"))
+        );
+        assert!(err.to_string().ends_with("may contain only `Nominal`."));
+        assert_eq!(
+            builder.set_terms("Terms", None).unwrap_err(),
+            BuilderError::StateNotDeclaredByOperation(tn!("ContractText"), s!("`Nominal`"))
+        );
+
+        let nominal = Nominal::new("TEST", "Test asset", Precision::CentiMicro);
+        let transition =
+            TransitionBuilder::with(rgb20(), self::schema(), fixtures::iimpl(), "Transfer")
+                .unwrap()
+                .add_global_state("Nominal", nominal)
+                .unwrap_err();
+        assert_eq!(
+            transition,
+            BuilderError::StateNotDeclaredByOperation(tn!("Nominal"), s!("no global state"))
+        );
+    }
+
+    #[test]
+    fn global_repeated_values() {
+        const COUNT: u16 = 100;
+        let text = ContractText::try_from(s!("repeated text")).unwrap();
+        let mut builder = texts_builder(COUNT);
+        let reified = builder.builder.reified.len();
+        for _ in 0..COUNT {
+            builder = builder.add_global_state("ContractText", text.clone()).unwrap();
+        }
+        assert_eq!(builder.builder.reified.len(), reified + 1);
+        assert_eq!(
+            builder.add_global_state("ContractText", text).unwrap_err(),
+            BuilderError::TooManyGlobalItems {
+                name: tn!("ContractText"),
+                max: COUNT
+            }
+        );
+    }
+
+    fn expiry_builder() -> ContractBuilder<Ready> {
+        const GS_EXPIRY: GlobalStateType = 2002;
+
+        let sem_id = StandardTypes::new().get("RGBContract.Expiry");
+        let mut schema = schema();
+        schema
+            .global_types
+            .insert(GS_EXPIRY, GlobalStateSchema {
+                sem_id,
+                max_items: 1,
+            })
+            .unwrap();
+        schema
+            .genesis
+            .globals
+            .insert(GS_EXPIRY, Occurrences::NoneOrOnce)
+            .unwrap();
+        let mut iface = rgb20();
+        iface
+            .global_state
+            .insert(tn!("Expiry"), Req::optional(sem_id))
+            .unwrap();
+        iface
+            .genesis
+            .global
+            .insert(tn!("Expiry"), Occurrences::NoneOrOnce)
+            .unwrap();
+        let mut iimpl = iimpl(&schema, &iface);
+        iimpl
+            .global_state
+            .push(NamedType::with(GS_EXPIRY, tn!("Expiry")))
+            .unwrap();
+        ContractBuilder::with(iface, schema, iimpl)
+            .unwrap()
+            .set_chain(Chain::Bitcoin)
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap()
+            .add_global_state("ContractText", ContractText::default())
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap()
+    }
+
+    #[test]
+    fn expiry() {
+        let contract = expiry_builder()
+            .set_expiry(Expiry::Height(800_000))
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        assert_eq!(contract.expiry(), Some(Expiry::Height(800_000)));
+        assert!(!contract.is_expired(799_999, i64::MAX));
+        assert!(contract.is_expired(800_000, 0));
+
+        let future = Expiry::Timestamp(i64::MAX);
+        let contract = expiry_builder().set_expiry(future).unwrap().issue_contract().unwrap();
+        assert_eq!(contract.expiry(), Some(future));
+        assert!(!contract.is_expired(u32::MAX, 1_700_000_000));
+
+        assert!(!issuable().issue_contract().unwrap().is_expired(u32::MAX, i64::MAX));
+    }
+
+    #[test]
+    fn expiry_validation() {
+        let past = Expiry::Timestamp(1_000_000_000);
+        assert_eq!(
+            expiry_builder().set_expiry(past).unwrap_err(),
+            BuilderError::ExpiryInPast(past)
+        );
+        let below_anchor = Expiry::Height(700_000);
+        assert_eq!(
+            expiry_builder()
+                .set_anchor_height(800_000)
+                .unwrap()
+                .set_expiry(below_anchor)
+                .unwrap_err(),
+            BuilderError::ExpiryInPast(below_anchor)
+        );
+        assert_eq!(
+            issuable().set_expiry(Expiry::Height(800_000)).unwrap_err(),
+            BuilderError::MetadataNotSupported(tn!("Expiry"))
+        );
+    }
+
+    #[test]
+    fn tagged_allocations() {
+        let (contract, secrets) = issuable()
+            .add_fungible_state_tagged("Assets", outpoint(1), 20_000, "advisors")
+            .unwrap()
+            .issue_contract_with_secrets()
+            .unwrap();
+        assert_eq!(secrets.len(), 2);
+        let tagged = secrets.iter().find(|secret| secret.tag.is_some()).unwrap();
+        assert_eq!(tagged.tag.as_deref(), Some("advisors"));
+        assert_eq!(tagged.assignment_type, OS_ASSETS);
+        assert_eq!(tagged.seal.vout, Vout::from(1u32));
+        assert_eq!(tagged.state.value.as_u64(), 20_000);
+
+        let genesis = contract
+            .genesis
+            .strict_encode(StrictWriter::in_memory(usize::MAX))
+            .unwrap()
+            .unbox();
+        assert!(!genesis.windows(8).any(|window| window == b"advisors"));
+    }
+
+    #[test]
+    fn push_methods() {
+        let mut builder = builder().set_chain(Chain::Bitcoin);
+        let nominal = Nominal::new("TEST", "Test asset", Precision::CentiMicro)
+            .to_strict_serialized::<{ u16::MAX as usize }>()
+            .unwrap();
+        let text = ContractText::default()
+            .to_strict_serialized::<{ u16::MAX as usize }>()
+            .unwrap();
+        builder.push_global_state(tn!("Nominal"), nominal).unwrap();
+        builder.push_global_state(tn!("ContractText"), text).unwrap();
+        for vout in 0..3 {
+            builder
+                .push_fungible_state(tn!("Assets"), SealSpec::from(outpoint(vout)), 100)
+                .unwrap();
+        }
+        assert_eq!(
+            builder
+                .push_fungible_state(tn!("Unknown"), SealSpec::from(outpoint(3)), 1)
+                .unwrap_err(),
+            BuilderError::TypeNotFound(tn!("Unknown"))
+        );
+
+        let contract = builder.issue_contract().unwrap();
+        let Some(TypedAssigns::Fungible(assigns)) = contract.genesis.assignments.get(&OS_ASSETS)
+        else {
+            panic!("no fungible state in genesis");
+        };
+        assert_eq!(assigns.len(), 3);
+    }
+
+    #[test]
+    fn non_canonical_state() {
+        let mut builder = builder();
+        let mut data = Nominal::new("TEST", "Test asset", Precision::CentiMicro)
+            .to_strict_serialized::<{ u16::MAX as usize }>()
+            .unwrap()
+            .into_inner();
+        data.push(0);
+        assert_eq!(
+            builder
+                .push_global_state(tn!("Nominal"), SmallBlob::try_from(data).unwrap())
+                .unwrap_err(),
+            BuilderError::NonCanonicalState(tn!("Nominal"))
+        );
+
+        // Length prefix is shorter than the encoded string
+        let mut data = ContractText::try_from(s!("ab"))
+            .unwrap()
+            .to_strict_serialized::<{ u16::MAX as usize }>()
+            .unwrap()
+            .into_inner();
+        data[0] -= 1;
+        assert_eq!(
+            builder
+                .push_global_state(tn!("ContractText"), SmallBlob::try_from(data).unwrap())
+                .unwrap_err(),
+            BuilderError::NonCanonicalState(tn!("ContractText"))
+        );
+    }
+
+    #[test]
+    fn schema_only() {
+        let nominal = Nominal::new("TEST", "Test asset", Precision::CentiMicro);
+        let builder = ContractBuilder::with_schema_only(schema())
+            .unwrap()
+            .set_chain(Chain::Bitcoin)
+            .add_global_state_by_id(GS_NOMINAL, nominal)
+            .unwrap()
+            .add_fungible_state_by_id(OS_ASSETS, outpoint(0), 100)
+            .unwrap();
+        assert_eq!(
+            builder
+                .clone()
+                .add_fungible_state_by_id(GS_NOMINAL, outpoint(1), 1)
+                .unwrap_err(),
+            BuilderError::TypeIdNotFound(GS_NOMINAL)
+        );
+
+        assert_eq!(
+            builder.clone().issue_contract().unwrap_err(),
+            IssueError::GlobalStateAbsent(GS_CONTRACT)
+        );
+
+        let pair = IfacePair::with(fixtures::iface(), fixtures::iimpl());
+        let contract = builder
+            .add_global_state_by_id(GS_CONTRACT, ContractText::default())
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        assert!(contract.ifaces.is_empty());
+        let mut partial = contract.clone();
+        partial.genesis.globals = none!();
+        assert_eq!(
+            partial.attach_iface(pair.clone()).unwrap_err(),
+            ForgeError::GenesisStateAbsent(tn!("ContractText"))
+        );
+        let contract = contract.attach_iface(pair.clone()).unwrap();
+        assert_eq!(contract.ifaces.get(&pair.iface_id()), Some(&pair));
+    }
+
+    #[test]
+    fn schema_only_operations() {
+        let (iface, iimpl) = schema_iface(&schema());
+        assert!(iface.extensions.is_empty());
+        assert!(iimpl.extensions.is_empty());
+        assert_eq!(iface.transitions.len(), schema().transitions.len());
+        assert!(!iface.features().inflatable);
+
+        let (iface, iimpl) = schema_iface(&issuance_schema());
+        let extension: TypeName = tn!(format!("Extension{ES_ISSUE}"));
+        assert_eq!(iface.extensions.keys().collect::<Vec<_>>(), vec![&extension]);
+        assert_eq!(iimpl.extensions, tiny_bset! { NamedType::with(ES_ISSUE, extension.clone()) });
+        assert!(iface.inflates(&extension));
+        assert!(iface.features().inflatable);
+        assert!(ContractBuilder::with_schema_only(issuance_schema()).is_ok());
+    }
+
+    #[test]
+    fn unmapped_global() {
+        const GS_UNMAPPED: GlobalStateType = 2999;
+
+        let mut builder = issuable();
+        builder
+            .builder
+            .global
+            .add_state(GS_UNMAPPED, SmallBlob::default().into())
+            .unwrap();
+        assert_eq!(
+            builder.contract_id_preview().unwrap_err(),
+            IssueError::UnmappedGlobal(GS_UNMAPPED)
+        );
+        assert_eq!(builder.issue_contract().unwrap_err(), IssueError::UnmappedGlobal(GS_UNMAPPED));
+
+        let mut builder = issuable();
+        builder.builder.iimpl.global_state = tiny_bset! {
+            NamedType::with(GS_NOMINAL, tn!("Nominal")),
+        };
+        assert_eq!(builder.issue_contract().unwrap_err(), IssueError::UnmappedGlobal(GS_CONTRACT));
+    }
+
+    #[test]
+    fn undeclared_inflation() {
+        let schema = issuance_schema();
+        let iface = rgb20();
+        let iimpl = issuance_iimpl(&schema, &iface);
+        assert_eq!(
+            ContractBuilder::with(iface, schema.clone(), iimpl).unwrap_err(),
+            ForgeError::Semantics(ImplErrors(vec![ImplError::UndeclaredInflation(ES_ISSUE)]))
+        );
+
+        let iface = issuance_iface();
+        assert!(iface.features().inflatable);
+        let iimpl = issuance_iimpl(&schema, &iface);
+        assert!(ContractBuilder::with(iface, schema, iimpl).is_ok());
+    }
+
+    #[test]
+    fn remaining_capacity() {
+        let mut builder = builder().set_chain(Chain::Bitcoin);
+        assert_eq!(builder.remaining_capacity("Assets"), Some(255));
+        builder.push_fungible_state(tn!("Assets"), outpoint(0).into(), 1).unwrap();
+        assert_eq!(builder.remaining_capacity("Assets"), Some(254));
+        builder.push_fungible_state(tn!("Assets"), outpoint(1).into(), 1).unwrap();
+        assert_eq!(builder.remaining_capacity("Assets"), Some(253));
+        assert_eq!(builder.remaining_capacity("Unknown"), None);
+    }
+
+    #[test]
+    fn typed_global() {
+        let types = StandardTypes::new();
+        let nominal = Nominal::new("TEST", "Test asset", Precision::CentiMicro);
+        let builder = builder().add_global_typed("Nominal", nominal).unwrap();
+        assert_eq!(
+            builder
+                .add_global_typed("Nominal", ContractText::default())
+                .unwrap_err(),
+            BuilderError::TypeMismatch {
+                expected: types.get("RGBContract.Nominal"),
+                got: types.get("RGBContract.ContractText"),
+            }
+        );
+    }
+
+    #[test]
+    fn required_globals_absent() {
+        let builder = builder()
+            .set_chain(Chain::Bitcoin)
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap();
+        assert_eq!(
+            builder.contract_id_preview().unwrap_err(),
+            IssueError::GlobalStateAbsent(GS_CONTRACT)
+        );
+        assert_eq!(
+            builder.issue_contract().unwrap_err(),
+            IssueError::GlobalStateAbsent(GS_CONTRACT)
+        );
+    }
+
+    #[test]
+    fn no_globals() {
+        let mut schema = schema();
+        schema.genesis.globals = none!();
+        let iface = rgb20();
+        let iimpl = iimpl(&schema, &iface);
+        let genesis = ContractBuilder::with(iface, schema, iimpl)
+            .unwrap()
+            .set_chain(Chain::Bitcoin)
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap()
+            .issue_genesis()
+            .unwrap();
+        assert!(genesis.globals.is_empty());
+        assert!(genesis.assignments.contains_key(&OS_ASSETS));
+    }
+
+    #[test]
+    fn events() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let log = events.clone();
+        let contract = builder()
+            .on_event(move |event| log.lock().unwrap().push(event))
+            .set_chain(Chain::Bitcoin)
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap()
+            .add_global_state("ContractText", ContractText::default())
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(1), 50)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0], BuilderEvent::GlobalAdded(tn!("Nominal")));
+        assert_eq!(events[1], BuilderEvent::GlobalAdded(tn!("ContractText")));
+        assert!(events[2..4]
+            .iter()
+            .all(|event| matches!(event, BuilderEvent::AllocationAdded { name, .. }
+                if *name == tn!("Assets"))));
+        assert_eq!(events[4], BuilderEvent::Issued(contract.contract_id()));
+    }
+
+    #[test]
+    fn fungible_from_csv() {
+        let csv = format!(
+            "outpoint,amount\n{},1.5\n\n{}, 2\n",
+            outpoint(0),
+            outpoint(1)
+        );
+        let genesis = required_globals(builder().set_chain(Chain::Bitcoin))
+            .add_fungible_from_csv("Assets", &csv, Precision::Centi)
+            .unwrap()
+            .issue_contract()
+            .unwrap()
+            .genesis;
+        let Some(TypedAssigns::Fungible(assigns)) = genesis.assignments.get(&OS_ASSETS) else {
+            panic!("no fungible state in genesis");
+        };
+        let mut amounts = assigns
+            .iter()
+            .filter_map(|assign| match assign {
+                Assign::Revealed { state, .. } => Some(state.value.as_u64()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        amounts.sort();
+        assert_eq!(amounts, [150, 200]);
+
+        let malformed = |csv: String| {
+            builder()
+                .set_chain(Chain::Bitcoin)
+                .add_fungible_from_csv("Assets", &csv, Precision::Centi)
+                .unwrap_err()
+        };
+        assert!(matches!(
+            malformed(format!("{},1\n{}, 1.005", outpoint(0), outpoint(1))),
+            BuilderError::Csv { line: 2, col: 69, .. }
+        ));
+        assert!(matches!(
+            malformed(format!("{},1\n\n{}", outpoint(0), outpoint(1))),
+            BuilderError::Csv { line: 3, col: 67, .. }
+        ));
+        assert!(matches!(
+            malformed(format!("{},1\n  txid:0,1", outpoint(0))),
+            BuilderError::Csv { line: 2, col: 3, .. }
+        ));
+        assert!(matches!(
+            malformed(format!("{},1,2", outpoint(0))),
+            BuilderError::Csv { line: 1, col: 70, .. }
+        ));
+    }
+
+    #[test]
+    fn type_system_swap() {
+        let builder = issuable();
+        let contract = builder.clone().with_type_system(schema().type_system).issue_contract();
+        assert!(contract.is_ok());
+        assert_eq!(
+            builder
+                .with_type_system(TypeSystem::default())
+                .issue_contract()
+                .unwrap_err(),
+            IssueError::GlobalReifyFailed {
+                name: tn!("Nominal")
+            }
+        );
     }
 }
@@ -22,23 +22,54 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::{iter, slice};
 
-use amplify::confinement::{LargeVec, MediumBlob, SmallOrdMap, SmallOrdSet, TinyOrdMap};
+use amplify::confinement::{
+    self, LargeVec, MediumBlob, SmallOrdMap, SmallOrdSet, TinyOrdMap, TinyString,
+};
+use amplify::Wrapper;
+use bp::{Chain, Txid};
 use commit_verify::Conceal;
 use rgb::validation::{AnchoredBundle, ConsignmentApi};
 use rgb::{
-    validation, AttachId, BundleId, ContractHistory, ContractId, Extension, Genesis, OpId, OpRef,
-    Operation, OrderedTxid, Schema, SchemaId, SecretSeal, SubSchema, Transition, TransitionBundle,
+    validation, AssignmentsType, AttachId, BundleId, ContractHistory, ContractId, Extension,
+    Genesis, GraphSeal, OpId, OpRef, Operation, OrderedTxid, Schema, SchemaId, SecretSeal,
+    SubSchema, Transition, TransitionBundle,
 };
-use strict_encoding::{StrictDeserialize, StrictDumb, StrictSerialize};
+use strict_encoding::{StrictDeserialize, StrictDumb, StrictSerialize, TypeName};
 
+use super::contract::{for_each_assign, AssignView};
 use super::{ContainerVer, ContentId, ContentSigs, Terminal};
 use crate::interface::{IfaceId, IfacePair};
-use crate::resolvers::ResolveHeight;
+use crate::resolvers::{OperationProvider, ResolveHeight};
 use crate::LIB_NAME_RGB_STD;
 
 pub type Transfer = Consignment<true>;
 pub type Contract = Consignment<false>;
 
+/// Issued supply of a fungible contract, as declared by the genesis global
+/// state `IssuedSupply`.
+#[derive(Wrapper, Copy, Clone, Eq, PartialEq, Debug, Default, From)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+struct IssuedSupply(u64);
+
+impl StrictSerialize for IssuedSupply {}
+impl StrictDeserialize for IssuedSupply {}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum MergeError {
+    /// consignments are issued on different chains: expected {expected},
+    /// found {found}.
+    ChainMismatch { expected: Chain, found: Chain },
+
+    /// consignments belong to different contracts {0::<0} and {1::<0}.
+    ContractMismatch(ContractId, ContractId),
+
+    #[from]
+    #[display(inner)]
+    Confinement(confinement::Error),
+}
+
 /// Consignment represents contract-specific data, always starting with genesis,
 /// which must be valid under client-side-validation rules (i.e. internally
 /// consistent and properly committed into the commitment layer, like bitcoin
@@ -102,6 +133,31 @@ pub struct Consignment<const TYPE: bool> {
     /// Signatures on the pieces of content which are the part of the
     /// consignment.
     pub signatures: TinyOrdMap<ContentId, ContentSigs>,
+
+    /// Height of the block from which the contract operations are expected to
+    /// get anchored, provided by the issuer as a hint for the light clients to
+    /// bound their chain scan. The value is not committed to by the genesis.
+    pub anchor_height: Option<u32>,
+
+    /// Category of the contract, like "stablecoins" or "collectibles", which
+    /// wallets use to group contracts in their user interface.
+    ///
+    /// This is local metadata: it is not committed to by the genesis, is not
+    /// validated, and may be changed by any party holding the container.
+    pub category: Option<TinyString>,
+}
+
+/// Numbers of the assignments of an owned state type, distinguishing the
+/// ones which seal or state is concealed.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct AssignmentCounts {
+    pub revealed: usize,
+    pub concealed: usize,
 }
 
 impl<const TYPE: bool> StrictSerialize for Consignment<TYPE> {}
@@ -119,13 +175,174 @@ impl<const TYPE: bool> Consignment<TYPE> {
     #[inline]
     pub fn contract_id(&self) -> ContractId { self.genesis.contract_id() }
 
+    /// Lists ids of all interfaces supported by the contract, in sorted order.
+    pub fn iface_ids(&self) -> Vec<IfaceId> { self.ifaces.keys().copied().collect() }
+
     pub fn validation_status(&self) -> Option<&validation::Status> {
         self.validation_status.as_ref()
     }
 
+    /// Drops the status of the latest validation, which doesn't hold once
+    /// the consignment data are changed.
+    pub(crate) fn reset_validation_status(&mut self) { self.validation_status = None; }
+
+    /// Lists witness transactions of all bundles of the consignment.
+    pub fn witness_txids(&self) -> BTreeSet<Txid> {
+        self.bundles
+            .iter()
+            .map(|anchored_bundle| anchored_bundle.anchor.txid)
+            .collect()
+    }
+
+    /// Counts assignments of each owned state type made by all operations of
+    /// the consignment, including the spent ones. Assignments with concealed
+    /// seal or state are counted separately, since their state can't be
+    /// reported by the read APIs.
+    pub fn assignment_counts(&self) -> BTreeMap<AssignmentsType, AssignmentCounts> {
+        let mut counts = BTreeMap::<_, AssignmentCounts>::new();
+        self.for_each_op_assign(|_, _, view| {
+            let counts = counts.entry(view.ty).or_default();
+            if view.seal.is_some() && view.state.is_some() {
+                counts.revealed += 1;
+            } else {
+                counts.concealed += 1;
+            }
+        });
+        counts
+    }
+
+    /// Decodes the first item of the genesis global state with the given
+    /// name, defined by the first of the contract interfaces declaring it.
+    pub(crate) fn genesis_global<T: StrictDeserialize>(&self, name: &TypeName) -> Option<T> {
+        self.ifaces
+            .values()
+            .find(|pair| pair.iface.global_state.contains_key(name))?
+            .iimpl
+            .global_first(name, |ty| self.genesis.globals.get(&ty)?.first())
+    }
+
+    /// Decodes the issued supply declared by the genesis, if the interface of
+    /// the pair defines global state `IssuedSupply`.
+    pub(crate) fn declared_supply(&self, pair: &IfacePair) -> Option<u64> {
+        let name = tn!("IssuedSupply");
+        if !pair.iface.global_state.contains_key(&name) {
+            return None;
+        }
+        pair.iimpl
+            .global_first(&name, |ty| self.genesis.globals.get(&ty)?.first())
+            .map(IssuedSupply::into_inner)
+    }
+
+    /// Calls the function for each assignment of the consignment operations,
+    /// together with the id of the operation and the witness transaction id
+    /// of the bundle containing it.
+    pub(crate) fn for_each_op_assign(
+        &self,
+        mut f: impl FnMut(OpId, Option<Txid>, AssignView<GraphSeal>),
+    ) {
+        let genesis_id = self.genesis.id();
+        for_each_assign(&self.genesis.assignments, |view| f(genesis_id, None, view.transmutate()));
+        for anchored_bundle in &self.bundles {
+            let witness_txid = Some(anchored_bundle.anchor.txid);
+            for item in anchored_bundle.bundle.values() {
+                if let Some(transition) = &item.transition {
+                    let opid = transition.id();
+                    for_each_assign(&transition.assignments, |view| f(opid, witness_txid, view));
+                }
+            }
+        }
+        for extension in &self.extensions {
+            let opid = extension.id();
+            for_each_assign(&extension.assignments, |view| f(opid, None, view.transmutate()));
+        }
+    }
+
+    /// Merges data from other consignment for the same contract into this
+    /// consignment. Bundles and extensions which are already present are
+    /// not duplicated.
+    ///
+    /// Since the merged consignment must be validated once again, the
+    /// validation status of the consignment is reset.
+    pub fn merge<const OTHER: bool>(
+        &mut self,
+        other: Consignment<OTHER>,
+    ) -> Result<(), MergeError> {
+        if self.genesis.chain != other.genesis.chain {
+            return Err(MergeError::ChainMismatch {
+                expected: self.genesis.chain,
+                found: other.genesis.chain,
+            });
+        }
+        if self.contract_id() != other.contract_id() {
+            return Err(MergeError::ContractMismatch(self.contract_id(), other.contract_id()));
+        }
+
+        for (iface_id, iface_pair) in other.ifaces {
+            if !self.ifaces.contains_key(&iface_id) {
+                self.ifaces.insert(iface_id, iface_pair)?;
+            }
+        }
+        for terminal in other.terminals {
+            self.terminals.push(terminal)?;
+        }
+        // TODO: Merge revealed state for the bundles which are already known
+        let known_bundles = self
+            .bundles
+            .iter()
+            .map(|anchored_bundle| anchored_bundle.bundle.bundle_id())
+            .collect::<BTreeSet<_>>();
+        for anchored_bundle in other.bundles {
+            if !known_bundles.contains(&anchored_bundle.bundle.bundle_id()) {
+                self.bundles.push(anchored_bundle)?;
+            }
+        }
+        let known_extensions = self.extensions.iter().map(Extension::id).collect::<BTreeSet<_>>();
+        for extension in other.extensions {
+            if !known_extensions.contains(&extension.id()) {
+                self.extensions.push(extension)?;
+            }
+        }
+        for (attach_id, data) in other.attachments {
+            if !self.attachments.contains_key(&attach_id) {
+                self.attachments.insert(attach_id, data)?;
+            }
+        }
+        for (content_id, sigs) in other.signatures {
+            match self.signatures.get_mut(&content_id) {
+                Some(prev_sigs) => prev_sigs.extend(sigs)?,
+                None => {
+                    self.signatures.insert(content_id, sigs)?;
+                }
+            }
+        }
+
+        self.validation_status = None;
+        Ok(())
+    }
+
     pub fn build_history<R: ResolveHeight>(
         &self,
         resolver: &mut R,
+    ) -> Result<ContractHistory, R::Error> {
+        self.compose_history(resolver, None)
+    }
+
+    /// Builds contract history as of the given block height, including only
+    /// the state transitions with witness transactions mined at or below the
+    /// height. Genesis is always included; state extensions are included if
+    /// they are used by the included transitions.
+    pub fn build_history_until<R: ResolveHeight>(
+        &self,
+        resolver: &mut R,
+        height: u32,
+    ) -> Result<ContractHistory, R::Error> {
+        self.compose_history(resolver, Some(height))
+    }
+
+    fn compose_history<R: ResolveHeight>(
+        &self,
+        resolver: &mut R,
+        max_height: Option<u32>,
     ) -> Result<ContractHistory, R::Error> {
         let mut history = ContractHistory::with(
             self.schema_id(),
@@ -146,6 +363,9 @@ impl<const TYPE: bool> Consignment<TYPE> {
                 if let Some(transition) = &item.transition {
                     let txid = anchored_bundle.anchor.txid;
                     let height = resolver.resolve_height(txid)?;
+                    if matches!(max_height, Some(max) if height > max) {
+                        continue;
+                    }
                     let ord_txid = OrderedTxid::new(height, txid);
                     history.add_transition(transition, ord_txid);
                     for (id, used) in &mut extension_idx {
@@ -178,6 +398,33 @@ impl<const TYPE: bool> Consignment<TYPE> {
     }
 }
 
+#[cfg(any(test, feature = "fixtures"))]
+impl Contract {
+    /// Converts the contract into the transfer of the state assigned to the
+    /// terminals, without validating it.
+    pub(crate) fn into_transfer(self, terminals: impl IntoIterator<Item = Terminal>) -> Transfer {
+        Transfer {
+            validation_status: None,
+            version: self.version,
+            transfer: true,
+            schema: self.schema,
+            ifaces: self.ifaces,
+            genesis: self.genesis,
+            terminals: SmallOrdSet::try_from_iter(terminals).expect("too many terminals"),
+            bundles: self.bundles,
+            extensions: self.extensions,
+            attachments: self.attachments,
+            signatures: self.signatures,
+            anchor_height: self.anchor_height,
+            category: self.category,
+        }
+    }
+}
+
+impl<const TYPE: bool> OperationProvider for Consignment<TYPE> {
+    fn operation(&self, opid: OpId) -> Option<OpRef> { ConsignmentApi::operation(self, opid) }
+}
+
 impl<const TYPE: bool> ConsignmentApi for Consignment<TYPE> {
     type BundleIter<'container>
     = slice::Iter<'container, AnchoredBundle> where Self: 'container;
@@ -244,7 +491,9 @@ impl<const TYPE: bool> ConsignmentApi for Consignment<TYPE> {
         exceptions
     }
 
-    fn has_operation(&self, opid: OpId) -> bool { self.operation(opid).is_some() }
+    fn has_operation(&self, opid: OpId) -> bool {
+        ConsignmentApi::operation(self, opid).is_some()
+    }
 
     fn known_transitions_by_bundle_id(&self, bundle_id: BundleId) -> Option<Vec<&Transition>> {
         self.bundle_by_id(bundle_id).map(|bundle| {
@@ -255,3 +504,47 @@ impl<const TYPE: bool> ConsignmentApi for Consignment<TYPE> {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::containers::builder::test::{issuable, NoHeights};
+
+    #[test]
+    fn iface_ids() {
+        let mut contract = issuable().issue_contract().unwrap();
+        let IfacePair { mut iface, mut iimpl } = contract.ifaces.values().next().unwrap().clone();
+        iface.name = tn!("RGB20Alt");
+        iimpl.iface_id = iface.iface_id();
+        let pair = IfacePair::with(iface, iimpl);
+        let mut expected = vec![contract.ifaces.keys().next().copied().unwrap(), pair.iface_id()];
+        expected.sort();
+        contract.ifaces.insert(pair.iface_id(), pair).unwrap();
+        assert_eq!(contract.iface_ids(), expected);
+    }
+
+    #[test]
+    fn history_until() {
+        let contract = issuable().issue_contract().unwrap();
+        let history = contract.build_history(&mut NoHeights).unwrap();
+        for height in [0, 800_000, u32::MAX] {
+            assert_eq!(contract.build_history_until(&mut NoHeights, height).unwrap(), history);
+        }
+    }
+
+    #[test]
+    fn merge_chain_mismatch() {
+        let mut contracts = issuable()
+            .issue_for_chains(&[Chain::Bitcoin, Chain::Testnet3])
+            .unwrap();
+        let testnet = contracts.pop().unwrap();
+        let mut bitcoin = contracts.pop().unwrap();
+        assert_eq!(bitcoin.merge(testnet), Err(MergeError::ChainMismatch {
+            expected: Chain::Bitcoin,
+            found: Chain::Testnet3,
+        }));
+        bitcoin.merge(bitcoin.clone()).unwrap();
+        assert_eq!(bitcoin.terminals.len(), 0);
+        assert!(bitcoin.validation_status().is_none());
+    }
+}
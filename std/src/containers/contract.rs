@@ -19,16 +19,252 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use rgb::{Genesis, SubSchema};
+use std::collections::BTreeSet;
+use std::mem;
 
-use crate::containers::{ContainerVer, Contract};
-use crate::interface::IfacePair;
+use amplify::confinement::{
+    Confined, LargeVec, MediumBlob, SmallOrdMap, SmallOrdSet, TinyOrdMap, TinyString,
+};
+use amplify::Wrapper;
+use bp::seals::txout::TxPtr;
+use bp::secp256k1::rand::{self, RngCore};
+use bp::{Chain, Outpoint, Txid};
+use commit_verify::Conceal;
+use rgb::validation::AnchoredBundle;
+use rgb::{
+    fungible, Assign, Assignments, AssignmentsRef, AssignmentsType, AttachId, ConfidentialState,
+    ContractId, ExposedSeal, ExposedState, Extension, Genesis, GenesisSeal, GraphSeal, Operation,
+    Opout, SchemaId, SecretSeal, StateCommitment, StateData, StateSchema, SubSchema, TxoSeal,
+    TypedAssigns,
+};
+use strict_encoding::{StrictDeserialize, StrictSerialize, TypeName};
+use strict_types::SemId;
+
+use crate::containers::{
+    ContainerVer, ContentId, ContentSigs, Contract, ForgeError, SealSecret, Terminal,
+};
+use crate::interface::{IfaceId, IfacePair};
+use crate::stl::Expiry;
+use crate::LIB_NAME_RGB_STD;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum RevealError {
+    /// contract doesn't contain concealed seal {0} matching the provided secret.
+    NoMatch(SecretSeal),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum IntegrityError {
+    /// contract genesis is issued under schema {expected::<0}, while the
+    /// contract contains schema {found::<0}; the schema might be tampered.
+    SchemaIdMismatch { expected: SchemaId, found: SchemaId },
+
+    /// implementation of interface {0} is made for a schema different from
+    /// the contract schema.
+    ImplSchemaMismatch(IfaceId),
+
+    /// contract can't have more than 255 interfaces.
+    TooManyIfaces,
+}
+
+/// Contract data used to prefill payment invoices.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct InvoiceTemplate {
+    pub contract_id: ContractId,
+    pub chain: Chain,
+    /// Interface under which the payment is requested; absent if the contract
+    /// doesn't have any interface implementation.
+    pub iface_id: Option<IfaceId>,
+    /// Name of the owned state which is assigned by the payment; absent if the
+    /// interface doesn't define owned state allocated by the genesis.
+    pub assignment: Option<TypeName>,
+}
+
+/// Seal of the owned state allocated by a contract operation, together with
+/// the type of the owned state.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub enum SealView {
+    /// Seal which definition is known. Seals pointing to the witness
+    /// transaction are resolved using the anchor of the operation bundle.
+    Revealed(AssignmentsType, Outpoint),
+
+    /// Seal known only in its concealed form, or a seal which outpoint can't
+    /// be resolved.
+    Concealed(AssignmentsType, SecretSeal),
+}
+
+impl SealView {
+    pub fn assignment_type(&self) -> AssignmentsType {
+        match self {
+            SealView::Revealed(ty, _) | SealView::Concealed(ty, _) => *ty,
+        }
+    }
+}
+
+/// Allocation of the contract state which is not spent by any known operation,
+/// such that its seal must be watched on chain to detect the witness
+/// transaction spending it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct WatchItem {
+    pub contract_id: ContractId,
+    pub opout: Opout,
+    /// Seal of the allocation. Seals known only in their concealed form must
+    /// be matched by the wallet against the seals it has created.
+    pub seal: SealView,
+    /// Fungible amount of the allocation, if it is revealed.
+    pub amount: Option<u64>,
+    /// Witness transaction of the bundle which made the allocation; absent
+    /// for the allocations made by genesis and state extensions.
+    pub witness_txid: Option<Txid>,
+}
+
+/// Sum of the unspent fungible allocations of the contract, which accounts
+/// for the allocations with concealed amounts instead of counting them as
+/// zero.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub enum Balance {
+    /// All allocations are revealed, and their amounts sum up to the value.
+    Known(u64),
+
+    /// Some of the allocations are concealed; the value is a sum of the
+    /// revealed ones only.
+    Partial { revealed: u64, concealed: usize },
+
+    /// All allocations are concealed, thus the balance can't be known.
+    Unknown { concealed: usize },
+}
+
+impl Balance {
+    /// Returns the balance only if it is fully known.
+    pub fn known(self) -> Option<u64> {
+        match self {
+            Balance::Known(value) => Some(value),
+            Balance::Partial { .. } | Balance::Unknown { .. } => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum AllocationProofError {
+    /// allocation proof is made for contract {found} and not {expected}.
+    ContractMismatch {
+        expected: ContractId,
+        found: ContractId,
+    },
+
+    /// allocation proof doesn't reveal any state assigned to {0}.
+    NotRevealed(Outpoint),
+
+    /// allocation proof reveals state assigned to {0}, which is not the
+    /// outpoint the proof is made for.
+    Leak(Outpoint),
+}
+
+/// Proof of the state allocated to a specific outpoint by the contract
+/// genesis. The proof contains the genesis in which all other allocations are
+/// concealed; since the genesis commitment is computed over the concealed
+/// state, the proof commits to the same contract id as the original genesis.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+pub struct AllocationProof {
+    pub outpoint: Outpoint,
+    pub genesis: Genesis,
+}
+
+/// Contract stripped of its schema and interfaces, such that the schema and
+/// interfaces can be stored once for all contracts sharing them. All other
+/// contract data are kept, allowing to reconstitute the contract with
+/// [`GenesisOnly::with_schema`].
+#[derive(Clone, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+pub struct GenesisOnly {
+    pub version: ContainerVer,
+    pub genesis: Genesis,
+    pub terminals: SmallOrdSet<Terminal>,
+    pub bundles: LargeVec<AnchoredBundle>,
+    pub extensions: LargeVec<Extension>,
+    pub attachments: SmallOrdMap<AttachId, MediumBlob>,
+    pub signatures: TinyOrdMap<ContentId, ContentSigs>,
+    pub anchor_height: Option<u32>,
+    pub category: Option<TinyString>,
+}
+
+impl StrictSerialize for GenesisOnly {}
+impl StrictDeserialize for GenesisOnly {}
+
+impl GenesisOnly {
+    #[inline]
+    pub fn contract_id(&self) -> ContractId { self.genesis.contract_id() }
+
+    /// Reconstitutes the contract from the stripped data, the schema and the
+    /// interfaces. The schema must be the one under which the genesis was
+    /// issued, and all interface implementations must be made for it.
+    pub fn with_schema(
+        self,
+        schema: SubSchema,
+        ifaces: Vec<IfacePair>,
+    ) -> Result<Contract, IntegrityError> {
+        let found = schema.schema_id();
+        if found != self.genesis.schema_id {
+            return Err(IntegrityError::SchemaIdMismatch {
+                expected: self.genesis.schema_id,
+                found,
+            });
+        }
+        if let Some(pair) = ifaces.iter().find(|pair| pair.iimpl.schema_id != found) {
+            return Err(IntegrityError::ImplSchemaMismatch(pair.iface_id()));
+        }
+        let ifaces = ifaces.into_iter().map(|pair| (pair.iface_id(), pair));
+        let ifaces =
+            TinyOrdMap::try_from_iter(ifaces).map_err(|_| IntegrityError::TooManyIfaces)?;
+        Ok(Contract {
+            validation_status: None,
+            version: self.version,
+            transfer: false,
+            schema,
+            ifaces,
+            genesis: self.genesis,
+            terminals: self.terminals,
+            bundles: self.bundles,
+            extensions: self.extensions,
+            attachments: self.attachments,
+            signatures: self.signatures,
+            anchor_height: self.anchor_height,
+            category: self.category,
+        })
+    }
+}
 
 impl Contract {
     pub fn new(schema: SubSchema, iface: IfacePair, genesis: Genesis) -> Self {
         Contract {
             validation_status: None,
-            version: ContainerVer::V1,
+            version: ContainerVer::V2,
             transfer: false,
             schema,
             ifaces: tiny_bmap! { iface.iface_id() => iface },
@@ -38,6 +274,1090 @@ impl Contract {
             extensions: none!(),
             attachments: none!(),
             signatures: none!(),
+            anchor_height: None,
+            category: None,
+        }
+    }
+
+    /// Verifies that the schema embedded into the contract is the schema under
+    /// which the contract genesis was issued. Contracts received from
+    /// untrusted sources must be checked before their state is interpreted
+    /// with the embedded schema.
+    pub fn verify_integrity(&self) -> Result<(), IntegrityError> {
+        let found = self.schema_id();
+        if found != self.genesis.schema_id {
+            return Err(IntegrityError::SchemaIdMismatch {
+                expected: self.genesis.schema_id,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    /// Splits the contract into its schema, interfaces and the rest of the
+    /// contract data, which can be stored separately.
+    pub fn strip_schema(self) -> (GenesisOnly, SubSchema, Vec<IfacePair>) {
+        let genesis_only = GenesisOnly {
+            version: self.version,
+            genesis: self.genesis,
+            terminals: self.terminals,
+            bundles: self.bundles,
+            extensions: self.extensions,
+            attachments: self.attachments,
+            signatures: self.signatures,
+            anchor_height: self.anchor_height,
+            category: self.category,
+        };
+        (genesis_only, self.schema, self.ifaces.into_inner().into_values().collect())
+    }
+
+    /// Produces copy of the contract which can be shared with others, without
+    /// the private metadata of its holder: the contract [`category`] and the
+    /// local validation status. All the data committed to by the contract, as
+    /// well as the public hints like the anchor height, are kept.
+    ///
+    /// [`category`]: Contract::category
+    pub fn public_copy(&self) -> Contract {
+        let mut contract = self.clone();
+        contract.category = None;
+        contract.validation_status = None;
+        contract
+    }
+
+    /// Constructs template for the invoices requesting payments under the
+    /// contract. The template uses the first of the contract interfaces and
+    /// the first owned state of that interface allocated by the genesis.
+    pub fn invoice_template(&self) -> InvoiceTemplate {
+        let pair = self.ifaces.values().next();
+        let assignment = pair.and_then(|pair| {
+            pair.iface
+                .owned_state
+                .keys()
+                .find(|name| {
+                    pair.iimpl
+                        .assignments_type(name)
+                        .map(|ty| self.genesis.assignments.contains_key(&ty))
+                        .unwrap_or_default()
+                })
+                .cloned()
+        });
+        InvoiceTemplate {
+            contract_id: self.contract_id(),
+            chain: self.genesis.chain,
+            iface_id: pair.map(IfacePair::iface_id),
+            assignment,
+        }
+    }
+
+    /// Attaches a new interface implementation to the contract, allowing to
+    /// work with its state via the interface without reissuing the contract.
+    /// The implementation must match the contract schema, and the genesis
+    /// must contain all state required by the interface.
+    ///
+    /// If the contract already has an implementation of the same interface,
+    /// it is replaced only if the new implementation has a newer version.
+    pub fn attach_iface(mut self, pair: IfacePair) -> Result<Contract, ForgeError> {
+        let iface_id = pair.iface_id();
+        if pair.iimpl.iface_id != iface_id {
+            return Err(ForgeError::InterfaceMismatch);
+        }
+        if pair.iimpl.schema_id != self.schema.schema_id() {
+            return Err(ForgeError::SchemaMismatch);
+        }
+        pair.iimpl.validate_interface_semantics(&pair.iface, &self.schema)?;
+        self.check_genesis_iface(&pair)?;
+        if let Some(existing) = self.ifaces.get(&iface_id) {
+            if pair.iimpl.is_newer_than(&existing.iimpl) != Some(true) {
+                return Err(ForgeError::IfaceConflict(iface_id));
+            }
+        }
+        self.ifaces.insert(iface_id, pair)?;
+        Ok(self)
+    }
+
+    /// Checks that the genesis contains all state which the interface requires
+    /// from the contract genesis.
+    fn check_genesis_iface(&self, pair: &IfacePair) -> Result<(), ForgeError> {
+        let genesis = &pair.iface.genesis;
+        for (name, occurrences) in &genesis.global {
+            let present = pair
+                .iimpl
+                .global_type(name)
+                .and_then(|ty| self.genesis.globals.get(&ty))
+                .map(|values| values.len())
+                .unwrap_or_default();
+            if present < occurrences.min_value() as usize {
+                return Err(ForgeError::GenesisStateAbsent(name.clone()));
+            }
+        }
+        for (name, occurrences) in &genesis.assignments {
+            let present = pair
+                .iimpl
+                .assignments_type(name)
+                .map(|ty| self.genesis.assignments.contains_key(&ty))
+                .unwrap_or_default();
+            if !present && occurrences.min_value() > 0 {
+                return Err(ForgeError::GenesisStateAbsent(name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the moment after which the contract expires, taken from the
+    /// `Expiry` global state of the first contract interface defining it.
+    pub fn expiry(&self) -> Option<Expiry> { self.genesis_global(&tn!("Expiry")) }
+
+    /// Checks whether the contract has expired at the given block height and
+    /// UNIX timestamp. Contracts without expiry never expire.
+    pub fn is_expired(&self, height: u32, timestamp: i64) -> bool {
+        self.expiry()
+            .map(|expiry| expiry.has_passed(height, timestamp))
+            .unwrap_or_default()
+    }
+
+    /// Lists all seals which are present in the contract operations only in
+    /// their concealed form.
+    pub fn concealed_seals(&self) -> BTreeSet<SecretSeal> {
+        let mut seals = BTreeSet::new();
+        self.for_each_op_assign(|_, _, view| {
+            if view.seal.is_none() {
+                seals.insert(view.secret_seal);
+            }
+        });
+        seals
+    }
+
+    /// Lists seals of all allocations made by the contract operations, both
+    /// revealed and concealed, in the order of the operations.
+    pub fn all_seals(&self) -> Vec<SealView> {
+        let mut seals = vec![];
+        self.for_each_op_assign(|_, witness_txid, view| seals.push(view.seal_view(witness_txid)));
+        seals
+    }
+
+    /// Lists allocations which are not spent by any of the contract state
+    /// transitions, thus their seals must be watched on chain.
+    pub fn watch_list(&self) -> Vec<WatchItem> {
+        let spent = self.spent_opouts();
+        let contract_id = self.contract_id();
+        let mut items = vec![];
+        self.for_each_op_assign(|opid, witness_txid, view| {
+            let opout = Opout::new(opid, view.ty, view.no);
+            if !spent.contains(&opout) {
+                items.push(WatchItem {
+                    contract_id,
+                    opout,
+                    seal: view.seal_view(witness_txid),
+                    amount: view.amount(),
+                    witness_txid,
+                });
+            }
+        });
+        items
+    }
+
+    /// Lists outputs of the contract operations spent by the known state
+    /// transitions.
+    pub(crate) fn spent_opouts(&self) -> BTreeSet<Opout> {
+        self.bundles
+            .iter()
+            .flat_map(|anchored_bundle| anchored_bundle.bundle.values())
+            .filter_map(|item| item.transition.as_ref())
+            .flat_map(|transition| transition.inputs.iter())
+            .flat_map(|(prev_id, prev)| {
+                prev.iter().flat_map(move |(ty, nos)| {
+                    nos.iter().map(move |no| Opout::new(*prev_id, *ty, *no))
+                })
+            })
+            .collect()
+    }
+
+    /// Computes balance of the owned state with the given interface name
+    /// over the unspent allocations. Returns `None` if none of the contract
+    /// interfaces defines such state.
+    pub fn balance(&self, name: &TypeName) -> Option<Balance> {
+        let ty = self
+            .ifaces
+            .values()
+            .find_map(|pair| pair.iimpl.assignments_type(name))?;
+        let mut revealed = 0u64;
+        let mut known = 0usize;
+        let mut concealed = 0usize;
+        for item in self.watch_list() {
+            if item.seal.assignment_type() != ty {
+                continue;
+            }
+            match item.amount {
+                Some(amount) => {
+                    revealed = revealed.saturating_add(amount);
+                    known += 1;
+                }
+                None => concealed += 1,
+            }
+        }
+        Some(match (known, concealed) {
+            (_, 0) => Balance::Known(revealed),
+            (0, concealed) => Balance::Unknown { concealed },
+            (_, concealed) => Balance::Partial {
+                revealed,
+                concealed,
+            },
+        })
+    }
+
+    /// Lists outputs of the contract operations which assign state to the
+    /// outpoint, including the outputs which are already spent.
+    pub fn outputs_at(&self, outpoint: Outpoint) -> Vec<Opout> {
+        let mut opouts = vec![];
+        self.for_each_op_assign(|opid, witness_txid, view| {
+            if view.outpoint(witness_txid) == Some(outpoint) {
+                opouts.push(Opout::new(opid, view.ty, view.no));
+            }
+        });
+        opouts
+    }
+
+    /// Lists ids of all attachments referenced by the revealed state of the
+    /// contract operations.
+    pub fn attachment_ids(&self) -> BTreeSet<AttachId> {
+        let mut ids = BTreeSet::new();
+        self.for_each_op_assign(|_, _, view| {
+            if let Some(StateData::Attachment(attach)) = view.state {
+                ids.insert(attach.id);
+            }
+        });
+        ids
+    }
+
+    /// Computes set of semantic types which are directly referenced by the
+    /// contract genesis via its metadata, global and structured owned state.
+    ///
+    /// Types of state which is defined by the schema but not present in the
+    /// genesis are not included, allowing to ship the contract with a subset
+    /// of the schema type system.
+    pub fn used_types(&self) -> BTreeSet<SemId> {
+        let mut types = BTreeSet::new();
+        if self.genesis.metadata.is_some() {
+            types.extend(self.schema.genesis.metadata);
+        }
+        for ty in self.genesis.globals.keys() {
+            if let Some(global_schema) = self.schema.global_types.get(ty) {
+                types.insert(global_schema.sem_id);
+            }
+        }
+        for ty in self.genesis.assignments.keys() {
+            if let Some(StateSchema::Structured(sem_id)) = self.schema.owned_types.get(ty) {
+                types.insert(*sem_id);
+            }
+        }
+        types
+    }
+
+    /// Constructs a proof of the state which genesis allocates to the given
+    /// outpoint, concealing all other genesis allocations. Returns `None` if
+    /// genesis has no revealed allocations to the outpoint.
+    pub fn allocation_proof(&self, outpoint: Outpoint) -> Option<AllocationProof> {
+        let mut genesis = self.genesis.clone();
+        if conceal_except(&mut genesis.assignments, None, &bset! {outpoint}).is_empty() {
+            return None;
+        }
+        Some(AllocationProof { outpoint, genesis })
+    }
+
+    /// Matches the secret against concealed seals of all contract operations
+    /// and replaces them with the revealed seal definition.
+    ///
+    /// Operation ids commit to the concealed seals, so revealing them doesn't
+    /// affect the contract or bundle ids.
+    pub fn reveal_seal(&mut self, secret: SealSecret) -> Result<(), RevealError> {
+        let concealed = secret.conceal();
+        let genesis_secret = match secret.txid {
+            TxPtr::Txid(txid) => {
+                Some(GenesisSeal::with_blinding(secret.method, txid, secret.vout, secret.blinding))
+            }
+            TxPtr::WitnessTx => None,
+        };
+        let mut found = false;
+        if let Some(genesis_secret) = genesis_secret {
+            found |= reveal_assignments(&mut self.genesis.assignments, genesis_secret);
+            for extension in self.extensions.iter_mut() {
+                found |= reveal_assignments(&mut extension.assignments, genesis_secret);
+            }
+        }
+        for anchored_bundle in self.bundles.iter_mut() {
+            for (_, item) in anchored_bundle.bundle.keyed_values_mut() {
+                if let Some(transition) = &mut item.transition {
+                    found |= reveal_assignments(&mut transition.assignments, secret);
+                }
+            }
+        }
+        if !found {
+            return Err(RevealError::NoMatch(concealed));
+        }
+        Ok(())
+    }
+
+    /// Serializes the contract in a canonical form, which is the same for the
+    /// contracts having the same semantic content, and thus can be used for
+    /// deduplication and as a key for caches.
+    ///
+    /// Unlike the contract commitment, the canonical form doesn't depend on
+    /// the blinding factors of the revealed seals and state, the order of the
+    /// bundles and extensions, and the contract signatures. Concealed data
+    /// can't be normalized and are serialized as they are.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut contract = self.clone();
+        contract.validation_status = None;
+        contract.signatures = none!();
+
+        normalize_assignments(&mut contract.genesis.assignments);
+        let mut bundles = mem::take(&mut contract.bundles).into_inner();
+        for anchored_bundle in &mut bundles {
+            for (_, item) in anchored_bundle.bundle.keyed_values_mut() {
+                if let Some(transition) = &mut item.transition {
+                    normalize_assignments(&mut transition.assignments);
+                }
+            }
+        }
+        bundles.sort_by_key(|anchored_bundle| anchored_bundle.bundle.bundle_id());
+        contract.bundles = Confined::try_from(bundles).expect("same size");
+
+        let mut extensions = mem::take(&mut contract.extensions).into_inner();
+        for extension in &mut extensions {
+            normalize_assignments(&mut extension.assignments);
+        }
+        extensions.sort_by_key(|extension| extension.id());
+        contract.extensions = Confined::try_from(extensions).expect("same size");
+
+        contract
+            .to_strict_serialized::<{ usize::MAX }>()
+            .expect("contract serialization has no size limit")
+            .into_inner()
+    }
+}
+
+/// Verifies that the allocation proof commits to the genesis of the contract
+/// with the given id, reveals state assigned to the proof outpoint and doesn't
+/// reveal state assigned to any other outpoint.
+pub fn verify_allocation_proof(
+    contract_id: ContractId,
+    proof: &AllocationProof,
+) -> Result<(), AllocationProofError> {
+    let found = proof.genesis.contract_id();
+    if found != contract_id {
+        return Err(AllocationProofError::ContractMismatch {
+            expected: contract_id,
+            found,
+        });
+    }
+    let mut revealed = false;
+    let mut leak = None;
+    for_each_assign(&proof.genesis.assignments, |view| match view.outpoint(None) {
+        Some(outpoint) if outpoint != proof.outpoint => {
+            leak.get_or_insert(outpoint);
+        }
+        // Assignment revealing the seal but not the state doesn't prove the
+        // allocation
+        Some(_) => revealed |= view.state.is_some(),
+        None => {}
+    });
+    if let Some(outpoint) = leak {
+        return Err(AllocationProofError::Leak(outpoint));
+    }
+    if !revealed {
+        return Err(AllocationProofError::NotRevealed(proof.outpoint));
+    }
+    Ok(())
+}
+
+/// Seal and state of a single assignment of a contract operation, which
+/// doesn't depend on the type of the assigned state.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub(crate) struct AssignView<Seal: ExposedSeal> {
+    pub ty: AssignmentsType,
+    pub no: u16,
+    /// Seal definition, if it is revealed.
+    pub seal: Option<Seal>,
+    pub secret_seal: SecretSeal,
+    /// Assigned state, if it is revealed.
+    pub state: Option<StateData>,
+    pub commitment: StateCommitment,
+}
+
+impl<Seal: ExposedSeal<Confidential = SecretSeal>> AssignView<Seal> {
+    fn with<State: ExposedState>(
+        ty: AssignmentsType,
+        no: u16,
+        assign: &Assign<State, Seal>,
+    ) -> Self {
+        AssignView {
+            ty,
+            no,
+            seal: assign.revealed_seal(),
+            secret_seal: assign.to_confidential_seal(),
+            state: assign.as_revealed_state().map(State::state_data),
+            commitment: assign.to_confidential_state().state_commitment(),
+        }
+    }
+
+    /// Outpoint of the revealed seal. Seals pointing to the witness
+    /// transaction are resolved with the witness transaction id, if it is
+    /// known.
+    pub fn outpoint(&self, witness_txid: Option<Txid>) -> Option<Outpoint> {
+        seal_outpoint(self.seal.as_ref()?, witness_txid)
+    }
+
+    pub fn seal_view(&self, witness_txid: Option<Txid>) -> SealView {
+        match self.outpoint(witness_txid) {
+            Some(outpoint) => SealView::Revealed(self.ty, outpoint),
+            None => SealView::Concealed(self.ty, self.secret_seal),
+        }
+    }
+
+    /// Fungible amount of the assignment, if it is revealed.
+    pub fn amount(&self) -> Option<u64> {
+        match &self.state {
+            Some(StateData::Fungible(state)) => Some(state.value.as_u64()),
+            _ => None,
+        }
+    }
+}
+
+impl AssignView<GenesisSeal> {
+    /// Converts the genesis seal to the graph seal, keeping the concealed
+    /// form of the original seal.
+    pub fn transmutate(self) -> AssignView<GraphSeal> {
+        AssignView {
+            ty: self.ty,
+            no: self.no,
+            seal: self.seal.map(GenesisSeal::transmutate),
+            secret_seal: self.secret_seal,
+            state: self.state,
+            commitment: self.commitment,
+        }
+    }
+}
+
+/// Calls the function for each assignment of an operation, in the order of
+/// the assignment types and the assignment numbers.
+pub(crate) fn for_each_assign<Seal: ExposedSeal<Confidential = SecretSeal>>(
+    assignments: &Assignments<Seal>,
+    mut f: impl FnMut(AssignView<Seal>),
+) {
+    for (ty, typed_assigns) in assignments.iter() {
+        let ty = *ty;
+        match typed_assigns {
+            TypedAssigns::Declarative(assigns) => {
+                assigns.iter().zip(0u16..).for_each(|(a, no)| f(AssignView::with(ty, no, a)))
+            }
+            TypedAssigns::Fungible(assigns) => {
+                assigns.iter().zip(0u16..).for_each(|(a, no)| f(AssignView::with(ty, no, a)))
+            }
+            TypedAssigns::Structured(assigns) => {
+                assigns.iter().zip(0u16..).for_each(|(a, no)| f(AssignView::with(ty, no, a)))
+            }
+            TypedAssigns::Attachment(assigns) => {
+                assigns.iter().zip(0u16..).for_each(|(a, no)| f(AssignView::with(ty, no, a)))
+            }
+        }
+    }
+}
+
+/// Lists all assignments of an operation, converting genesis seals to graph
+/// seals.
+pub(crate) fn assign_views(assignments: AssignmentsRef) -> Vec<AssignView<GraphSeal>> {
+    let mut views = vec![];
+    match assignments {
+        AssignmentsRef::Genesis(assignments) => {
+            for_each_assign(assignments, |view| views.push(view.transmutate()))
+        }
+        AssignmentsRef::Graph(assignments) => for_each_assign(assignments, |view| views.push(view)),
+    }
+    views
+}
+
+/// Visitor modifying assignments of an operation, called by
+/// [`visit_assignments_mut`] for each assignment of every state type.
+pub(crate) trait AssignVisitorMut<Seal: ExposedSeal> {
+    fn visit_mut<State: ExposedState>(
+        &mut self,
+        ty: AssignmentsType,
+        no: u16,
+        assign: &mut Assign<State, Seal>,
+    );
+}
+
+pub(crate) fn visit_assignments_mut<Seal: ExposedSeal>(
+    assignments: &mut Assignments<Seal>,
+    visitor: &mut impl AssignVisitorMut<Seal>,
+) {
+    let mut inner = mem::take(assignments).into_inner();
+    for (ty, typed_assigns) in inner.keyed_values_mut() {
+        let ty = *ty;
+        match typed_assigns {
+            TypedAssigns::Declarative(assigns) => {
+                assigns.iter_mut().zip(0u16..).for_each(|(a, no)| visitor.visit_mut(ty, no, a))
+            }
+            TypedAssigns::Fungible(assigns) => {
+                assigns.iter_mut().zip(0u16..).for_each(|(a, no)| visitor.visit_mut(ty, no, a))
+            }
+            TypedAssigns::Structured(assigns) => {
+                assigns.iter_mut().zip(0u16..).for_each(|(a, no)| visitor.visit_mut(ty, no, a))
+            }
+            TypedAssigns::Attachment(assigns) => {
+                assigns.iter_mut().zip(0u16..).for_each(|(a, no)| visitor.visit_mut(ty, no, a))
+            }
+        }
+    }
+    *assignments = Assignments::from_inner(inner);
+}
+
+fn seal_outpoint(seal: &impl TxoSeal, witness_txid: Option<Txid>) -> Option<Outpoint> {
+    match witness_txid {
+        Some(txid) => Some(seal.outpoint_or(txid)),
+        None => seal.outpoint(),
+    }
+}
+
+/// Lists outpoints of all revealed seals of the operation assignments.
+pub(crate) fn revealed_outpoints<Seal: ExposedSeal<Confidential = SecretSeal>>(
+    assignments: &Assignments<Seal>,
+    witness_txid: Option<Txid>,
+) -> Vec<Outpoint> {
+    let mut outpoints = vec![];
+    for_each_assign(assignments, |view| outpoints.extend(view.outpoint(witness_txid)));
+    outpoints
+}
+
+/// Conceals all assignments of the operation except the ones revealing both
+/// seal and state allocated to one of the given outpoints. Returns the kept
+/// assignments.
+pub(crate) fn conceal_except<Seal: ExposedSeal>(
+    assignments: &mut Assignments<Seal>,
+    witness_txid: Option<Txid>,
+    outpoints: &BTreeSet<Outpoint>,
+) -> Vec<(AssignmentsType, u16, Outpoint)> {
+    struct Concealer<'a> {
+        witness_txid: Option<Txid>,
+        outpoints: &'a BTreeSet<Outpoint>,
+        kept: Vec<(AssignmentsType, u16, Outpoint)>,
+    }
+
+    impl<Seal: ExposedSeal> AssignVisitorMut<Seal> for Concealer<'_> {
+        fn visit_mut<State: ExposedState>(
+            &mut self,
+            ty: AssignmentsType,
+            no: u16,
+            assign: &mut Assign<State, Seal>,
+        ) {
+            if let Assign::Revealed { seal, .. } = assign {
+                if let Some(outpoint) = seal_outpoint(seal, self.witness_txid) {
+                    if self.outpoints.contains(&outpoint) {
+                        self.kept.push((ty, no, outpoint));
+                        return;
+                    }
+                }
+            }
+            *assign = assign.conceal();
         }
     }
+
+    let mut concealer = Concealer {
+        witness_txid,
+        outpoints,
+        kept: vec![],
+    };
+    visit_assignments_mut(assignments, &mut concealer);
+    concealer.kept
+}
+
+/// Replaces the assignments with the concealed seal matching the secret with
+/// the revealed seal. Returns whether any of the assignments was revealed.
+fn reveal_assignments<Seal: ExposedSeal>(
+    assignments: &mut Assignments<Seal>,
+    secret: Seal,
+) -> bool {
+    struct Revealer<Seal: ExposedSeal> {
+        secret: Seal,
+        found: bool,
+    }
+
+    impl<Seal: ExposedSeal> AssignVisitorMut<Seal> for Revealer<Seal> {
+        fn visit_mut<State: ExposedState>(
+            &mut self,
+            _: AssignmentsType,
+            _: u16,
+            assign: &mut Assign<State, Seal>,
+        ) {
+            let concealed = self.secret.conceal();
+            *assign = match assign {
+                Assign::Confidential { seal, state } if *seal == concealed => {
+                    Assign::ConfidentialState {
+                        seal: self.secret,
+                        state: *state,
+                    }
+                }
+                Assign::ConfidentialSeal { seal, state } if *seal == concealed => {
+                    Assign::Revealed {
+                        seal: self.secret,
+                        state: state.clone(),
+                    }
+                }
+                _ => return,
+            };
+            self.found = true;
+        }
+    }
+
+    let mut revealer = Revealer {
+        secret,
+        found: false,
+    };
+    visit_assignments_mut(assignments, &mut revealer);
+    revealer.found
+}
+
+/// Random number generator used to produce the same blinding factors for the
+/// canonical form of the contract state.
+struct CanonicalRng;
+
+impl RngCore for CanonicalRng {
+    fn next_u32(&mut self) -> u32 { 1 }
+    fn next_u64(&mut self) -> u64 { 1 }
+    fn fill_bytes(&mut self, dest: &mut [u8]) { dest.fill(1) }
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Seals which blinding factor is reset in the canonical form of the
+/// contract.
+trait CanonicalSeal: ExposedSeal {
+    fn reset_blinding(&mut self);
+}
+
+impl CanonicalSeal for GenesisSeal {
+    fn reset_blinding(&mut self) { self.blinding = 0 }
+}
+
+impl CanonicalSeal for GraphSeal {
+    fn reset_blinding(&mut self) { self.blinding = 0 }
+}
+
+fn normalize_seal<State: ExposedState, Seal: CanonicalSeal>(assign: &mut Assign<State, Seal>) {
+    if let Assign::Revealed { seal, .. } | Assign::ConfidentialState { seal, .. } = assign {
+        seal.reset_blinding();
+    }
+}
+
+fn normalize_assignments<Seal: CanonicalSeal>(assignments: &mut Assignments<Seal>) {
+    let mut inner = mem::take(assignments).into_inner();
+    for (_, typed_assigns) in inner.keyed_values_mut() {
+        match typed_assigns {
+            TypedAssigns::Declarative(assigns) => assigns.iter_mut().for_each(normalize_seal),
+            TypedAssigns::Fungible(assigns) => {
+                for assign in assigns.iter_mut() {
+                    normalize_seal(assign);
+                    if let Assign::Revealed { state, .. } | Assign::ConfidentialSeal { state, .. } =
+                        assign
+                    {
+                        *state = fungible::Revealed::new(state.value.as_u64(), &mut CanonicalRng);
+                    }
+                }
+            }
+            TypedAssigns::Structured(assigns) => assigns.iter_mut().for_each(normalize_seal),
+            TypedAssigns::Attachment(assigns) => {
+                for assign in assigns.iter_mut() {
+                    normalize_seal(assign);
+                    if let Assign::Revealed { state, .. } | Assign::ConfidentialSeal { state, .. } =
+                        assign
+                    {
+                        state.salt = 0;
+                    }
+                }
+            }
+        }
+    }
+    *assignments = Assignments::from_inner(inner);
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use rgb::Occurrences;
+
+    use super::*;
+    use crate::containers::builder::test::{builder, iimpl, issuable, outpoint};
+    use crate::containers::ContractBuilder;
+    use crate::containers::AssignmentCounts;
+    use crate::fixtures::{self, schema, GS_CONTRACT, GS_NOMINAL, OS_ASSETS};
+    use crate::interface::rgb20;
+    use crate::stl::{ContractText, Nominal, Precision, StandardTypes};
+
+    fn conceal_genesis_seals(contract: &mut Contract) -> Vec<GraphSeal> {
+        struct SealConcealer(Vec<GraphSeal>);
+
+        impl AssignVisitorMut<GenesisSeal> for SealConcealer {
+            fn visit_mut<State: ExposedState>(
+                &mut self,
+                _: AssignmentsType,
+                _: u16,
+                assign: &mut Assign<State, GenesisSeal>,
+            ) {
+                let Assign::Revealed { seal, state } = assign.clone() else {
+                    return;
+                };
+                if !matches!(state.state_data(), StateData::Fungible(_)) {
+                    return;
+                }
+                self.0.push(seal.transmutate());
+                *assign = Assign::ConfidentialSeal {
+                    seal: seal.conceal(),
+                    state,
+                };
+            }
+        }
+
+        let mut concealer = SealConcealer(vec![]);
+        visit_assignments_mut(&mut contract.genesis.assignments, &mut concealer);
+        concealer.0
+    }
+
+    #[test]
+    fn used_types() {
+        let types = StandardTypes::new();
+        let nominal = types.get("RGBContract.Nominal");
+        let contract_text = types.get("RGBContract.ContractText");
+
+        let contract = issuable().issue_contract().unwrap();
+        assert_eq!(contract.used_types(), bset! { nominal, contract_text });
+
+        let mut schema = schema();
+        schema
+            .genesis
+            .globals
+            .insert(GS_CONTRACT, Occurrences::NoneOrOnce)
+            .unwrap();
+        let iface = rgb20();
+        let iimpl = iimpl(&schema, &iface);
+        let contract = ContractBuilder::with(iface, schema, iimpl)
+            .unwrap()
+            .set_chain(Chain::Bitcoin)
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::Centi))
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        assert_eq!(contract.used_types(), bset! { nominal });
+    }
+
+    #[test]
+    fn allocation_proof() {
+        let contract = issuable()
+            .add_fungible_state("Assets", outpoint(1), 500)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        let contract_id = contract.contract_id();
+
+        let proof = contract.allocation_proof(outpoint(1)).unwrap();
+        assert_eq!(proof.genesis.contract_id(), contract_id);
+        assert_eq!(revealed_outpoints(&proof.genesis.assignments, None), vec![outpoint(1)]);
+        verify_allocation_proof(contract_id, &proof).unwrap();
+
+        assert_eq!(contract.allocation_proof(outpoint(5)), None);
+
+        let other_id = issuable().issue_contract().unwrap().contract_id();
+        assert_eq!(
+            verify_allocation_proof(other_id, &proof),
+            Err(AllocationProofError::ContractMismatch {
+                expected: other_id,
+                found: contract_id
+            })
+        );
+
+        let leaking = AllocationProof {
+            outpoint: outpoint(1),
+            genesis: contract.genesis,
+        };
+        assert_eq!(
+            verify_allocation_proof(contract_id, &leaking),
+            Err(AllocationProofError::Leak(outpoint(0)))
+        );
+
+        let mut hiding = proof;
+        let mut assignments = mem::take(&mut hiding.genesis.assignments).into_inner();
+        let Some(TypedAssigns::Fungible(assigns)) = assignments.get_mut(&OS_ASSETS) else {
+            panic!("no fungible state in genesis");
+        };
+        for assign in assigns.iter_mut() {
+            if let Assign::Revealed { seal, state } = assign {
+                *assign = Assign::ConfidentialState {
+                    seal: *seal,
+                    state: state.conceal(),
+                };
+            }
+        }
+        hiding.genesis.assignments = Assignments::from_inner(assignments);
+        assert_eq!(hiding.genesis.contract_id(), contract_id);
+        assert_eq!(revealed_outpoints(&hiding.genesis.assignments, None), vec![outpoint(1)]);
+        assert_eq!(
+            verify_allocation_proof(contract_id, &hiding),
+            Err(AllocationProofError::NotRevealed(outpoint(1)))
+        );
+    }
+
+    #[test]
+    fn canonical_bytes() {
+        let contract = issuable().issue_contract().unwrap();
+        let reissued = issuable().issue_contract().unwrap();
+        assert_ne!(contract.contract_id(), reissued.contract_id());
+        assert_eq!(contract.canonical_bytes(), reissued.canonical_bytes());
+
+        let other = builder()
+            .set_chain(Chain::Bitcoin)
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap()
+            .add_global_state("ContractText", ContractText::default())
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(0), 1)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        assert_ne!(contract.canonical_bytes(), other.canonical_bytes());
+    }
+
+    #[test]
+    fn invoice_template() {
+        let contract = issuable().issue_contract().unwrap();
+        assert_eq!(contract.invoice_template(), InvoiceTemplate {
+            contract_id: contract.contract_id(),
+            chain: Chain::Bitcoin,
+            iface_id: Some(rgb20().iface_id()),
+            assignment: Some(tn!("Assets")),
+        });
+
+        let mut contract = contract;
+        contract.ifaces = none!();
+        let template = contract.invoice_template();
+        assert_eq!(template.iface_id, None);
+        assert_eq!(template.assignment, None);
+    }
+
+    #[test]
+    fn verify_integrity() {
+        let mut contract = issuable().issue_contract().unwrap();
+        assert_eq!(contract.verify_integrity(), Ok(()));
+
+        let expected = contract.schema_id();
+        contract
+            .schema
+            .global_types
+            .get_mut(&GS_NOMINAL)
+            .expect("test schema has nominal")
+            .max_items = 2;
+        assert_eq!(
+            contract.verify_integrity(),
+            Err(IntegrityError::SchemaIdMismatch {
+                expected,
+                found: contract.schema_id()
+            })
+        );
+    }
+
+    #[test]
+    fn strip_schema() {
+        let contract = issuable().issue_contract().unwrap();
+        let serialized = contract.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let (genesis_only, schema, ifaces) = contract.clone().strip_schema();
+        let stored = genesis_only.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        assert!(stored.len() < serialized.len());
+
+        let mut other = schema.clone();
+        other
+            .global_types
+            .get_mut(&GS_NOMINAL)
+            .expect("test schema has nominal")
+            .max_items = 2;
+        assert_eq!(
+            genesis_only.with_schema(other.clone(), ifaces.clone()).unwrap_err(),
+            IntegrityError::SchemaIdMismatch {
+                expected: schema.schema_id(),
+                found: other.schema_id()
+            }
+        );
+
+        let restored = GenesisOnly::from_strict_serialized::<{ usize::MAX }>(stored)
+            .unwrap()
+            .with_schema(schema, ifaces)
+            .unwrap();
+        assert_eq!(restored.contract_id(), contract.contract_id());
+        assert_eq!(restored.to_strict_serialized::<{ usize::MAX }>().unwrap(), serialized);
+    }
+
+    #[test]
+    fn attach_iface() {
+        let contract = issuable().issue_contract().unwrap();
+        let mut iface = rgb20();
+        iface.name = tn!("RGB20Ext");
+        let pair = IfacePair::with(iface.clone(), iimpl(&contract.schema, &iface));
+        let iface_id = pair.iface_id();
+
+        let contract = contract.attach_iface(pair.clone()).unwrap();
+        assert_eq!(contract.ifaces.len(), 2);
+        assert!(contract.ifaces.contains_key(&rgb20().iface_id()));
+        assert_eq!(contract.ifaces.get(&iface_id), Some(&pair));
+
+        assert_eq!(
+            contract.clone().attach_iface(pair.clone()).unwrap_err(),
+            ForgeError::IfaceConflict(iface_id)
+        );
+        let mut newer = pair;
+        newer.iimpl.version = 1;
+        let contract = contract.attach_iface(newer.clone()).unwrap();
+        assert_eq!(contract.ifaces.get(&iface_id), Some(&newer));
+
+        let mut other = newer;
+        other.iimpl.version = 2;
+        other.iimpl.schema_id = strict_dumb!();
+        assert_eq!(contract.attach_iface(other).unwrap_err(), ForgeError::SchemaMismatch);
+    }
+
+    #[test]
+    fn conceal_reveal() {
+        let original = issuable().issue_contract().unwrap();
+        let mut contract = original.clone();
+        let secrets = conceal_genesis_seals(&mut contract);
+        assert_eq!(secrets.len(), 1);
+        assert_eq!(contract.contract_id(), original.contract_id());
+        assert_eq!(
+            contract.concealed_seals(),
+            secrets
+                .iter()
+                .map(|secret| {
+                    let txid = secret.txid().unwrap();
+                    GenesisSeal::with_blinding(secret.method, txid, secret.vout, secret.blinding)
+                        .conceal()
+                })
+                .collect()
+        );
+
+        contract.reveal_seal(secrets[0]).unwrap();
+        assert!(contract.concealed_seals().is_empty());
+        assert_eq!(contract.genesis, original.genesis);
+        assert_eq!(
+            contract.reveal_seal(secrets[0]),
+            Err(RevealError::NoMatch(secrets[0].conceal()))
+        );
+    }
+
+    #[test]
+    fn all_seals() {
+        let mut contract = issuable()
+            .add_fungible_state("Assets", outpoint(1), 10)
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(2), 20)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        conceal_genesis_assets(&mut contract, 1);
+
+        let seals = contract.all_seals();
+        assert_eq!(seals.len(), 3);
+        assert!(seals.iter().all(|seal| seal.assignment_type() == OS_ASSETS));
+        let concealed = seals
+            .iter()
+            .filter(|seal| matches!(seal, SealView::Concealed(..)))
+            .count();
+        assert_eq!(concealed, 1);
+    }
+
+    pub(crate) fn conceal_genesis_assets(contract: &mut Contract, count: usize) {
+        let mut assignments = mem::take(&mut contract.genesis.assignments).into_inner();
+        let Some(TypedAssigns::Fungible(assigns)) = assignments.get_mut(&OS_ASSETS) else {
+            panic!("no fungible state in genesis");
+        };
+        for assign in assigns.iter_mut().take(count) {
+            *assign = assign.conceal();
+        }
+        contract.genesis.assignments = Assignments::from_inner(assignments);
+    }
+
+    #[test]
+    fn concealed_state() {
+        let allocations = [(outpoint(0), 100), (outpoint(1), 50)];
+        let contract = fixtures::issue(&allocations);
+        assert_eq!(contract.balance(&tn!("Assets")), Some(Balance::Known(150)));
+        assert_eq!(contract.balance(&tn!("Unknown")), None);
+
+        let mut partial = contract.clone();
+        conceal_genesis_assets(&mut partial, 1);
+        let Some(Balance::Partial { revealed, concealed }) = partial.balance(&tn!("Assets"))
+        else {
+            panic!("balance must be partially known");
+        };
+        assert!(revealed == 100 || revealed == 50);
+        assert_eq!(concealed, 1);
+        assert_eq!(partial.assignment_counts()[&OS_ASSETS], AssignmentCounts {
+            revealed: 1,
+            concealed: 1
+        });
+
+        let mut concealed = contract;
+        conceal_genesis_assets(&mut concealed, 2);
+        let balance = concealed.balance(&tn!("Assets")).unwrap();
+        assert_eq!(balance, Balance::Unknown { concealed: 2 });
+        assert_eq!(balance.known(), None);
+        assert_eq!(concealed.assignment_counts()[&OS_ASSETS], AssignmentCounts {
+            revealed: 0,
+            concealed: 2
+        });
+        assert_eq!(concealed.to_summary().supply, None);
+        assert!(concealed
+            .display_summary()
+            .lines()
+            .any(|line| line == "allocations 0 revealed, 2 concealed"));
+    }
+
+    #[test]
+    fn watch_list() {
+        let contract = issuable()
+            .add_fungible_state("Assets", outpoint(1), 50)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        let items = contract.watch_list();
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| item.contract_id == contract.contract_id() &&
+            item.opout.op == contract.genesis.id() &&
+            item.witness_txid.is_none()));
+        let mut watched = items
+            .iter()
+            .map(|item| (item.seal, item.amount))
+            .collect::<Vec<_>>();
+        watched.sort_by_key(|(_, amount)| *amount);
+        assert_eq!(watched, [
+            (SealView::Revealed(OS_ASSETS, outpoint(1)), Some(50)),
+            (SealView::Revealed(OS_ASSETS, outpoint(0)), Some(100_000)),
+        ]);
+    }
+
+    #[test]
+    fn public_copy() {
+        let contract = issuable()
+            .set_category("collectibles")
+            .unwrap()
+            .set_anchor_height(800_000)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        let public = contract.public_copy();
+        assert_eq!(public.category, None);
+        assert_eq!(public.anchor_height, Some(800_000));
+        assert_eq!(public.contract_id(), contract.contract_id());
+        assert_eq!(public.genesis, contract.genesis);
+        assert_eq!(public.schema_id(), contract.schema_id());
+        assert_eq!(contract.category.as_deref().map(String::as_str), Some("collectibles"));
+    }
 }
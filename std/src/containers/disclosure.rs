@@ -19,5 +19,377 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-// TODO: Implement disclosures
-pub struct Disclosure;
+//! Selective disclosure of the contract state, allowing a holder or an issuer
+//! to reveal the history of the state allocated to some seals without
+//! revealing the rest of the contract state.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use amplify::confinement::{Confined, LargeOrdSet, LargeVec};
+use bp::{Outpoint, Txid};
+use rgb::{
+    Assignments, ContractId, ExposedSeal, Extension, Genesis, OpId, Operation, Opout, SecretSeal,
+    Transition,
+};
+use strict_encoding::{StrictDeserialize, StrictSerialize};
+
+use crate::containers::contract::{conceal_except, revealed_outpoints};
+use crate::containers::Contract;
+use crate::LIB_NAME_RGB_STD;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum DisclosureError {
+    /// disclosure is made for contract {found} and not {expected}.
+    ContractMismatch {
+        expected: ContractId,
+        found: ContractId,
+    },
+
+    /// disclosed operation {0} is not a part of the contract.
+    UnknownOperation(OpId),
+
+    /// disclosed state transition {0} is anchored to a different witness
+    /// transaction.
+    WitnessMismatch(OpId),
+
+    /// disclosure reveals state assigned to {0}, which is not one of the
+    /// disclosed seals.
+    Leak(Outpoint),
+}
+
+/// State transition from a [`Disclosure`] together with its witness
+/// transaction.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+pub struct DisclosedTransition {
+    pub witness_txid: Txid,
+    pub transition: Transition,
+}
+
+/// Contract operations assigning state to or spending state from a set of
+/// seals. Only the state assigned to the disclosed seals is revealed, all
+/// other state of the operations is concealed; since operation ids commit to
+/// the concealed state, the disclosure can be checked against the public
+/// contract with [`Disclosure::verify_within`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+pub struct Disclosure {
+    pub contract_id: ContractId,
+    /// Seals which state history is disclosed.
+    pub seals: LargeOrdSet<Outpoint>,
+    /// Disclosed seals to which the discloser doesn't know any revealed
+    /// state assignment.
+    pub unknown: LargeOrdSet<Outpoint>,
+    /// Genesis, if it assigns state to the disclosed seals.
+    pub genesis: Option<Genesis>,
+    pub transitions: LargeVec<DisclosedTransition>,
+    pub extensions: LargeVec<Extension>,
+}
+
+impl StrictSerialize for Disclosure {}
+impl StrictDeserialize for Disclosure {}
+
+impl Contract {
+    /// Constructs disclosure of all contract operations assigning state to the
+    /// seals or spending the state assigned to them.
+    ///
+    /// # Panics
+    ///
+    /// If the number of seals exceeds `u32::MAX`.
+    pub fn disclose_seals(&self, seals: &[Outpoint]) -> Disclosure {
+        let seals = seals.iter().copied().collect::<BTreeSet<_>>();
+        let mut assigned = BTreeMap::<Opout, Outpoint>::new();
+
+        let mut genesis = self.genesis.clone();
+        let genesis_id = genesis.id();
+        let genesis = disclose(&mut genesis.assignments, genesis_id, None, &seals, &mut assigned)
+            .then_some(genesis);
+
+        let mut transitions = vec![];
+        for anchored_bundle in &self.bundles {
+            let witness_txid = anchored_bundle.anchor.txid;
+            for item in anchored_bundle.bundle.values() {
+                let Some(transition) = &item.transition else {
+                    continue;
+                };
+                let mut transition = transition.clone();
+                let opid = transition.id();
+                let assigns = disclose(
+                    &mut transition.assignments,
+                    opid,
+                    Some(witness_txid),
+                    &seals,
+                    &mut assigned,
+                );
+                transitions.push((assigns, DisclosedTransition {
+                    witness_txid,
+                    transition,
+                }));
+            }
+        }
+
+        let mut extensions = vec![];
+        for extension in &self.extensions {
+            let mut extension = extension.clone();
+            let opid = extension.id();
+            if disclose(&mut extension.assignments, opid, None, &seals, &mut assigned) {
+                extensions.push(extension);
+            }
+        }
+
+        let transitions = transitions
+            .into_iter()
+            .filter(|(assigns, disclosed)| *assigns || spends(&disclosed.transition, &assigned))
+            .map(|(_, disclosed)| disclosed);
+        let known = assigned.values().copied().collect::<BTreeSet<_>>();
+        let unknown = seals.difference(&known).copied().collect::<BTreeSet<_>>();
+        Disclosure {
+            contract_id: self.contract_id(),
+            seals: Confined::try_from(seals).expect("too many seals"),
+            unknown: Confined::try_from(unknown).expect("subset of seals"),
+            genesis,
+            transitions: Confined::try_from_iter(transitions).expect("subset of contract bundles"),
+            extensions: Confined::try_from(extensions).expect("subset of contract extensions"),
+        }
+    }
+}
+
+impl Disclosure {
+    /// Checks that all the disclosed operations are a part of the contract
+    /// and reveal only the state assigned to the disclosed seals.
+    pub fn verify_within(&self, contract: &Contract) -> Result<(), DisclosureError> {
+        let expected = contract.contract_id();
+        if self.contract_id != expected {
+            return Err(DisclosureError::ContractMismatch {
+                expected,
+                found: self.contract_id,
+            });
+        }
+        if let Some(genesis) = &self.genesis {
+            if genesis.id() != contract.genesis.id() {
+                return Err(DisclosureError::UnknownOperation(genesis.id()));
+            }
+            self.check_revealed(&genesis.assignments, None)?;
+        }
+        let witnesses = contract
+            .bundles
+            .iter()
+            .flat_map(|anchored_bundle| {
+                let witness_txid = anchored_bundle.anchor.txid;
+                anchored_bundle
+                    .bundle
+                    .values()
+                    .filter_map(|item| item.transition.as_ref())
+                    .map(move |transition| (transition.id(), witness_txid))
+            })
+            .collect::<BTreeMap<_, _>>();
+        for disclosed in &self.transitions {
+            let opid = disclosed.transition.id();
+            let witness_txid = *witnesses
+                .get(&opid)
+                .ok_or(DisclosureError::UnknownOperation(opid))?;
+            if witness_txid != disclosed.witness_txid {
+                return Err(DisclosureError::WitnessMismatch(opid));
+            }
+            self.check_revealed(&disclosed.transition.assignments, Some(witness_txid))?;
+        }
+        let extensions = contract
+            .extensions
+            .iter()
+            .map(Extension::id)
+            .collect::<BTreeSet<_>>();
+        for extension in &self.extensions {
+            let opid = extension.id();
+            if !extensions.contains(&opid) {
+                return Err(DisclosureError::UnknownOperation(opid));
+            }
+            self.check_revealed(&extension.assignments, None)?;
+        }
+        Ok(())
+    }
+
+    fn check_revealed<Seal: ExposedSeal<Confidential = SecretSeal>>(
+        &self,
+        assignments: &Assignments<Seal>,
+        witness_txid: Option<Txid>,
+    ) -> Result<(), DisclosureError> {
+        for outpoint in revealed_outpoints(assignments, witness_txid) {
+            if !self.seals.contains(&outpoint) {
+                return Err(DisclosureError::Leak(outpoint));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn spends(transition: &Transition, assigned: &BTreeMap<Opout, Outpoint>) -> bool {
+    transition.inputs.iter().any(|(prev_id, prev)| {
+        prev.iter().any(|(ty, nos)| {
+            nos.iter().any(|no| assigned.contains_key(&Opout::new(*prev_id, *ty, *no)))
+        })
+    })
+}
+
+/// Conceals all state of the operation not assigned to the seals, recording
+/// the outputs assigning state to the seals. Returns whether any state is
+/// assigned to the seals.
+fn disclose<Seal: ExposedSeal>(
+    assignments: &mut Assignments<Seal>,
+    opid: OpId,
+    witness_txid: Option<Txid>,
+    seals: &BTreeSet<Outpoint>,
+    assigned: &mut BTreeMap<Opout, Outpoint>,
+) -> bool {
+    let kept = conceal_except(assignments, witness_txid, seals);
+    let found = !kept.is_empty();
+    for (ty, no, outpoint) in kept {
+        assigned.insert(Opout::new(opid, ty, no), outpoint);
+    }
+    found
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::{self, outpoint, witness_txid, TwoHopTransfer, OS_ASSETS};
+
+    fn transferred() -> (Contract, Transition, Transition) {
+        let TwoHopTransfer {
+            contract,
+            first,
+            second,
+        } = fixtures::anchored_two_hop_transfer();
+        (contract, first, second)
+    }
+
+    fn disclosed_ids(disclosure: &Disclosure) -> Vec<OpId> {
+        disclosure
+            .transitions
+            .iter()
+            .map(|disclosed| disclosed.transition.id())
+            .collect()
+    }
+
+    #[test]
+    fn disclose_seals() {
+        let contract = fixtures::issue(&[(outpoint(0), 10), (outpoint(1), 20)]);
+        let disclosure = contract.disclose_seals(&[outpoint(0), outpoint(2)]);
+        assert!(disclosure.genesis.is_some());
+        assert!(disclosure.unknown.contains(&outpoint(2)));
+        assert!(!disclosure.unknown.contains(&outpoint(0)));
+        assert_eq!(disclosure.verify_within(&contract), Ok(()));
+
+        let data = disclosure.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let decoded = Disclosure::from_strict_serialized::<{ usize::MAX }>(data).unwrap();
+        assert_eq!(decoded, disclosure);
+
+        let leaking = Disclosure {
+            genesis: Some(contract.genesis.clone()),
+            ..disclosure.clone()
+        };
+        assert_eq!(leaking.verify_within(&contract), Err(DisclosureError::Leak(outpoint(1))));
+
+        let other = fixtures::issue(&[(outpoint(0), 10)]);
+        assert_eq!(
+            disclosure.verify_within(&other),
+            Err(DisclosureError::ContractMismatch {
+                expected: other.contract_id(),
+                found: contract.contract_id(),
+            })
+        );
+
+        let unrelated = contract.disclose_seals(&[outpoint(3)]);
+        assert_eq!(unrelated.genesis, None);
+        assert_eq!(unrelated.unknown.len(), 1);
+    }
+
+    #[test]
+    fn disclose_transitions() {
+        let (contract, first, second) = transferred();
+
+        // Genesis assigns to the seal and the first transition spends it
+        let disclosure = contract.disclose_seals(&[outpoint(0)]);
+        assert!(disclosure.genesis.is_some());
+        assert_eq!(disclosed_ids(&disclosure), vec![first.id()]);
+        let disclosed = &disclosure.transitions[0].transition;
+        assert!(revealed_outpoints(&disclosed.assignments, Some(witness_txid(1))).is_empty());
+        assert_eq!(disclosure.verify_within(&contract), Ok(()));
+
+        // The first transition assigns to the seal and the second one spends it
+        let disclosure = contract.disclose_seals(&[outpoint(1)]);
+        assert_eq!(disclosure.genesis, None);
+        assert!(disclosure.unknown.is_empty());
+        assert_eq!(disclosed_ids(&disclosure), vec![first.id(), second.id()]);
+        let [assigning, spending] = [0, 1].map(|no| &disclosure.transitions[no]);
+        assert_eq!(assigning.witness_txid, witness_txid(1));
+        assert_eq!(
+            revealed_outpoints(&assigning.transition.assignments, Some(witness_txid(1))),
+            vec![outpoint(1)]
+        );
+        assert!(
+            revealed_outpoints(&spending.transition.assignments, Some(witness_txid(2))).is_empty()
+        );
+        assert_eq!(disclosure.verify_within(&contract), Ok(()));
+
+        // Only the last transition assigns to the seal, and nothing spends it
+        let disclosure = contract.disclose_seals(&[outpoint(2)]);
+        assert_eq!(disclosed_ids(&disclosure), vec![second.id()]);
+        assert_eq!(disclosure.verify_within(&contract), Ok(()));
+    }
+
+    #[test]
+    fn spending() {
+        let (_, first, second) = transferred();
+        let assigned = bmap! { Opout::new(first.id(), OS_ASSETS, 0) => outpoint(1) };
+        assert!(spends(&second, &assigned));
+        assert!(!spends(&first, &assigned));
+        let other = bmap! { Opout::new(first.id(), OS_ASSETS, 1) => outpoint(1) };
+        assert!(!spends(&second, &other));
+    }
+
+    #[test]
+    fn verify_transitions() {
+        let (contract, first, _) = transferred();
+        let disclosure = contract.disclose_seals(&[outpoint(1)]);
+
+        let mut misanchored = disclosure.clone();
+        let transitions = disclosure.transitions.iter().cloned().map(|disclosed| {
+            DisclosedTransition {
+                witness_txid: witness_txid(3),
+                ..disclosed
+            }
+        });
+        misanchored.transitions = Confined::try_from_iter(transitions).unwrap();
+        assert_eq!(
+            misanchored.verify_within(&contract),
+            Err(DisclosureError::WitnessMismatch(first.id()))
+        );
+
+        let unanchored = fixtures::two_hop_transfer().contract;
+        assert_eq!(
+            disclosure.verify_within(&unanchored),
+            Err(DisclosureError::UnknownOperation(first.id()))
+        );
+
+        let mut leaking = disclosure;
+        let transitions = contract
+            .bundles
+            .iter()
+            .flat_map(|anchored_bundle| {
+                let witness_txid = anchored_bundle.anchor.txid;
+                anchored_bundle
+                    .bundle
+                    .values()
+                    .filter_map(|item| item.transition.clone())
+                    .map(move |transition| DisclosedTransition {
+                        witness_txid,
+                        transition,
+                    })
+            });
+        leaking.transitions = Confined::try_from_iter(transitions).unwrap();
+        assert_eq!(leaking.verify_within(&contract), Err(DisclosureError::Leak(outpoint(2))));
+    }
+}
@@ -0,0 +1,190 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Issuance requests, separating the decision on what to issue, which may be
+//! approved by an offline signer, from the assembly of the contract.
+
+use amplify::confinement::{SmallBlob, SmallVec};
+use bp::{Chain, Outpoint};
+use rgb::SubSchema;
+use strict_encoding::{StrictDeserialize, StrictSerialize, TypeName};
+
+use crate::containers::{BuilderError, ContractBuilder, IssueError, NeedsChain, Ready, SealSpec};
+use crate::interface::{Iface, IfaceImpl};
+use crate::LIB_NAME_RGB_STD;
+
+/// Value of the global state requested to be issued, in strict-serialized
+/// form.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct RequestedGlobal {
+    pub name: TypeName,
+    pub value: SmallBlob,
+}
+
+/// Fungible state requested to be allocated to an outpoint.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct RequestedAllocation {
+    pub name: TypeName,
+    pub outpoint: Outpoint,
+    pub value: u64,
+}
+
+/// Parameters of a contract issuance, which are replayed by
+/// [`ContractBuilder::from_request`].
+///
+/// The request doesn't contain any blinding factors, thus its strict
+/// serialization can be signed and verified independently from the issued
+/// contract.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct IssuanceRequest {
+    pub chain: Chain,
+    pub globals: SmallVec<RequestedGlobal>,
+    pub allocations: SmallVec<RequestedAllocation>,
+}
+
+impl StrictSerialize for IssuanceRequest {}
+impl StrictDeserialize for IssuanceRequest {}
+
+impl IssuanceRequest {
+    pub fn new(chain: Chain) -> Self {
+        IssuanceRequest {
+            chain,
+            globals: none!(),
+            allocations: none!(),
+        }
+    }
+
+    /// Adds value of the global state, serializing it. Values are not checked
+    /// against the schema until the request is replayed.
+    pub fn add_global_state(
+        &mut self,
+        name: impl Into<TypeName>,
+        value: impl StrictSerialize,
+    ) -> Result<(), BuilderError> {
+        let value = value.to_strict_serialized::<{ u16::MAX as usize }>()?;
+        self.globals.push(RequestedGlobal {
+            name: name.into(),
+            value,
+        })?;
+        Ok(())
+    }
+
+    pub fn add_allocation(
+        &mut self,
+        name: impl Into<TypeName>,
+        outpoint: Outpoint,
+        value: u64,
+    ) -> Result<(), BuilderError> {
+        self.allocations.push(RequestedAllocation {
+            name: name.into(),
+            outpoint,
+            value,
+        })?;
+        Ok(())
+    }
+}
+
+impl ContractBuilder<NeedsChain> {
+    /// Constructs builder replaying the issuance request, which must be
+    /// approved before. All the state is checked as if it was added to the
+    /// builder directly; additional state may be added before the contract is
+    /// issued.
+    pub fn from_request(
+        iface: Iface,
+        schema: SubSchema,
+        iimpl: IfaceImpl,
+        request: &IssuanceRequest,
+    ) -> Result<ContractBuilder<Ready>, IssueError> {
+        let mut builder = ContractBuilder::with(iface, schema, iimpl)?.set_chain(request.chain);
+        for global in &request.globals {
+            builder.push_global_state(global.name.clone(), global.value.clone())?;
+        }
+        for allocation in &request.allocations {
+            let seal = SealSpec::from(allocation.outpoint);
+            builder.push_fungible_state(allocation.name.clone(), seal, allocation.value)?;
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rgb::TypedAssigns;
+
+    use super::*;
+    use crate::fixtures::{iface, iimpl, outpoint, schema, GS_NOMINAL, OS_ASSETS};
+    use crate::stl::{ContractText, Nominal, Precision};
+
+    #[test]
+    fn round_trip() {
+        let nominal = Nominal::new("TEST", "Test asset", Precision::CentiMicro);
+        let mut request = IssuanceRequest::new(Chain::Bitcoin);
+        request.add_global_state("Nominal", nominal).unwrap();
+        request.add_global_state("ContractText", ContractText::default()).unwrap();
+        request.add_allocation("Assets", outpoint(0), 100).unwrap();
+        request.add_allocation("Assets", outpoint(1), 50).unwrap();
+
+        let data = request.to_strict_serialized::<{ u16::MAX as usize }>().unwrap();
+        let decoded = IssuanceRequest::from_strict_serialized::<{ u16::MAX as usize }>(data)
+            .expect("request must round-trip");
+        assert_eq!(decoded, request);
+
+        let contract = ContractBuilder::from_request(iface(), schema(), iimpl(), &decoded)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        let genesis = &contract.genesis;
+        assert_eq!(genesis.chain, Chain::Bitcoin);
+        let global = genesis.globals.get(&GS_NOMINAL).unwrap();
+        assert_eq!(global[0].as_ref().as_slice(), request.globals[0].value.as_slice());
+        let Some(TypedAssigns::Fungible(assigns)) = genesis.assignments.get(&OS_ASSETS) else {
+            panic!("no fungible state in genesis");
+        };
+        assert_eq!(assigns.len(), 2);
+
+        request.add_allocation("Unknown", outpoint(2), 1).unwrap();
+        assert_eq!(
+            ContractBuilder::from_request(iface(), schema(), iimpl(), &request).unwrap_err(),
+            IssueError::from(BuilderError::TypeNotFound(tn!("Unknown")))
+        );
+    }
+}
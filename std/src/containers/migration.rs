@@ -0,0 +1,361 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for reading containers saved in the previous versions of the
+//! container formats. Each change in the serialized layout bumps the envelope
+//! version and comes with an explicit migration function converting the legacy
+//! layout into the current in-memory data model.
+
+use amplify::confinement::{
+    Confined, LargeVec, MediumBlob, SmallOrdMap, SmallOrdSet, TinyOrdMap, TinyOrdSet, TinyVec,
+};
+use bp::Chain;
+use rgb::validation::AnchoredBundle;
+use rgb::{
+    AssignmentsType, AttachId, Extension, ExtensionType, Genesis, GlobalStateType, SchemaId,
+    SubSchema, TransitionType, ValencyType,
+};
+use strict_encoding::{DecodeError, StrictDecode, StrictReader};
+
+use super::{
+    Bindle, BindleContent, Cert, Consignment, ContainerVer, ContentId, ContentSigs, Terminal,
+};
+use crate::interface::{Iface, IfaceId, IfaceImpl, IfacePair, NamedType};
+use crate::LIB_NAME_RGB_STD;
+
+/// Migration of the serialized data from a legacy format version, which was
+/// performed when the data were loaded.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+#[display(doc_comments)]
+pub enum Migration {
+    /// consignment container from v1 to v2 (added anchor height hint,
+    /// interface implementation version and restrictions and contract
+    /// category).
+    ConsignmentV1ToV2,
+
+    /// stock from v0 to v1 (added stock chain, alternative interface
+    /// implementations and contract category).
+    StockV0ToV1,
+}
+
+/// Data loaded from a persistent storage together with the information about
+/// the migrations which were run to convert them into the current format.
+#[derive(Clone, Debug)]
+pub struct Migrated<T> {
+    pub data: T,
+    pub migrations: Vec<Migration>,
+}
+
+impl<T> Migrated<T> {
+    /// Wraps data which were stored in the current format version.
+    pub fn current(data: T) -> Self {
+        Migrated {
+            data,
+            migrations: vec![],
+        }
+    }
+
+    /// Detects whether the data were stored in one of the legacy formats.
+    pub fn is_migrated(&self) -> bool { !self.migrations.is_empty() }
+
+    pub fn into_inner(self) -> T { self.data }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum MigrationError {
+    /// {format} has format version {found}, while only versions from {min} to
+    /// {max} are supported.
+    UnsupportedVersion {
+        format: &'static str,
+        found: u8,
+        min: u8,
+        max: u8,
+    },
+
+    /// legacy stock contains contracts issued on different chains and can't be
+    /// migrated into a single stock.
+    AmbiguousChain(Chain, Chain),
+
+    /// data are truncated and do not contain format version information.
+    NoVersion,
+
+    #[from]
+    #[display(inner)]
+    Decode(DecodeError),
+}
+
+/// Layout of interface implementations saved with [`ContainerVer::V1`],
+/// before the implementation version and the restrictions on the chains,
+/// close methods and media types were introduced.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD, rename = "IfaceImpl")]
+pub(crate) struct IfaceImplV1 {
+    pub schema_id: SchemaId,
+    pub iface_id: IfaceId,
+    pub global_state: TinyOrdSet<NamedType<GlobalStateType>>,
+    pub owned_state: TinyOrdSet<NamedType<AssignmentsType>>,
+    pub valencies: TinyOrdSet<NamedType<ValencyType>>,
+    pub transitions: TinyOrdSet<NamedType<TransitionType>>,
+    pub extensions: TinyOrdSet<NamedType<ExtensionType>>,
+}
+
+impl From<IfaceImplV1> for IfaceImpl {
+    /// Migrates the implementation as the initial version without any
+    /// restrictions. Since the id of the implementation commits to all its
+    /// fields, the migrated implementation has a different id.
+    fn from(legacy: IfaceImplV1) -> Self {
+        IfaceImpl {
+            version: 0,
+            schema_id: legacy.schema_id,
+            iface_id: legacy.iface_id,
+            global_state: legacy.global_state,
+            owned_state: legacy.owned_state,
+            valencies: legacy.valencies,
+            transitions: legacy.transitions,
+            extensions: legacy.extensions,
+            chains: none!(),
+            close_methods: none!(),
+            media_types: none!(),
+        }
+    }
+}
+
+/// Layout of interface and implementation pairs saved with
+/// [`ContainerVer::V1`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD, rename = "IfacePair")]
+pub(crate) struct IfacePairV1 {
+    pub iface: Iface,
+    pub iimpl: IfaceImplV1,
+}
+
+impl From<IfacePairV1> for IfacePair {
+    fn from(legacy: IfacePairV1) -> Self { IfacePair::with(legacy.iface, legacy.iimpl.into()) }
+}
+
+/// Layout of consignments saved with [`ContainerVer::V1`], before the anchor
+/// height hint was introduced.
+#[derive(Clone, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD, rename = "Consignment")]
+pub(crate) struct ConsignmentV1<const TYPE: bool> {
+    pub version: ContainerVer,
+    pub transfer: bool,
+    pub schema: SubSchema,
+    pub ifaces: TinyOrdMap<IfaceId, IfacePairV1>,
+    pub genesis: Genesis,
+    pub terminals: SmallOrdSet<Terminal>,
+    pub bundles: LargeVec<AnchoredBundle>,
+    pub extensions: LargeVec<Extension>,
+    pub attachments: SmallOrdMap<AttachId, MediumBlob>,
+    pub signatures: TinyOrdMap<ContentId, ContentSigs>,
+}
+
+impl<const TYPE: bool> From<ConsignmentV1<TYPE>> for Consignment<TYPE> {
+    fn from(legacy: ConsignmentV1<TYPE>) -> Self {
+        let ifaces = legacy
+            .ifaces
+            .into_iter()
+            .map(|(id, pair)| (id, IfacePair::from(pair)));
+        Consignment {
+            validation_status: None,
+            version: ContainerVer::V2,
+            transfer: legacy.transfer,
+            schema: legacy.schema,
+            ifaces: Confined::try_from_iter(ifaces).expect("same collection size"),
+            genesis: legacy.genesis,
+            terminals: legacy.terminals,
+            bundles: legacy.bundles,
+            extensions: legacy.extensions,
+            attachments: legacy.attachments,
+            signatures: legacy.signatures,
+            anchor_height: None,
+            category: None,
+        }
+    }
+}
+
+/// Decodes consignment bindle data (following the file magic bytes), detecting
+/// the container version and migrating legacy layouts.
+pub(crate) fn decode_consignment<const TYPE: bool>(
+    data: &[u8],
+) -> Result<Migrated<Bindle<Consignment<TYPE>>>, MigrationError>
+where
+    Consignment<TYPE>: BindleContent,
+{
+    let mut rest = data;
+    // Bindle id is not used: it is re-computed from the migrated data
+    <Consignment<TYPE> as BindleContent>::Id::strict_decode(&mut StrictReader::with(
+        usize::MAX,
+        &mut rest,
+    ))?;
+    let Migrated {
+        data: consignment,
+        migrations,
+    } = decode_consignment_content::<TYPE>(&mut rest)?;
+    let sigs = TinyVec::<Cert>::strict_decode(&mut StrictReader::with(usize::MAX, rest))?;
+
+    let mut bindle = Bindle::new(consignment);
+    for cert in sigs {
+        bindle
+            .add_cert(cert)
+            .expect("bindle has the same limit on the number of signatures");
+    }
+    Ok(Migrated {
+        data: bindle,
+        migrations,
+    })
+}
+
+/// Decodes consignment data, detecting the container version and migrating
+/// legacy layouts. The data slice is advanced past the decoded consignment.
+pub(crate) fn decode_consignment_content<const TYPE: bool>(
+    data: &mut &[u8],
+) -> Result<Migrated<Consignment<TYPE>>, MigrationError> {
+    let version = *data.first().ok_or(MigrationError::NoVersion)?;
+
+    let mut reader = StrictReader::with(usize::MAX, data);
+    let (consignment, migrations) = match ContainerVer::try_from(version) {
+        Ok(ContainerVer::V1) => {
+            let legacy = ConsignmentV1::<TYPE>::strict_decode(&mut reader)?;
+            (Consignment::from(legacy), vec![Migration::ConsignmentV1ToV2])
+        }
+        Ok(ContainerVer::V2) => (Consignment::strict_decode(&mut reader)?, vec![]),
+        Err(_) => {
+            return Err(MigrationError::UnsupportedVersion {
+                format: "consignment",
+                found: version,
+                min: ContainerVer::V1 as u8,
+                max: ContainerVer::V2 as u8,
+            })
+        }
+    };
+    Ok(Migrated {
+        data: consignment,
+        migrations,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use base64::Engine;
+    use strict_encoding::{StrictEncode, StrictWriter};
+
+    use super::*;
+    use crate::containers::builder::test::issuable;
+    use crate::containers::Contract;
+
+    /// Serializes contract bindle without signatures with the given
+    /// serialization of the contract data.
+    fn bindle_data(contract: &Contract, content: impl StrictEncode) -> Vec<u8> {
+        let mut data = vec![];
+        let writer = StrictWriter::with(usize::MAX, &mut data);
+        let writer = contract.contract_id().strict_encode(writer).unwrap();
+        let writer = content.strict_encode(writer).unwrap();
+        TinyVec::<Cert>::default().strict_encode(writer).unwrap();
+        data
+    }
+
+    /// Contract bindle saved with the v1 container layout by the library
+    /// before the container format got versioned.
+    const CONTRACT_V1: &[u8] = include_bytes!("../../tests/data/contract-v1.rgb");
+
+    /// Contract bindle data of [`CONTRACT_V1`] following the file magic bytes.
+    fn contract_v1() -> &'static [u8] { &CONTRACT_V1[7..] }
+
+    #[test]
+    fn consignment_v1() {
+        assert_eq!(&CONTRACT_V1[..7], b"RGBCNRC");
+        let migrated = decode_consignment::<false>(contract_v1()).unwrap();
+        assert_eq!(migrated.migrations, vec![Migration::ConsignmentV1ToV2]);
+        let bindle = migrated.into_inner();
+        assert!(bindle.is_consistent());
+        let contract = bindle.unbindle();
+        assert_eq!(contract.version, ContainerVer::V2);
+        assert_eq!(
+            contract.contract_id().to_string(),
+            "CC1Z5Mmpoz8ZZVAka2V4WH3x43UxEPEWsdSnsrj7py4h"
+        );
+        assert_eq!(contract.genesis.chain, Chain::Testnet3);
+        assert_eq!(contract.anchor_height, None);
+        assert_eq!(contract.category, None);
+        let pair = contract.ifaces.values().next().unwrap();
+        assert_eq!(pair.iimpl.version, 0);
+        assert_eq!(pair.iimpl.schema_id, contract.schema_id());
+        assert_eq!(pair.iimpl.iface_id, pair.iface_id());
+        assert!(pair.iimpl.chains.is_empty());
+
+        let data = bindle_data(&contract, contract.clone());
+        let reloaded = decode_consignment::<false>(&data).unwrap();
+        assert!(!reloaded.is_migrated());
+        assert_eq!(reloaded.into_inner().unbindle().contract_id(), contract.contract_id());
+    }
+
+    #[test]
+    fn armored_v1() {
+        // the v1 bindle data consist of the contract id, the contract and an
+        // empty list of signatures
+        let data = contract_v1();
+        let content = &data[32..data.len() - 1];
+        let id = "CC1Z5Mmpoz8ZZVAka2V4WH3x43UxEPEWsdSnsrj7py4h";
+        let armored = format!(
+            "----- BEGIN RGB CONTRACT -----\nId: {id}\n\n{}\n\n----- END RGB CONTRACT -----\n",
+            base64::engine::general_purpose::STANDARD.encode(content)
+        );
+        let bindle = Bindle::<Contract>::from_str(&armored).unwrap();
+        assert_eq!(bindle.id().to_string(), id);
+        let contract = bindle.unbindle();
+        assert_eq!(contract.version, ContainerVer::V2);
+        assert_eq!(contract.contract_id().to_string(), id);
+
+        let armored = contract.bindle().to_string();
+        let bindle = Bindle::<Contract>::from_str(&armored).unwrap();
+        assert_eq!(bindle.id().to_string(), id);
+    }
+
+    #[test]
+    fn consignment_v2() {
+        let contract = issuable().issue_contract().unwrap();
+        let data = bindle_data(&contract, contract.clone());
+        let migrated = decode_consignment::<false>(&data).unwrap();
+        assert!(!migrated.is_migrated());
+    }
+
+    #[test]
+    fn consignment_unsupported() {
+        let mut data = contract_v1().to_vec();
+        data[32] = 3;
+        assert_eq!(
+            decode_consignment::<false>(&data).unwrap_err(),
+            MigrationError::UnsupportedVersion {
+                format: "consignment",
+                found: 3,
+                min: 1,
+                max: 2
+            }
+        );
+    }
+}
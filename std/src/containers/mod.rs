@@ -23,26 +23,56 @@
 //! contract users. There are two main types of containers:
 //! 1. [`Consignment`]s, containing information about partial state of a *single
 //!    contract*, extending from its genesis up to certain contract endpoints.
-//! 2. [`Disclosure`]s, containing extracts from (possibly) independent state
-//!    transitions and extensions under multiple contracts. Useful fro
-//!    disclosing the concealed state for some other parties, and also for
-//!    performing "change" operations on inventory during state transfers.
+//! 2. [`Disclosure`]s, containing extracts from the operations of a contract
+//!    which reveal only the state assigned to selected seals. Useful for
+//!    disclosing the history of some seals to other parties, like auditors,
+//!    without disclosing the rest of the contract state.
 
 mod consignment;
 mod disclosure;
 mod bindle;
 mod contract;
 mod transfer;
-mod builder;
+mod receipt;
+pub(crate) mod builder;
+mod issuance;
 mod seal;
+mod snapshot;
+mod stats;
+mod summary;
 mod util;
 mod validate;
+mod migration;
 mod certs;
 
-pub use bindle::{Bindle, BindleContent};
-pub use builder::{ContractBuilder, ForgeError, IssueError};
+#[cfg(feature = "fs")]
+pub use bindle::LoadError;
+pub use bindle::{Bindle, BindleContent, BindleParseError};
+pub use builder::{
+    issue, AllocationSecret, BuilderError, BuilderEvent, BuilderState, ContractBuilder, Dynamic,
+    ExtensionBuilder, ForgeError, IssueError, IssueState, NeedsChain, Ready, TransitionBuilder,
+};
 pub use certs::{Cert, ContentId, ContentSigs, Identity};
-pub use consignment::{Consignment, Contract, Transfer};
-pub use disclosure::Disclosure;
-pub use seal::{EndpointSeal, VoutSeal};
+pub use consignment::{AssignmentCounts, Consignment, Contract, MergeError, Transfer};
+pub(crate) use contract::{assign_views, for_each_assign, AssignView};
+pub use contract::{
+    verify_allocation_proof, AllocationProof, AllocationProofError, Balance, GenesisOnly,
+    IntegrityError, InvoiceTemplate, RevealError, SealView, WatchItem,
+};
+pub use disclosure::{DisclosedTransition, Disclosure, DisclosureError};
+pub use issuance::{IssuanceRequest, RequestedAllocation, RequestedGlobal};
+#[cfg(feature = "fs")]
+pub(crate) use migration::{ConsignmentV1, IfaceImplV1};
+pub use migration::{Migrated, Migration, MigrationError};
+pub use receipt::{PaymentReceipt, ReceiptError, ReceiptTerminal};
+pub use seal::{Beneficiary, EndpointSeal, SealSecret, SealSpec, SealSpecError, VoutSeal};
+pub use snapshot::{AllocationInclusion, AllocationLeaf};
+pub use stats::ConsignmentStats;
+pub use summary::ContractSummary;
+pub use transfer::TransferId;
 pub use util::{ContainerVer, Terminal};
+#[cfg(feature = "fs")]
+pub use validate::{ContractFileError, ContractFileResults};
+pub use validate::{
+    LimitedReadError, ReportEntry, ResourceLimit, ValencyError, ValidationConfig, ValidationReport,
+};
@@ -0,0 +1,249 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Payment receipts, which allow the payer to prove that a transfer was sent.
+
+use amplify::confinement::SmallVec;
+use bp::Txid;
+use commit_verify::Conceal;
+use rgb::{BundleId, ContractId, SecretSeal};
+use strict_encoding::{StrictDeserialize, StrictSerialize};
+
+use crate::containers::{for_each_assign, Terminal, Transfer, TransferId};
+use crate::LIB_NAME_RGB_STD;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ReceiptError {
+    /// receipt is issued for transfer {expected}, while the provided transfer
+    /// has id {found}.
+    TransferMismatch {
+        expected: TransferId,
+        found: TransferId,
+    },
+
+    /// receipt is issued for a different contract {0::<0}.
+    ContractMismatch(ContractId),
+
+    /// transfer doesn't have terminal {0} listed in the receipt.
+    UnknownTerminal(SecretSeal),
+
+    /// terminal {0} is anchored to a different witness transaction.
+    WitnessMismatch(SecretSeal),
+
+    /// amount assigned to the terminal {0} doesn't match the receipt.
+    AmountMismatch(SecretSeal),
+}
+
+/// Terminal of a transfer as it is listed in a [`PaymentReceipt`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct ReceiptTerminal {
+    /// Commitment to the seal of the terminal.
+    pub seal: SecretSeal,
+    pub bundle_id: BundleId,
+    /// Witness transaction of the bundle, if the bundle is present in the
+    /// transfer.
+    pub witness_txid: Option<Txid>,
+    /// Fungible amount assigned to the seal, if it is revealed in the
+    /// transfer.
+    pub amount: Option<u64>,
+}
+
+/// Compact proof of a payment, which is produced by the payer from the sent
+/// transfer and can be matched by the payee against the accepted transfer.
+///
+/// The receipt lists only the transfer terminals, thus it doesn't contain any
+/// information about the change seals of the payer.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct PaymentReceipt {
+    pub transfer_id: TransferId,
+    pub contract_id: ContractId,
+    pub terminals: SmallVec<ReceiptTerminal>,
+}
+
+impl StrictSerialize for PaymentReceipt {}
+impl StrictDeserialize for PaymentReceipt {}
+
+impl Transfer {
+    /// Produces receipt proving that the transfer was sent.
+    pub fn receipt(&self) -> PaymentReceipt {
+        let terminals = self.terminals.iter().map(|terminal| self.receipt_terminal(terminal));
+        PaymentReceipt {
+            transfer_id: self.transfer_id(),
+            contract_id: self.contract_id(),
+            terminals: SmallVec::try_from_iter(terminals)
+                .expect("same or smaller collection size"),
+        }
+    }
+
+    fn receipt_terminal(&self, terminal: &Terminal) -> ReceiptTerminal {
+        let seal = terminal.seal.conceal();
+        let anchored_bundle = self
+            .bundles
+            .iter()
+            .find(|anchored_bundle| anchored_bundle.bundle.bundle_id() == terminal.bundle_id);
+        let mut amount = None;
+        let transitions = anchored_bundle
+            .into_iter()
+            .flat_map(|anchored_bundle| anchored_bundle.bundle.values())
+            .filter_map(|item| item.transition.as_ref());
+        for transition in transitions {
+            for_each_assign(&transition.assignments, |view| {
+                if view.secret_seal == seal {
+                    amount = amount.or(view.amount());
+                }
+            });
+        }
+        ReceiptTerminal {
+            seal,
+            bundle_id: terminal.bundle_id,
+            witness_txid: anchored_bundle.map(|anchored_bundle| anchored_bundle.anchor.txid),
+            amount,
+        }
+    }
+}
+
+impl PaymentReceipt {
+    /// Checks that the receipt was produced for the provided transfer: all
+    /// terminals listed in the receipt must be present in the transfer with
+    /// the same witness transactions (or without a witness transaction in
+    /// both of them), and amounts known both to the receipt and the transfer
+    /// must match.
+    pub fn verify_against(&self, transfer: &Transfer) -> Result<(), ReceiptError> {
+        let found = transfer.transfer_id();
+        if self.transfer_id != found {
+            return Err(ReceiptError::TransferMismatch {
+                expected: self.transfer_id,
+                found,
+            });
+        }
+        if self.contract_id != transfer.contract_id() {
+            return Err(ReceiptError::ContractMismatch(self.contract_id));
+        }
+        for listed in &self.terminals {
+            let Some(terminal) = transfer.terminals.iter().find(|terminal| {
+                terminal.bundle_id == listed.bundle_id && terminal.seal.conceal() == listed.seal
+            }) else {
+                return Err(ReceiptError::UnknownTerminal(listed.seal));
+            };
+            let actual = transfer.receipt_terminal(terminal);
+            if actual.witness_txid != listed.witness_txid {
+                return Err(ReceiptError::WitnessMismatch(listed.seal));
+            }
+            if matches!((listed.amount, actual.amount), (Some(a), Some(b)) if a != b) {
+                return Err(ReceiptError::AmountMismatch(listed.seal));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+    use bp::seals::txout::CloseMethod;
+    use rgb::GraphSeal;
+
+    use super::*;
+    use crate::containers::EndpointSeal;
+    use crate::fixtures::{self, outpoint, TRANSFER_AMOUNT};
+
+    fn modified(receipt: &PaymentReceipt, f: impl Fn(&mut ReceiptTerminal)) -> PaymentReceipt {
+        let terminals = receipt.terminals.iter().copied().map(|mut terminal| {
+            f(&mut terminal);
+            terminal
+        });
+        PaymentReceipt {
+            terminals: Confined::try_from_iter(terminals).unwrap(),
+            ..receipt.clone()
+        }
+    }
+
+    #[test]
+    fn receipt() {
+        let sent = fixtures::transfer_consignment();
+        let receipt = sent.receipt();
+        assert_eq!(receipt.transfer_id, sent.transfer_id());
+        assert_eq!(receipt.contract_id, sent.contract_id());
+        assert_eq!(receipt.terminals.len(), 1);
+        let terminal = &receipt.terminals[0];
+        let sent_terminal = sent.terminals.iter().next().unwrap();
+        assert_eq!(terminal.seal, sent_terminal.seal.conceal());
+        assert_eq!(terminal.bundle_id, sent_terminal.bundle_id);
+        assert_eq!(terminal.witness_txid, Some(fixtures::witness_txid(2)));
+        assert_eq!(terminal.amount, Some(TRANSFER_AMOUNT));
+
+        let data = receipt.to_strict_serialized::<{ u16::MAX as usize }>().unwrap();
+        let decoded = PaymentReceipt::from_strict_serialized::<{ u16::MAX as usize }>(data)
+            .expect("receipt must round-trip");
+        assert_eq!(decoded, receipt);
+        assert_eq!(decoded.verify_against(&sent), Ok(()));
+
+        let seal = GraphSeal::new(CloseMethod::TapretFirst, outpoint(3).txid, 3);
+        let other = fixtures::issue(&[(outpoint(0), TRANSFER_AMOUNT)]).into_transfer([Terminal {
+            bundle_id: strict_dumb!(),
+            seal: EndpointSeal::ConcealedUtxo(seal.conceal()),
+        }]);
+        assert_eq!(
+            receipt.verify_against(&other),
+            Err(ReceiptError::TransferMismatch {
+                expected: sent.transfer_id(),
+                found: other.transfer_id()
+            })
+        );
+    }
+
+    #[test]
+    fn receipt_mismatch() {
+        let sent = fixtures::transfer_consignment();
+        let receipt = sent.receipt();
+        let seal = receipt.terminals[0].seal;
+
+        let unwitnessed = modified(&receipt, |terminal| terminal.witness_txid = None);
+        assert_eq!(unwitnessed.verify_against(&sent), Err(ReceiptError::WitnessMismatch(seal)));
+        let misattributed = modified(&receipt, |terminal| {
+            terminal.witness_txid = Some(fixtures::witness_txid(1))
+        });
+        assert_eq!(misattributed.verify_against(&sent), Err(ReceiptError::WitnessMismatch(seal)));
+
+        let overstated = modified(&receipt, |terminal| terminal.amount = Some(TRANSFER_AMOUNT + 1));
+        assert_eq!(overstated.verify_against(&sent), Err(ReceiptError::AmountMismatch(seal)));
+        let unstated = modified(&receipt, |terminal| terminal.amount = None);
+        assert_eq!(unstated.verify_against(&sent), Ok(()));
+
+        let unknown = modified(&receipt, |terminal| terminal.bundle_id = strict_dumb!());
+        assert_eq!(unknown.verify_against(&sent), Err(ReceiptError::UnknownTerminal(seal)));
+    }
+}
@@ -26,13 +26,18 @@ use std::str::FromStr;
 
 use bp::seals::txout::blind::ParseError;
 use bp::seals::txout::{CloseMethod, TxPtr};
-use bp::secp256k1::rand::{thread_rng, RngCore};
-use bp::Vout;
+use bp::secp256k1::rand::RngCore;
+use bp::{Outpoint, Txid, Vout};
 use commit_verify::Conceal;
 use rgb::{GraphSeal, SecretSeal};
 
+use crate::rng::BlindingRng;
 use crate::LIB_NAME_RGB_STD;
 
+/// Secret data required to reveal a concealed seal: the full seal definition
+/// including its blinding factor.
+pub type SealSecret = GraphSeal;
+
 /// Seal definition which re-uses witness transaction id of some other seal,
 /// which is not known at the moment of seal construction. Thus, the definition
 /// has only information about output number.
@@ -60,20 +65,20 @@ pub struct VoutSeal {
 
 impl VoutSeal {
     /// Creates new seal definition for the provided output number and seal
-    /// closing method. Uses `thread_rng` to initialize blinding factor.
+    /// closing method. Uses [`BlindingRng`] to initialize blinding factor.
     #[inline]
     pub fn new(method: CloseMethod, vout: impl Into<Vout>) -> Self {
-        VoutSeal::with(method, vout, thread_rng().next_u64())
+        VoutSeal::with(method, vout, BlindingRng.next_u64())
     }
 
     /// Creates new opret-seal seal definition for the provided output number
-    /// and seal closing method. Uses `thread_rng` to initialize blinding
+    /// and seal closing method. Uses [`BlindingRng`] to initialize blinding
     /// factor.
     #[inline]
     pub fn new_opret(vout: impl Into<Vout>) -> Self { VoutSeal::new(CloseMethod::OpretFirst, vout) }
 
     /// Creates new tapret-seal seal definition for the provided output number
-    /// and seal closing method. Uses `thread_rng` to initialize blinding
+    /// and seal closing method. Uses [`BlindingRng`] to initialize blinding
     /// factor.
     #[inline]
     pub fn new_tapret(vout: impl Into<Vout>) -> Self {
@@ -139,6 +144,10 @@ pub enum EndpointSeal {
     WitnessVout(VoutSeal),
 }
 
+/// Beneficiary of a transfer: either a blinded seal provided by the payee, or
+/// an output of the witness transaction constructed by the payer.
+pub type Beneficiary = EndpointSeal;
+
 impl From<GraphSeal> for EndpointSeal {
     fn from(seal: GraphSeal) -> Self {
         match seal.txid {
@@ -152,7 +161,7 @@ impl From<GraphSeal> for EndpointSeal {
 
 impl EndpointSeal {
     /// Constructs [`EndpointSeal`] for the witness transaction. Uses
-    /// `thread_rng` to initialize blinding factor.
+    /// [`BlindingRng`] to initialize blinding factor.
     pub fn new_vout(method: CloseMethod, vout: impl Into<Vout>) -> EndpointSeal {
         EndpointSeal::WitnessVout(VoutSeal::new(method, vout))
     }
@@ -187,3 +196,198 @@ impl FromStr for EndpointSeal {
             .or_else(|_| GraphSeal::from_str(s).map(EndpointSeal::from))
     }
 }
+
+/// Errors parsing [`SealSpec`] string representation.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum SealSpecError {
+    /// seal specification '{0}' has invalid format.
+    InvalidFormat(String),
+
+    /// unknown seal close method '{0}'.
+    InvalidMethod(String),
+
+    /// '{0}' is not a valid transaction id.
+    InvalidTxid(String),
+
+    /// '{0}' is not a valid transaction output number.
+    InvalidVout(String),
+
+    /// blinded seal has prefix '{0}' instead of 'txob'.
+    WrongHrp(String),
+
+    /// '{0}' is not a valid blinded seal.
+    InvalidBlinded(String),
+}
+
+/// Seal specification as it is provided by users: either an explicit
+/// transaction output with the seal close method, or a blinded seal.
+///
+/// The string representation is `[<method>:]<txid>:<vout>` for the explicit
+/// seals, where the method may be omitted to use the default one, or the
+/// [`SecretSeal`] string for the blinded seals.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, From)]
+pub enum SealSpec {
+    /// Explicit transaction output to be used with the close method. If the
+    /// method is not given, the default one is used.
+    Explicit {
+        method: Option<CloseMethod>,
+        outpoint: Outpoint,
+    },
+
+    /// Blinded seal, provided by the seal owner.
+    #[from]
+    Blinded(SecretSeal),
+}
+
+impl From<Outpoint> for SealSpec {
+    fn from(outpoint: Outpoint) -> Self {
+        SealSpec::Explicit {
+            method: None,
+            outpoint,
+        }
+    }
+}
+
+impl SealSpec {
+    /// Seal close method used when it is not provided explicitly and no other
+    /// preference is known.
+    pub const DEFAULT_METHOD: CloseMethod = CloseMethod::TapretFirst;
+
+    /// Returns seal close method, if it is explicitly specified.
+    pub fn close_method(&self) -> Option<CloseMethod> {
+        match self {
+            SealSpec::Explicit { method, .. } => *method,
+            SealSpec::Blinded(_) => None,
+        }
+    }
+
+    /// Constructs seal definition from the explicit seal specification,
+    /// using [`BlindingRng`] to initialize the blinding factor and
+    /// `default_method` if the close method is not specified. Returns `None`
+    /// for the blinded seals, which can't be revealed.
+    pub fn to_graph_seal(&self, default_method: CloseMethod) -> Option<GraphSeal> {
+        match *self {
+            SealSpec::Explicit { method, outpoint } => {
+                let method = method.unwrap_or(default_method);
+                let mut seal = GraphSeal::new(method, outpoint.txid, outpoint.vout);
+                seal.blinding = BlindingRng.next_u64();
+                Some(seal)
+            }
+            SealSpec::Blinded(_) => None,
+        }
+    }
+}
+
+impl Display for SealSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SealSpec::Explicit {
+                method: None,
+                outpoint,
+            } => write!(f, "{}:{}", outpoint.txid, outpoint.vout),
+            SealSpec::Explicit {
+                method: Some(method),
+                outpoint,
+            } => write!(f, "{}:{}:{}", method, outpoint.txid, outpoint.vout),
+            SealSpec::Blinded(seal) => Display::fmt(seal, f),
+        }
+    }
+}
+
+impl FromStr for SealSpec {
+    type Err = SealSpecError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split(':').collect::<Vec<_>>();
+        let (method, txid, vout) = match parts[..] {
+            [prefix, rest] if !rest.chars().all(|c| c.is_ascii_digit()) => {
+                if prefix != "txob" {
+                    return Err(SealSpecError::WrongHrp(prefix.to_owned()));
+                }
+                return SecretSeal::from_str(s)
+                    .map(SealSpec::Blinded)
+                    .map_err(|_| SealSpecError::InvalidBlinded(s.to_owned()));
+            }
+            [_] => {
+                return SecretSeal::from_str(s)
+                    .map(SealSpec::Blinded)
+                    .map_err(|_| SealSpecError::InvalidBlinded(s.to_owned()));
+            }
+            [txid, vout] => (None, txid, vout),
+            [method, txid, vout] => {
+                let method = CloseMethod::from_str(method)
+                    .map_err(|_| SealSpecError::InvalidMethod(method.to_owned()))?;
+                (Some(method), txid, vout)
+            }
+            _ => return Err(SealSpecError::InvalidFormat(s.to_owned())),
+        };
+        let txid = Txid::from_str(txid).map_err(|_| SealSpecError::InvalidTxid(txid.to_owned()))?;
+        let vout = u32::from_str(vout).map_err(|_| SealSpecError::InvalidVout(vout.to_owned()))?;
+        Ok(SealSpec::Explicit {
+            method,
+            outpoint: Outpoint::new(txid, vout),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TXID: &str = "a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5";
+
+    #[test]
+    fn seal_spec_explicit() {
+        let outpoint = Outpoint::new(Txid::from([0xA5; 32]), 1);
+        let s = format!("{TXID}:1");
+        let spec = SealSpec::from_str(&s).unwrap();
+        assert_eq!(spec, SealSpec::from(outpoint));
+        assert_eq!(spec.to_string(), s);
+        assert_eq!(spec.close_method(), None);
+        assert_eq!(
+            spec.to_graph_seal(CloseMethod::OpretFirst).unwrap().method,
+            CloseMethod::OpretFirst
+        );
+
+        let s = format!("opret1st:{TXID}:1");
+        let spec = SealSpec::from_str(&s).unwrap();
+        assert_eq!(spec, SealSpec::Explicit {
+            method: Some(CloseMethod::OpretFirst),
+            outpoint
+        });
+        assert_eq!(spec.to_string(), s);
+        assert_eq!(
+            spec.to_graph_seal(CloseMethod::TapretFirst).unwrap().method,
+            CloseMethod::OpretFirst
+        );
+    }
+
+    #[test]
+    fn seal_spec_blinded() {
+        let seal = GraphSeal::new(CloseMethod::TapretFirst, Txid::from([0xA5; 32]), 1u32).conceal();
+        let spec = SealSpec::from_str(&seal.to_string()).unwrap();
+        assert_eq!(spec, SealSpec::Blinded(seal));
+        assert_eq!(spec.to_string(), seal.to_string());
+        assert_eq!(spec.to_graph_seal(SealSpec::DEFAULT_METHOD), None);
+    }
+
+    #[test]
+    fn seal_spec_errors() {
+        assert_eq!(SealSpec::from_str("a5a5:1"), Err(SealSpecError::InvalidTxid(s!("a5a5"))));
+        assert_eq!(
+            SealSpec::from_str(&format!("{TXID}:4294967296")),
+            Err(SealSpecError::InvalidVout(s!("4294967296")))
+        );
+        assert_eq!(
+            SealSpec::from_str(&format!("nomethod:{TXID}:1")),
+            Err(SealSpecError::InvalidMethod(s!("nomethod")))
+        );
+        assert_eq!(SealSpec::from_str("utxo:abcdef"), Err(SealSpecError::WrongHrp(s!("utxo"))));
+        assert_eq!(
+            SealSpec::from_str("txob:abcdef"),
+            Err(SealSpecError::InvalidBlinded(s!("txob:abcdef")))
+        );
+        assert_eq!(SealSpec::from_str("1:2:3:4"), Err(SealSpecError::InvalidFormat(s!("1:2:3:4"))));
+    }
+}
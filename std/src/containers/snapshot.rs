@@ -0,0 +1,258 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merkle trees over the unspent contract allocations, allowing to prove to
+//! third parties that an allocation is a part of a contract state snapshot.
+//!
+//! Leaves of the tree are sorted by their hash, and adjacent nodes are
+//! hashed pairwise; the last node of a level with odd number of nodes is
+//! moved to the next level unchanged. The root of an empty tree is all
+//! zeros.
+
+use std::io;
+
+use amplify::Bytes32;
+use bp::Outpoint;
+use commit_verify::Sha256;
+use rgb::{AssignmentsType, Opout};
+use strict_encoding::{StrictEncode, StrictWriter};
+
+use crate::containers::Contract;
+
+const LEAF_TAG: u8 = 0;
+const NODE_TAG: u8 = 1;
+
+/// Allocation of the owned state to an outpoint, as it is committed to by a
+/// leaf of the allocation tree.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct AllocationLeaf {
+    pub outpoint: Outpoint,
+    pub assignment_type: AssignmentsType,
+    /// Hash of the concealed seal and the commitment to the allocated state,
+    /// which doesn't reveal the state.
+    pub assignment_hash: Bytes32,
+}
+
+impl AllocationLeaf {
+    pub fn leaf_hash(&self) -> Bytes32 {
+        let mut writer = HashWriter(Sha256::default());
+        writer.0.input_raw(&[LEAF_TAG]);
+        self.outpoint
+            .strict_encode(StrictWriter::with(usize::MAX, &mut writer))
+            .expect("hash engine doesn't fail");
+        writer.0.input_raw(&self.assignment_type.to_le_bytes());
+        writer.0.input_raw(self.assignment_hash.as_ref());
+        Bytes32::from(writer.0.finish())
+    }
+}
+
+/// Proof of inclusion of an allocation into the allocation tree of a
+/// contract.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct AllocationInclusion {
+    pub leaf: AllocationLeaf,
+    /// Sibling nodes from the leaf up to the root, each accompanied with a
+    /// flag whether the sibling is on the left side.
+    pub path: Vec<(Bytes32, bool)>,
+}
+
+impl AllocationInclusion {
+    /// Checks that the proof commits to the given allocation tree root.
+    pub fn verify(&self, root: Bytes32) -> bool {
+        let node = self.path.iter().fold(self.leaf.leaf_hash(), |node, (sibling, left)| {
+            if *left {
+                node_hash(*sibling, node)
+            } else {
+                node_hash(node, *sibling)
+            }
+        });
+        node == root
+    }
+}
+
+impl Contract {
+    /// Computes root of the merkle tree over the allocations of the contract
+    /// which seals are known and which are not spent by any of the known
+    /// state transitions. Thus, the root changes with each accepted spending
+    /// of the contract state.
+    pub fn allocations_root(&self) -> Bytes32 {
+        let mut level = self.allocation_leaves();
+        if level.is_empty() {
+            return Bytes32::from([0u8; 32]);
+        }
+        while level.len() > 1 {
+            level = next_level(&level);
+        }
+        level[0]
+    }
+
+    /// Constructs proof of inclusion into the allocation tree for the first
+    /// of the unspent allocations assigned to the outpoint. Returns `None` if
+    /// the contract doesn't allocate unspent state to the outpoint.
+    pub fn prove_allocation(&self, outpoint: Outpoint) -> Option<AllocationInclusion> {
+        let leaf = self
+            .allocations()
+            .into_iter()
+            .filter(|leaf| leaf.outpoint == outpoint)
+            .min_by_key(AllocationLeaf::leaf_hash)?;
+        let mut level = self.allocation_leaves();
+        let mut pos = level
+            .binary_search(&leaf.leaf_hash())
+            .expect("leaf is a part of the tree");
+        let mut path = vec![];
+        while level.len() > 1 {
+            let sibling = pos ^ 1;
+            if sibling < level.len() {
+                path.push((level[sibling], sibling < pos));
+            }
+            level = next_level(&level);
+            pos /= 2;
+        }
+        Some(AllocationInclusion { leaf, path })
+    }
+
+    fn allocation_leaves(&self) -> Vec<Bytes32> {
+        let mut leaves = self
+            .allocations()
+            .iter()
+            .map(AllocationLeaf::leaf_hash)
+            .collect::<Vec<_>>();
+        leaves.sort();
+        leaves
+    }
+
+    fn allocations(&self) -> Vec<AllocationLeaf> {
+        let spent = self.spent_opouts();
+        let mut leaves = vec![];
+        self.for_each_op_assign(|opid, witness_txid, view| {
+            if spent.contains(&Opout::new(opid, view.ty, view.no)) {
+                return;
+            }
+            let Some(outpoint) = view.outpoint(witness_txid) else {
+                return;
+            };
+            let mut writer = HashWriter(Sha256::default());
+            view.secret_seal
+                .strict_encode(StrictWriter::with(usize::MAX, &mut writer))
+                .and_then(|w| view.commitment.strict_encode(w))
+                .expect("hash engine doesn't fail");
+            leaves.push(AllocationLeaf {
+                outpoint,
+                assignment_type: view.ty,
+                assignment_hash: Bytes32::from(writer.0.finish()),
+            });
+        });
+        leaves
+    }
+}
+
+fn node_hash(left: Bytes32, right: Bytes32) -> Bytes32 {
+    let mut engine = Sha256::default();
+    engine.input_raw(&[NODE_TAG]);
+    engine.input_raw(left.as_ref());
+    engine.input_raw(right.as_ref());
+    Bytes32::from(engine.finish())
+}
+
+fn next_level(level: &[Bytes32]) -> Vec<Bytes32> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(*left, *right),
+            [single] => *single,
+            _ => unreachable!("chunks of two"),
+        })
+        .collect()
+}
+
+/// Writer feeding the written bytes into the hash engine.
+struct HashWriter(Sha256);
+
+impl io::Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.input_raw(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+
+    use crate::fixtures::{self, outpoint, TwoHopTransfer, TRANSFER_AMOUNT};
+
+    #[test]
+    fn inclusion() {
+        let allocations = [(outpoint(0), 10), (outpoint(1), 20), (outpoint(2), 30)];
+        let contract = fixtures::issue(&allocations);
+        let root = contract.allocations_root();
+        assert_eq!(root, fixtures::issue(&allocations).allocations_root());
+
+        for (outpoint, _) in allocations {
+            let proof = contract.prove_allocation(outpoint).unwrap();
+            assert_eq!(proof.leaf.outpoint, outpoint);
+            assert!(proof.verify(root));
+        }
+
+        assert_eq!(contract.prove_allocation(outpoint(3)), None);
+        let mut forged = contract.prove_allocation(outpoint(0)).unwrap();
+        forged.leaf.outpoint = outpoint(3);
+        assert!(!forged.verify(root));
+    }
+
+    #[test]
+    fn spending() {
+        let issued = fixtures::issue(&[(outpoint(0), TRANSFER_AMOUNT)]);
+        assert!(issued.prove_allocation(outpoint(0)).is_some());
+        let TwoHopTransfer {
+            contract: mut transferred,
+            first,
+            ..
+        } = fixtures::anchored_two_hop_transfer();
+
+        let root = transferred.allocations_root();
+        assert_ne!(root, issued.allocations_root());
+        assert_eq!(transferred.prove_allocation(outpoint(0)), None);
+        assert_eq!(transferred.prove_allocation(outpoint(1)), None);
+        assert!(transferred.prove_allocation(outpoint(2)).unwrap().verify(root));
+
+        // Without the second transition the allocation of the first one is unspent
+        let bundle = fixtures::anchor(first, fixtures::witness_txid(1));
+        transferred.bundles = Confined::try_from(vec![bundle]).unwrap();
+        let intermediate = transferred.allocations_root();
+        assert_ne!(intermediate, root);
+        assert_ne!(intermediate, issued.allocations_root());
+        assert!(transferred.prove_allocation(outpoint(1)).unwrap().verify(intermediate));
+        assert_eq!(transferred.prove_allocation(outpoint(2)), None);
+    }
+}
@@ -0,0 +1,199 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Statistics on the size and composition of consignments.
+
+use std::fmt::{self, Display, Formatter};
+use std::io;
+
+use rgb::{ConfidentialState, StateType};
+use strict_encoding::{StrictEncode, StrictWriter};
+
+use super::Consignment;
+
+/// Size and composition of a consignment.
+///
+/// Sizes are given in bytes of the strict serialization. Bytes which are not
+/// attributed to any of the consignment sections (interfaces, terminals,
+/// attachments, signatures and collection length prefixes) are reported by
+/// [`ConsignmentStats::other_size`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct ConsignmentStats {
+    pub bundles: usize,
+    pub transitions: usize,
+    pub extensions: usize,
+
+    pub declarative_assignments: usize,
+    pub fungible_assignments: usize,
+    pub structured_assignments: usize,
+    pub attachment_assignments: usize,
+
+    pub total_size: usize,
+    /// Size of the schema, including its type system.
+    pub schema_size: usize,
+    pub type_system_size: usize,
+    pub ifaces_size: usize,
+    pub genesis_size: usize,
+    pub transitions_size: usize,
+    pub extensions_size: usize,
+    pub anchors_size: usize,
+}
+
+impl ConsignmentStats {
+    /// Size of all contract operations: genesis, state transitions and state
+    /// extensions.
+    pub fn operations_size(&self) -> usize {
+        self.genesis_size + self.transitions_size + self.extensions_size
+    }
+
+    /// Size of the data not attributed to any of the other sections.
+    pub fn other_size(&self) -> usize {
+        self.total_size
+            .saturating_sub(self.schema_size)
+            .saturating_sub(self.ifaces_size)
+            .saturating_sub(self.operations_size())
+            .saturating_sub(self.anchors_size)
+    }
+}
+
+impl Display for ConsignmentStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<14}{:>8}{:>12}", "section", "count", "bytes")?;
+        let mut row = |name: &str, count: Option<usize>, size: usize| {
+            let count = count.map(|count| count.to_string()).unwrap_or_else(|| s!("-"));
+            writeln!(f, "{name:<14}{count:>8}{size:>12}")
+        };
+        row("schema", Some(1), self.schema_size)?;
+        row("  type system", None, self.type_system_size)?;
+        row("interfaces", None, self.ifaces_size)?;
+        row("genesis", Some(1), self.genesis_size)?;
+        row("transitions", Some(self.transitions), self.transitions_size)?;
+        row("extensions", Some(self.extensions), self.extensions_size)?;
+        row("anchors", Some(self.bundles), self.anchors_size)?;
+        row("other", None, self.other_size())?;
+        row("total", None, self.total_size)?;
+        write!(
+            f,
+            "assignments: {} declarative, {} fungible, {} structured, {} attachment",
+            self.declarative_assignments,
+            self.fungible_assignments,
+            self.structured_assignments,
+            self.attachment_assignments
+        )
+    }
+}
+
+/// Writer counting the bytes instead of storing them.
+#[derive(Default)]
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+pub(super) fn encoded_len(value: &impl StrictEncode) -> usize {
+    let mut counter = ByteCounter::default();
+    value
+        .strict_encode(StrictWriter::with(usize::MAX, &mut counter))
+        .expect("byte counter doesn't fail");
+    counter.0
+}
+
+impl<const TYPE: bool> Consignment<TYPE> {
+    /// Computes statistics on the consignment size and composition. The
+    /// sizes are computed by counting the serialized bytes, without
+    /// allocating the serialized data.
+    pub fn stats(&self) -> ConsignmentStats {
+        let mut stats = ConsignmentStats {
+            bundles: self.bundles.len(),
+            extensions: self.extensions.len(),
+            total_size: encoded_len(self),
+            schema_size: encoded_len(&self.schema),
+            type_system_size: encoded_len(&self.schema.type_system),
+            ifaces_size: encoded_len(&self.ifaces),
+            genesis_size: encoded_len(&self.genesis),
+            ..default!()
+        };
+        for anchored_bundle in &self.bundles {
+            stats.anchors_size += encoded_len(&anchored_bundle.anchor);
+            for transition in anchored_bundle
+                .bundle
+                .values()
+                .filter_map(|item| item.transition.as_ref())
+            {
+                stats.transitions += 1;
+                stats.transitions_size += encoded_len(transition);
+            }
+        }
+        for extension in &self.extensions {
+            stats.extensions_size += encoded_len(extension);
+        }
+        self.for_each_op_assign(|_, _, view| match view.commitment.state_type() {
+            StateType::Void => stats.declarative_assignments += 1,
+            StateType::Fungible => stats.fungible_assignments += 1,
+            StateType::Structured => stats.structured_assignments += 1,
+            StateType::Attachment => stats.attachment_assignments += 1,
+        });
+        stats
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::StrictSerialize;
+
+    use crate::containers::builder::test::issuable;
+
+    #[test]
+    fn contract_stats() {
+        let contract = issuable().issue_contract().unwrap();
+        let stats = contract.stats();
+        assert_eq!(stats.bundles, 0);
+        assert_eq!(stats.transitions, 0);
+        assert_eq!(stats.extensions, 0);
+        assert_eq!(stats.fungible_assignments, 1);
+        assert_eq!(stats.declarative_assignments, 0);
+        assert_eq!(stats.transitions_size, 0);
+        assert_eq!(stats.anchors_size, 0);
+        assert!(stats.type_system_size > 0);
+        assert!(stats.schema_size > stats.type_system_size);
+        let serialized = contract.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        assert_eq!(stats.total_size, serialized.len());
+        assert_eq!(
+            stats.total_size,
+            stats.schema_size + stats.ifaces_size + stats.genesis_size + stats.other_size()
+        );
+
+        let table = stats.to_string();
+        assert!(table.starts_with("section"));
+        assert!(table.ends_with("0 declarative, 1 fungible, 0 structured, 0 attachment"));
+    }
+}
@@ -0,0 +1,292 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Short summaries of contracts and transfers for the command-line tools and
+//! for the APIs listing many contracts.
+
+use std::fmt::{Display, Write};
+
+use bp::Chain;
+use rgb::{Assign, ContractId, TypedAssigns};
+use strict_encoding::TypeName;
+
+use super::{Consignment, Contract, Transfer};
+use crate::interface::OwnedIface;
+use crate::stl::Nominal;
+
+/// Placeholder for the values which are absent or can't be decoded.
+const UNKNOWN: &str = "-";
+
+/// Compact description of a contract, which is returned instead of the full
+/// [`Contract`] by the APIs listing contracts.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct ContractSummary {
+    pub contract_id: ContractId,
+    /// Name of the first interface implemented by the contract, if any.
+    pub iface: Option<TypeName>,
+    pub chain: Chain,
+    pub ticker: Option<String>,
+    pub name: Option<String>,
+    /// Issued supply, if it is declared by the genesis or can be computed
+    /// from its revealed allocations.
+    pub supply: Option<u64>,
+}
+
+fn line(summary: &mut String, label: &str, value: impl Display) {
+    writeln!(summary, "{label:<12}{value}").expect("writing to string doesn't fail");
+}
+
+/// Formats the count of items, using the plural form of the noun unless
+/// there is exactly one item.
+fn count(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {noun}"),
+        _ => format!("{count} {noun}s"),
+    }
+}
+
+impl Contract {
+    /// Renders short multi-line summary of the contract. Values which are
+    /// absent or can't be decoded are rendered as `-`.
+    pub fn display_summary(&self) -> String {
+        let mut summary = String::new();
+        self.write_summary(&mut summary);
+        summary
+    }
+
+    /// Produces compact summary of the contract. Only the well-known global
+    /// state is decoded; the fields which are absent or can't be decoded are
+    /// set to `None`.
+    pub fn to_summary(&self) -> ContractSummary {
+        let nominal = self.nominal();
+        ContractSummary {
+            contract_id: self.contract_id(),
+            iface: self.ifaces.values().next().map(|pair| pair.iface.name.clone()),
+            chain: self.genesis.chain,
+            ticker: nominal.as_ref().map(|nominal| nominal.ticker().to_string()),
+            name: nominal.as_ref().map(|nominal| nominal.name().to_string()),
+            supply: self.issued_supply(),
+        }
+    }
+}
+
+impl Transfer {
+    /// Renders short multi-line summary of the transfer, including its
+    /// terminals. Values which are absent or can't be decoded are rendered as
+    /// `-`.
+    pub fn display_summary(&self) -> String {
+        let mut summary = String::new();
+        line(&mut summary, "transfer", self.transfer_id());
+        self.write_summary(&mut summary);
+        line(&mut summary, "terminals", self.terminals.len());
+        summary
+    }
+}
+
+impl<const TYPE: bool> Consignment<TYPE> {
+    fn write_summary(&self, summary: &mut String) {
+        line(summary, "contract", self.contract_id());
+        line(summary, "schema", self.schema_id());
+        line(summary, "chain", self.genesis.chain);
+
+        let ifaces = self
+            .ifaces
+            .values()
+            .map(|pair| pair.iface.name.to_string())
+            .collect::<Vec<_>>();
+        if ifaces.is_empty() {
+            line(summary, "interfaces", UNKNOWN);
+        } else {
+            line(summary, "interfaces", ifaces.join(", "));
+        }
+
+        match self.nominal() {
+            Some(nominal) => {
+                line(summary, "ticker", nominal.ticker());
+                line(summary, "name", nominal.name());
+                line(summary, "precision", nominal.precision().decimals());
+            }
+            None => {
+                line(summary, "ticker", UNKNOWN);
+                line(summary, "name", UNKNOWN);
+                line(summary, "precision", UNKNOWN);
+            }
+        }
+        match self.issued_supply() {
+            Some(supply) => line(summary, "supply", supply),
+            None => line(summary, "supply", UNKNOWN),
+        }
+        let (revealed, concealed) = self
+            .assignment_counts()
+            .values()
+            .fold((0, 0), |(revealed, concealed), counts| {
+                (revealed + counts.revealed, concealed + counts.concealed)
+            });
+        line(summary, "allocations", format!("{revealed} revealed, {concealed} concealed"));
+
+        let transitions = self
+            .bundles
+            .iter()
+            .flat_map(|anchored_bundle| anchored_bundle.bundle.values())
+            .filter(|item| item.transition.is_some())
+            .count();
+        line(
+            summary,
+            "operations",
+            format!(
+                "1 genesis, {} in {}, {}",
+                count(transitions, "transition"),
+                count(self.bundles.len(), "bundle"),
+                count(self.extensions.len(), "extension")
+            ),
+        );
+    }
+
+    fn nominal(&self) -> Option<Nominal> { self.genesis_global(&tn!("Nominal")) }
+
+    /// Issued supply declared by the genesis or, if it is not declared, the
+    /// sum of the genesis allocations, provided all of them are revealed.
+    ///
+    /// The supply is taken from the first interface declaring global state
+    /// `IssuedSupply` or fungible owned state `Assets`, using its
+    /// implementation to find the schema state types.
+    fn issued_supply(&self) -> Option<u64> {
+        let assets_name = tn!("Assets");
+        self.ifaces.values().find_map(|pair| {
+            let supply = self.declared_supply(pair);
+            if supply.is_some() {
+                return supply;
+            }
+            if pair.iface.owned_state.get(&assets_name) != Some(&OwnedIface::Amount) {
+                return None;
+            }
+            let assets_type = pair.iimpl.assignments_type(&assets_name)?;
+            let Some(TypedAssigns::Fungible(assigns)) = self.genesis.assignments.get(&assets_type)
+            else {
+                return None;
+            };
+            assigns.iter().try_fold(0u64, |sum, assign| match assign {
+                Assign::Revealed { state, .. } | Assign::ConfidentialSeal { state, .. } => {
+                    sum.checked_add(state.value.as_u64())
+                }
+                Assign::Confidential { .. } | Assign::ConfidentialState { .. } => None,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+
+    use super::*;
+    use crate::fixtures::{self, outpoint};
+
+    #[test]
+    fn contract_summary() {
+        let allocations = [(outpoint(0), 100), (outpoint(1), 50)];
+        let contract = fixtures::issue(&allocations);
+        let summary = contract.display_summary();
+        assert_eq!(summary, fixtures::issue(&allocations).display_summary());
+
+        let lines = summary.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], format!("contract    {}", contract.contract_id()));
+        assert_eq!(lines[3..], [
+            "interfaces  RGB20",
+            "ticker      TEST",
+            "name        Test asset",
+            "precision   8",
+            "supply      150",
+            "allocations 2 revealed, 0 concealed",
+            "operations  1 genesis, 0 transitions in 0 bundles, 0 extensions",
+        ]);
+
+        let mut bare = contract;
+        bare.ifaces = none!();
+        let lines = bare.display_summary().lines().map(str::to_owned).collect::<Vec<_>>();
+        assert_eq!(lines[3..8], [
+            "interfaces  -",
+            "ticker      -",
+            "name        -",
+            "precision   -",
+            "supply      -",
+        ]);
+    }
+
+    #[test]
+    fn contract_summary_dto() {
+        let contract = fixtures::issue(&[(outpoint(0), 100), (outpoint(1), 50)]);
+        assert_eq!(contract.to_summary(), ContractSummary {
+            contract_id: contract.contract_id(),
+            iface: Some(tn!("RGB20")),
+            chain: Chain::Bitcoin,
+            ticker: Some(s!("TEST")),
+            name: Some(s!("Test asset")),
+            supply: Some(150),
+        });
+
+        let mut bare = contract;
+        bare.ifaces = none!();
+        let summary = bare.to_summary();
+        assert_eq!(summary.iface, None);
+        assert_eq!(summary.ticker, None);
+        assert_eq!(summary.supply, None);
+    }
+
+    #[test]
+    fn iface_resolution() {
+        let mut contract = fixtures::issue(&[(outpoint(0), 100)]);
+        let pair = contract.ifaces.get_mut(&fixtures::iface().iface_id()).unwrap();
+        let globals = pair.iface.global_state.iter().filter(|(name, _)| **name != tn!("Nominal"));
+        pair.iface.global_state =
+            Confined::try_from_iter(globals.map(|(name, req)| (name.clone(), req.clone())))
+                .unwrap();
+        pair.iface.owned_state = none!();
+        let summary = contract.to_summary();
+        assert_eq!(summary.ticker, None);
+        assert_eq!(summary.name, None);
+        assert_eq!(summary.supply, None);
+    }
+
+    #[test]
+    fn transfer_summary() {
+        let mut transfer = fixtures::transfer_consignment();
+        let lines = transfer.display_summary().lines().map(str::to_owned).collect::<Vec<_>>();
+        assert_eq!(lines[0], format!("transfer    {}", transfer.transfer_id()));
+        assert_eq!(lines[9..], [
+            "allocations 3 revealed, 0 concealed",
+            "operations  1 genesis, 2 transitions in 2 bundles, 0 extensions",
+            "terminals   1",
+        ]);
+
+        let bundles = transfer.bundles.iter().take(1).cloned();
+        transfer.bundles = Confined::try_from_iter(bundles).unwrap();
+        assert!(transfer
+            .display_summary()
+            .lines()
+            .any(|line| line == "operations  1 genesis, 1 transition in 1 bundle, 0 extensions"));
+    }
+}
@@ -27,7 +27,7 @@ use baid58::{Baid58ParseError, FromBaid58, ToBaid58};
 use commit_verify::{CommitEncode, CommitmentId, Conceal};
 use strict_encoding::{StrictEncode, StrictWriter};
 
-use crate::containers::Transfer;
+use crate::containers::{Contract, Transfer};
 use crate::LIB_NAME_RGB_STD;
 
 /// Transfer identifier.
@@ -86,4 +86,24 @@ impl CommitmentId for Transfer {
 impl Transfer {
     #[inline]
     pub fn transfer_id(&self) -> TransferId { self.commitment_id() }
+
+    /// Converts transfer into a contract consignment, preserving the status of
+    /// the transfer validation.
+    pub fn into_contract(self) -> Contract {
+        Contract {
+            validation_status: self.validation_status,
+            version: self.version,
+            transfer: false,
+            schema: self.schema,
+            ifaces: self.ifaces,
+            genesis: self.genesis,
+            terminals: self.terminals,
+            bundles: self.bundles,
+            extensions: self.extensions,
+            attachments: self.attachments,
+            signatures: self.signatures,
+            anchor_height: self.anchor_height,
+            category: self.category,
+        }
+    }
 }
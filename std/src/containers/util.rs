@@ -49,6 +49,10 @@ pub struct Terminal {
 #[repr(u8)]
 pub enum ContainerVer {
     // V0 was a previous version before v0.10, which is now not supported.
-    #[default]
+    /// Layout without the anchor height hint, interface implementation
+    /// versions and restrictions and the contract category; migrated on load.
     V1 = 1,
+
+    #[default]
+    V2 = 2,
 }
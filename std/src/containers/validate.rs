@@ -19,22 +19,580 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use rgb::validation::{ResolveTx, Validator, Validity, Warning};
+use std::fmt::Display;
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
 
+use amplify::confinement::Confined;
+use amplify::Wrapper;
+use bp::Tx;
+use rgb::validation::{
+    self, AnchoredBundle, Failure, Info, ResolveTx, TxResolverError, Validator, Validity, Warning,
+};
+use commit_verify::CommitVerify;
+use rgb::fungible::BlindingFactor;
+use rgb::{
+    fungible, Anchor, ContractId, Extension, ExtensionType, OpId, Operation, PedersenCommitment,
+    StateCommitment, StateData, SubSchema, TransitionBundle, Valencies, ValencyType,
+};
+use secp256k1_zkp::SECP256K1;
+use strict_encoding::{DecodeError, StrictDecode, StrictReader};
+
+use super::contract::for_each_assign;
+use super::stats::encoded_len;
 use super::Consignment;
+#[cfg(feature = "async")]
+use crate::resolvers::AsyncResolveTx;
+use crate::resolvers::WitnessFetch;
+
+/// Errors in redeeming valencies by state extensions.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ValencyError {
+    /// schema doesn't allow extensions of type {0} to redeem valency {1}.
+    UnknownValency(ExtensionType, ValencyType),
+
+    /// operation {opid} doesn't declare valency {valency} redeemed by the
+    /// extension.
+    ValencyNotDeclared { opid: OpId, valency: ValencyType },
+}
+
+/// Result of checking the issued supply declared by the contract genesis
+/// against the amounts it allocates.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum SupplyError {
+    /// genesis declares issued supply of {declared}, while it allocates
+    /// {computed}.
+    SupplyMismatch { declared: u64, computed: u64 },
+
+    /// issued supply of {declared} can't be verified exactly since
+    /// {concealed} genesis allocations are concealed; the revealed
+    /// allocations sum up to {revealed}.
+    Unverifiable {
+        declared: u64,
+        revealed: u64,
+        concealed: usize,
+    },
+}
+
+impl SupplyError {
+    /// Detects whether the error must fail the validation. Unverifiable supply
+    /// is reported just as a warning.
+    pub fn is_failure(&self) -> bool { matches!(self, SupplyError::SupplyMismatch { .. }) }
+}
+
+/// Checks that the Pedersen commitments sum up to the commitment of the
+/// supply with the blinding factor of one.
+fn supply_committed(commitments: &[secp256k1_zkp::PedersenCommitment], supply: u64) -> bool {
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    let blinding = BlindingFactor::try_from(one).expect("one is a valid blinding factor");
+    let supply = PedersenCommitment::commit(&fungible::Revealed::with(supply, blinding));
+    secp256k1_zkp::verify_commitments_sum_to_equal(SECP256K1, commitments, &[supply.into_inner()])
+}
+
+/// Checks that each of the valencies redeemed by an extension of the given
+/// type is allowed by the schema, and is declared by the operation it
+/// references. Valencies declared by an operation are provided by
+/// `declared`, which returns `None` for unknown operations.
+pub(crate) fn check_redeemed<'op>(
+    schema: &SubSchema,
+    extension_type: ExtensionType,
+    redeemed: impl IntoIterator<Item = (ValencyType, OpId)>,
+    declared: impl Fn(OpId) -> Option<&'op Valencies>,
+) -> Result<(), ValencyError> {
+    let redeems = schema
+        .extensions
+        .get(&extension_type)
+        .map(|extension_schema| &extension_schema.redeems);
+    for (valency, opid) in redeemed {
+        if !schema.valency_types.contains(&valency) ||
+            !redeems.map(|redeems| redeems.contains(&valency)).unwrap_or_default()
+        {
+            return Err(ValencyError::UnknownValency(extension_type, valency));
+        }
+        if !declared(opid)
+            .map(|valencies| valencies.contains(&valency))
+            .unwrap_or_default()
+        {
+            return Err(ValencyError::ValencyNotDeclared { opid, valency });
+        }
+    }
+    Ok(())
+}
+
+/// Single entry (failure, warning or information message) in the
+/// [`ValidationReport`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "snake_case")
+)]
+pub struct ReportEntry {
+    /// Snake-case name of the entry type.
+    pub kind: String,
+    /// Human-readable description of the entry, including ids of the related
+    /// operations, bundles and transactions.
+    pub message: String,
+}
+
+impl ReportEntry {
+    fn with(entry: &(impl ReportKind + Display)) -> Self {
+        ReportEntry {
+            kind: entry.kind().to_owned(),
+            message: entry.to_string(),
+        }
+    }
+}
+
+/// Representation of the [`validation::Status`] suitable for archiving and
+/// processing by external tools. All ids are represented as strings.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "snake_case")
+)]
+pub struct ValidationReport {
+    pub contract_id: Option<String>,
+    pub validity: String,
+    pub unresolved_txids: Vec<String>,
+    pub unmined_endpoint_txids: Vec<String>,
+    pub failures: Vec<ReportEntry>,
+    pub warnings: Vec<ReportEntry>,
+    pub info: Vec<ReportEntry>,
+}
+
+impl ValidationReport {
+    pub fn with(status: &validation::Status, contract_id: Option<ContractId>) -> Self {
+        ValidationReport {
+            contract_id: contract_id.as_ref().map(ContractId::to_string),
+            validity: status.validity().kind().to_owned(),
+            unresolved_txids: status.unresolved_txids.iter().map(ToString::to_string).collect(),
+            unmined_endpoint_txids: status
+                .unmined_endpoint_txids
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            failures: status.failures.iter().map(ReportEntry::with).collect(),
+            warnings: status.warnings.iter().map(ReportEntry::with).collect(),
+            info: status.info.iter().map(ReportEntry::with).collect(),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report serialization can't fail")
+    }
+}
+
+/// Stable snake-case names of the validation status entries, which are used
+/// in the [`ValidationReport`] independently from the naming and ordering of
+/// the enum variants.
+trait ReportKind {
+    fn kind(&self) -> &'static str;
+}
+
+impl ReportKind for Validity {
+    fn kind(&self) -> &'static str {
+        match self {
+            Validity::Valid => "valid",
+            Validity::ValidExceptEndpoints => "valid_except_endpoints",
+            Validity::UnresolvedTransactions => "unresolved_transactions",
+            Validity::Invalid => "invalid",
+        }
+    }
+}
+
+impl ReportKind for Failure {
+    fn kind(&self) -> &'static str {
+        match self {
+            Failure::SchemaUnknown(..) => "schema_unknown",
+            Failure::SchemaRootRequired(..) => "schema_root_required",
+            Failure::SchemaRootHierarchy => "schema_root_hierarchy",
+            Failure::SchemaRootNoFieldTypeMatch(..) => "schema_root_no_field_type_match",
+            Failure::SchemaRootNoOwnedRightTypeMatch(..) => "schema_root_no_owned_right_type_match",
+            Failure::SchemaRootNoPublicRightTypeMatch(..) => {
+                "schema_root_no_public_right_type_match"
+            }
+            Failure::SchemaRootNoTransitionTypeMatch(..) => "schema_root_no_transition_type_match",
+            Failure::SchemaRootNoExtensionTypeMatch(..) => "schema_root_no_extension_type_match",
+            Failure::SchemaRootNoMetadataMatch(..) => "schema_root_no_metadata_match",
+            Failure::SchemaRootNoParentOwnedRightsMatch(..) => {
+                "schema_root_no_parent_owned_rights_match"
+            }
+            Failure::SchemaRootNoParentPublicRightsMatch(..) => {
+                "schema_root_no_parent_public_rights_match"
+            }
+            Failure::SchemaRootNoOwnedRightsMatch(..) => "schema_root_no_owned_rights_match",
+            Failure::SchemaRootNoPublicRightsMatch(..) => "schema_root_no_public_rights_match",
+            Failure::SchemaUnknownExtensionType(..) => "schema_unknown_extension_type",
+            Failure::SchemaUnknownTransitionType(..) => "schema_unknown_transition_type",
+            Failure::SchemaUnknownFieldType(..) => "schema_unknown_field_type",
+            Failure::SchemaUnknownOwnedRightType(..) => "schema_unknown_owned_right_type",
+            Failure::SchemaUnknownPublicRightType(..) => "schema_unknown_public_right_type",
+            Failure::SchemaDeniedScriptExtension(..) => "schema_denied_script_extension",
+            Failure::SchemaMetaValueTooSmall(..) => "schema_meta_value_too_small",
+            Failure::SchemaMetaValueTooLarge(..) => "schema_meta_value_too_large",
+            Failure::SchemaStateValueTooSmall(..) => "schema_state_value_too_small",
+            Failure::SchemaStateValueTooLarge(..) => "schema_state_value_too_large",
+            Failure::SchemaWrongEnumValue { .. } => "schema_wrong_enum_value",
+            Failure::SchemaWrongDataLength { .. } => "schema_wrong_data_length",
+            Failure::SchemaMismatchedDataType(..) => "schema_mismatched_data_type",
+            Failure::SchemaMetaOccurrencesError(..) => "schema_meta_occurrences_error",
+            Failure::SchemaParentOwnedRightOccurrencesError(..) => {
+                "schema_parent_owned_right_occurrences_error"
+            }
+            Failure::SchemaOwnedRightOccurrencesError(..) => "schema_owned_right_occurrences_error",
+            Failure::SchemaScriptOverrideDenied => "schema_script_override_denied",
+            Failure::SchemaScriptVmChangeDenied => "schema_script_vm_change_denied",
+            Failure::SchemaTypeSystem(..) => "schema_type_system",
+            Failure::OperationAbsent(..) => "operation_absent",
+            Failure::TransitionAbsent(..) => "transition_absent",
+            Failure::BundleInvalid(..) => "bundle_invalid",
+            Failure::NotAnchored(..) => "not_anchored",
+            Failure::NotInAnchor(..) => "not_in_anchor",
+            Failure::NoPrevState { .. } => "no_prev_state",
+            Failure::NoPrevOut(..) => "no_prev_out",
+            Failure::ConfidentialSeal(..) => "confidential_seal",
+            Failure::MpcInvalid(..) => "mpc_invalid",
+            Failure::SealNoWitnessTx(..) => "seal_no_witness_tx",
+            Failure::SealInvalid(..) => "seal_invalid",
+            Failure::AnchorInvalid(..) => "anchor_invalid",
+            Failure::ValencyNoParent { .. } => "valency_no_parent",
+            Failure::NoPrevValency { .. } => "no_prev_valency",
+            Failure::StateTypeMismatch { .. } => "state_type_mismatch",
+            Failure::FungibleTypeMismatch { .. } => "fungible_type_mismatch",
+            Failure::InvalidStateDataType(..) => "invalid_state_data_type",
+            Failure::InvalidStateDataValue(..) => "invalid_state_data_value",
+            Failure::BulletproofsInvalid(..) => "bulletproofs_invalid",
+            Failure::ScriptFailure(..) => "script_failure",
+            Failure::Custom(..) => "custom",
+        }
+    }
+}
+
+impl ReportKind for Warning {
+    fn kind(&self) -> &'static str {
+        match self {
+            Warning::EndpointDuplication(..) => "endpoint_duplication",
+            Warning::EndpointTransitionSealNotFound(..) => "endpoint_transition_seal_not_found",
+            Warning::ExcessiveNode(..) => "excessive_node",
+            Warning::EndpointTransactionMissed(..) => "endpoint_transaction_missed",
+            Warning::Custom(..) => "custom",
+        }
+    }
+}
+
+impl ReportKind for Info {
+    fn kind(&self) -> &'static str {
+        match self {
+            Info::UncheckableConfidentialState(..) => "uncheckable_confidential_state",
+            Info::Custom(..) => "custom",
+        }
+    }
+}
+
+/// Resource which use during the validation is limited by
+/// [`ValidationConfig`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+pub enum ResourceLimit {
+    #[display("number of operations")]
+    Operations,
+
+    #[display("bundle size")]
+    BundleSize,
+
+    #[display("type system size")]
+    TypeSystemSize,
+
+    #[display("validation time")]
+    ValidationTime,
+}
+
+impl From<ResourceLimit> for Failure {
+    fn from(limit: ResourceLimit) -> Self {
+        Failure::Custom(format!("resource limit exceeded: {limit}"))
+    }
+}
+
+/// Errors reading consignment from a stream with
+/// [`Consignment::strict_read_with`].
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum LimitedReadError {
+    /// resource limit exceeded while reading the consignment: {0}.
+    #[from]
+    Limit(ResourceLimit),
+
+    #[display(inner)]
+    #[from]
+    Decode(DecodeError),
+}
+
+/// Reader failing once the validation time is exceeded, such that reading of
+/// a large stream is interrupted as soon as the deadline passes.
+struct DeadlineReader<R: Read> {
+    inner: R,
+    start: Instant,
+    max_time: Duration,
+    exceeded: bool,
+}
+
+impl<R: Read> Read for DeadlineReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.start.elapsed() > self.max_time {
+            self.exceeded = true;
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Read> DeadlineReader<R> {
+    fn decode<T: StrictDecode>(&mut self) -> Result<T, LimitedReadError> {
+        let result = T::strict_decode(&mut StrictReader::with(usize::MAX, &mut *self));
+        self.check(result)
+    }
+
+    /// Decodes the value of a collection which length prefix was already
+    /// read from the stream.
+    fn decode_prefixed<T: StrictDecode>(&mut self, len: u8) -> Result<T, LimitedReadError> {
+        let prefix = [len];
+        let reader = prefix.chain(&mut *self);
+        let result = T::strict_decode(&mut StrictReader::with(usize::MAX, reader));
+        self.check(result)
+    }
+
+    fn check<T>(&self, result: Result<T, DecodeError>) -> Result<T, LimitedReadError> {
+        result.map_err(|err| {
+            if self.exceeded {
+                ResourceLimit::ValidationTime.into()
+            } else {
+                err.into()
+            }
+        })
+    }
+}
+
+/// Limits on the resources used by the consignment validation, protecting
+/// validating devices from consignments with pathological structure.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ValidationConfig {
+    /// Maximal number of operations in the consignment, including genesis.
+    pub max_operations: usize,
+    /// Maximal number of state transitions in a single bundle.
+    pub max_bundle_size: usize,
+    /// Maximal size of the schema type system, in serialized bytes.
+    pub max_type_system_size: usize,
+    /// Maximal validation time. Validation of the operations is performed by
+    /// the RGB consensus library and can't be interrupted, thus the time is
+    /// checked between the validation stages.
+    pub max_validation_time: Duration,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        ValidationConfig {
+            max_operations: 100_000,
+            max_bundle_size: 1024,
+            max_type_system_size: 1024 * 1024,
+            max_validation_time: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ValidationConfig {
+    /// Checks the consignment structure against the limits before any
+    /// operation is validated. The consignment is walked once, stopping at
+    /// the first exceeded limit. Consignments from untrusted streams should
+    /// be read with [`Consignment::strict_read_with`], which checks the same
+    /// limits before the whole stream is decoded.
+    fn check_structure<const TYPE: bool>(
+        &self,
+        consignment: &Consignment<TYPE>,
+        start: Instant,
+    ) -> Result<(), ResourceLimit> {
+        if encoded_len(&consignment.schema.type_system) > self.max_type_system_size {
+            return Err(ResourceLimit::TypeSystemSize);
+        }
+        let mut operations = 1 + consignment.extensions.len();
+        for anchored_bundle in &consignment.bundles {
+            let bundle_size = anchored_bundle.bundle.len();
+            if bundle_size > self.max_bundle_size {
+                return Err(ResourceLimit::BundleSize);
+            }
+            operations += bundle_size;
+            if operations > self.max_operations {
+                return Err(ResourceLimit::Operations);
+            }
+            if start.elapsed() > self.max_validation_time {
+                return Err(ResourceLimit::ValidationTime);
+            }
+        }
+        if operations > self.max_operations {
+            return Err(ResourceLimit::Operations);
+        }
+        Ok(())
+    }
+}
 
 impl<const TYPE: bool> Consignment<TYPE> {
+    /// Reads consignment from a stream in the current container layout,
+    /// enforcing the structural limits of the `config` while the stream is
+    /// read. Each bundle and the list of extensions are checked against the
+    /// limits once their length is read and before their data are decoded,
+    /// and reading fails as soon as the validation time is exceeded.
+    ///
+    /// The consignment still has to be validated; the limits are checked
+    /// again by the validation, since its time budget starts anew.
+    pub fn strict_read_with(
+        reader: impl Read,
+        config: &ValidationConfig,
+    ) -> Result<Self, LimitedReadError> {
+        let mut reader = DeadlineReader {
+            inner: reader,
+            start: Instant::now(),
+            max_time: config.max_validation_time,
+            exceeded: false,
+        };
+
+        let version = reader.decode()?;
+        let transfer = reader.decode()?;
+        let schema: SubSchema = reader.decode()?;
+        if encoded_len(&schema.type_system) > config.max_type_system_size {
+            return Err(ResourceLimit::TypeSystemSize.into());
+        }
+        let ifaces = reader.decode()?;
+        let genesis = reader.decode()?;
+        let terminals = reader.decode()?;
+
+        let mut operations = 1usize;
+        let mut bundles = vec![];
+        for _ in 0..reader.decode::<u32>()? {
+            let anchor: Anchor<_> = reader.decode()?;
+            let bundle_size = reader.decode::<u8>()?;
+            if bundle_size as usize > config.max_bundle_size {
+                return Err(ResourceLimit::BundleSize.into());
+            }
+            operations += bundle_size as usize;
+            if operations > config.max_operations {
+                return Err(ResourceLimit::Operations.into());
+            }
+            let bundle: TransitionBundle = reader.decode_prefixed(bundle_size)?;
+            bundles.push(AnchoredBundle { anchor, bundle });
+        }
+
+        let extension_count = reader.decode::<u32>()?;
+        if operations + extension_count as usize > config.max_operations {
+            return Err(ResourceLimit::Operations.into());
+        }
+        let mut extensions = vec![];
+        for _ in 0..extension_count {
+            extensions.push(reader.decode::<Extension>()?);
+        }
+
+        Ok(Consignment {
+            validation_status: None,
+            version,
+            transfer,
+            schema,
+            ifaces,
+            genesis,
+            terminals,
+            bundles: Confined::try_from(bundles).map_err(DecodeError::from)?,
+            extensions: Confined::try_from(extensions).map_err(DecodeError::from)?,
+            attachments: reader.decode()?,
+            signatures: reader.decode()?,
+            anchor_height: reader.decode()?,
+            category: reader.decode()?,
+        })
+    }
+
+    /// Validates consignment with the default [`ValidationConfig`].
     pub fn validate<R: ResolveTx>(
-        mut self,
+        self,
         resolver: &mut R,
     ) -> Result<Consignment<TYPE>, Consignment<TYPE>> {
-        let mut status = Validator::validate(&self, resolver);
+        self.validate_with(resolver, &ValidationConfig::default())
+    }
 
-        let validity = status.validity();
+    /// Validates consignment, failing with a custom validation failure once
+    /// any of the resource limits from the `config` is exceeded.
+    pub fn validate_with<R: ResolveTx>(
+        self,
+        resolver: &mut R,
+        config: &ValidationConfig,
+    ) -> Result<Consignment<TYPE>, Consignment<TYPE>> {
+        let start = Instant::now();
+        let consignment = self.check_limits(config, start)?;
+        let fetch = WitnessFetch::new(consignment.witness_txids())
+            .resolve_with(|txid| resolver.resolve_tx(txid));
+        consignment.complete_validation(fetch, config, start)
+    }
+
+    /// Async version of [`Self::validate_with`], which resolves witness
+    /// transactions with an async resolver. Validation of the resolved data
+    /// is the same as for the blocking resolvers.
+    #[cfg(feature = "async")]
+    pub async fn validate_async<R: AsyncResolveTx>(
+        self,
+        resolver: &R,
+        config: &ValidationConfig,
+    ) -> Result<Consignment<TYPE>, Consignment<TYPE>> {
+        let start = Instant::now();
+        let consignment = self.check_limits(config, start)?;
+        let mut fetch = WitnessFetch::new(consignment.witness_txids());
+        while let Some(txid) = fetch.next_txid() {
+            let result = resolver.resolve_tx_async(txid).await;
+            fetch.provide(txid, result);
+        }
+        consignment.complete_validation(fetch, config, start)
+    }
+
+    fn check_limits(
+        mut self,
+        config: &ValidationConfig,
+        start: Instant,
+    ) -> Result<Consignment<TYPE>, Consignment<TYPE>> {
+        if let Err(limit) = config.check_structure(&self, start) {
+            let mut status = validation::Status::new();
+            status.add_failure(limit);
+            self.validation_status = Some(status);
+            return Err(self);
+        }
+        Ok(self)
+    }
+
+    fn complete_validation(
+        mut self,
+        fetch: WitnessFetch<Tx, TxResolverError>,
+        config: &ValidationConfig,
+        start: Instant,
+    ) -> Result<Consignment<TYPE>, Consignment<TYPE>> {
+        let mut status = Validator::validate(&self, &fetch);
 
         if self.transfer != TYPE {
             status.add_warning(Warning::Custom(s!("invalid consignment type")));
         }
+        if start.elapsed() > config.max_validation_time {
+            status.add_failure(ResourceLimit::ValidationTime);
+        } else {
+            for err in self.valency_errors() {
+                status.add_failure(Failure::Custom(err.to_string()));
+            }
+            for err in self.supply_errors() {
+                if err.is_failure() {
+                    status.add_failure(Failure::Custom(err.to_string()));
+                } else {
+                    status.add_warning(Warning::Custom(err.to_string()));
+                }
+            }
+        }
+        let validity = status.validity();
+
         // TODO: check that interface ids match implementations
         // TODO: check bundle ids listed in terminals are present in the consignment
         // TODO: check attach ids from data containers are present in operations
@@ -46,4 +604,481 @@ impl<const TYPE: bool> Consignment<TYPE> {
             Ok(self)
         }
     }
+
+    /// Checks valencies redeemed by all state extensions of the consignment
+    /// against the schema and the operations declaring them.
+    pub(crate) fn valency_errors(&self) -> Vec<ValencyError> {
+        self.extensions
+            .iter()
+            .filter_map(|extension| {
+                check_redeemed(
+                    &self.schema,
+                    extension.extension_type,
+                    extension
+                        .redeemed
+                        .iter()
+                        .flat_map(|(opid, valencies)| valencies.iter().map(|v| (*v, *opid))),
+                    |opid| self.operation_valencies(opid),
+                )
+                .err()
+            })
+            .collect()
+    }
+
+    /// Checks issued supply declared by the genesis against the sum of the
+    /// amounts allocated by it, for each of the interfaces which link global
+    /// state `IssuedSupply` with the owned state `Assets`.
+    ///
+    /// Amounts of the concealed allocations are known only as Pedersen
+    /// commitments, which can't be opened without their blinding factors. In
+    /// this case the supply is checked to be not less than the sum of the
+    /// revealed amounts, and the commitments of all the allocations are
+    /// checked to sum up to the commitment of the declared supply with the
+    /// blinding factor of one. The issuer of the contract with concealed
+    /// allocations makes its supply verifiable by choosing the blinding
+    /// factors of the allocations summing up to one; otherwise the supply is
+    /// reported as unverifiable.
+    pub(crate) fn supply_errors(&self) -> Vec<SupplyError> {
+        let assets_name = tn!("Assets");
+        let mut errors = vec![];
+        for pair in self.ifaces.values() {
+            if !pair.iface.owned_state.contains_key(&assets_name) {
+                continue;
+            }
+            let (Some(declared), Some(assets_type)) =
+                (self.declared_supply(pair), pair.iimpl.assignments_type(&assets_name))
+            else {
+                continue;
+            };
+
+            let mut revealed = 0u64;
+            let mut concealed = 0usize;
+            let mut commitments = vec![];
+            for_each_assign(&self.genesis.assignments, |view| {
+                let StateCommitment::Fungible(commitment) = view.commitment else {
+                    return;
+                };
+                if view.ty != assets_type {
+                    return;
+                }
+                match view.state {
+                    Some(StateData::Fungible(state)) => {
+                        revealed = revealed.saturating_add(state.value.as_u64())
+                    }
+                    _ => concealed += 1,
+                }
+                commitments.push(commitment.commitment.into_inner());
+            });
+
+            if (concealed == 0 && revealed != declared) || revealed > declared {
+                errors.push(SupplyError::SupplyMismatch {
+                    declared,
+                    computed: revealed,
+                });
+            } else if concealed > 0 && !supply_committed(&commitments, declared) {
+                errors.push(SupplyError::Unverifiable {
+                    declared,
+                    revealed,
+                    concealed,
+                });
+            }
+        }
+        errors
+    }
+
+    fn operation_valencies(&self, opid: OpId) -> Option<&Valencies> {
+        if self.genesis.id() == opid {
+            return Some(&self.genesis.valencies);
+        }
+        self.bundles
+            .iter()
+            .flat_map(|anchored_bundle| anchored_bundle.bundle.values())
+            .filter_map(|item| item.transition.as_ref())
+            .find(|transition| transition.id() == opid)
+            .map(|transition| &transition.valencies)
+            .or_else(|| {
+                self.extensions
+                    .iter()
+                    .find(|extension| extension.id() == opid)
+                    .map(|extension| &extension.valencies)
+            })
+    }
+
+    /// Returns report on the latest validation, if the consignment was
+    /// validated.
+    pub fn validation_report(&self) -> Option<ValidationReport> {
+        self.validation_status
+            .as_ref()
+            .map(|status| ValidationReport::with(status, Some(self.contract_id())))
+    }
+}
+
+#[cfg(feature = "fs")]
+pub use _fs::{ContractFileError, ContractFileResults};
+
+#[cfg(feature = "fs")]
+mod _fs {
+    use std::path::{Path, PathBuf};
+    use std::{fs, io};
+
+    use rgb::SchemaId;
+
+    use super::*;
+    use crate::containers::{Bindle, Contract, LoadError};
+    use crate::resolvers::SchemaProvider;
+
+    /// Errors of validating contract stored in a file.
+    #[derive(Debug, Display, Error, From)]
+    #[display(doc_comments)]
+    pub enum ContractFileError {
+        #[from]
+        #[display(inner)]
+        Load(LoadError),
+
+        /// contract uses schema {0::<0}, which is not known to the schema
+        /// provider.
+        UnknownSchema(SchemaId),
+
+        /// contract is invalid.
+        Invalid(validation::Status),
+    }
+
+    /// Per-file results of [`Contract::validate_dir`].
+    pub type ContractFileResults = Vec<(PathBuf, Result<(), ContractFileError>)>;
+
+    impl Contract {
+        /// Loads and validates all contract files (having `rgb` extension)
+        /// from the directory, using the schemata from the provider instead of
+        /// the ones embedded into the contracts.
+        ///
+        /// Failure to load or validate some of the contracts doesn't stop the
+        /// validation of the rest of them; the results are reported for each
+        /// of the files in the order of their paths.
+        pub fn validate_dir<R: ResolveTx>(
+            dir: impl AsRef<Path>,
+            schemas: &impl SchemaProvider,
+            resolver: &mut R,
+        ) -> Result<ContractFileResults, io::Error> {
+            let mut paths = fs::read_dir(dir)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            paths.retain(|path| path.is_file() && path.extension() == Some("rgb".as_ref()));
+            paths.sort();
+            Ok(paths
+                .into_iter()
+                .map(|path| {
+                    let res = Self::validate_file(&path, schemas, resolver);
+                    (path, res)
+                })
+                .collect())
+        }
+
+        fn validate_file<R: ResolveTx>(
+            path: &Path,
+            schemas: &impl SchemaProvider,
+            resolver: &mut R,
+        ) -> Result<(), ContractFileError> {
+            let mut contract = Bindle::<Contract>::load(path)?.unbindle();
+            let schema_id = contract.schema_id();
+            contract.schema = schemas
+                .schema(schema_id)
+                .ok_or(ContractFileError::UnknownSchema(schema_id))?
+                .clone();
+            contract.validate(resolver).map(|_| ()).map_err(|contract| {
+                ContractFileError::Invalid(
+                    contract
+                        .validation_status()
+                        .expect("validated contract always has status")
+                        .clone(),
+                )
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use std::collections::BTreeMap;
+
+        use bp::{Tx, Txid};
+        use rgb::validation::TxResolverError;
+
+        use super::*;
+        use crate::containers::builder::test::{issuable, schema};
+        use crate::fixtures::{test_dir, GS_NOMINAL};
+
+        struct NoTxs;
+
+        impl ResolveTx for NoTxs {
+            fn resolve_tx(&self, _txid: Txid) -> Result<Tx, TxResolverError> {
+                unreachable!("genesis-only contracts have no witness transactions")
+            }
+        }
+
+        #[test]
+        fn validate_dir() {
+            let dir = test_dir("validate-dir");
+
+            let contract = issuable().issue_contract().unwrap();
+            Bindle::new(contract.clone()).save(dir.join("a.rgb")).unwrap();
+            // Contract under a modified schema, which is not known to the provider
+            let mut unknown = contract;
+            unknown.schema.global_types.get_mut(&GS_NOMINAL).unwrap().max_items += 1;
+            Bindle::new(unknown.clone()).save(dir.join("b.rgb")).unwrap();
+            fs::write(dir.join("c.rgb"), b"RGBCNRC\x02").unwrap();
+            fs::write(dir.join("d.txt"), b"not a contract").unwrap();
+
+            let schema = schema();
+            let schemas: BTreeMap<_, _> = bmap! { schema.schema_id() => schema };
+            let results = Contract::validate_dir(&dir, &schemas, &mut NoTxs).unwrap();
+            let names = results
+                .iter()
+                .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(names, ["a.rgb", "b.rgb", "c.rgb"]);
+            assert!(results[0].1.is_ok());
+            assert!(matches!(
+                results[1].1,
+                Err(ContractFileError::UnknownSchema(id)) if id == unknown.schema_id()
+            ));
+            assert!(matches!(results[2].1, Err(ContractFileError::Load(_))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::SmallBlob;
+    use bp::seals::txout::CloseMethod;
+    use bp::{Chain, Tx, Txid};
+    use rgb::validation::TxResolverError;
+    use rgb::{GlobalStateType, GraphSeal};
+    use strict_encoding::StrictSerialize;
+
+    use super::*;
+    use crate::containers::builder::test::{builder, iimpl, issuable, outpoint, required_globals};
+    use crate::containers::contract::test::conceal_genesis_assets;
+    use crate::containers::Contract;
+    use crate::interface::{rgb20, IfacePair, NamedType, Req};
+
+    const GS_SUPPLY: GlobalStateType = 2010;
+
+    fn supply_contract(declared: u64) -> Contract {
+        with_supply(issuable().issue_contract().unwrap(), declared)
+    }
+
+    fn with_supply(mut contract: Contract, declared: u64) -> Contract {
+        let mut iface = rgb20();
+        iface
+            .global_state
+            .insert(tn!("IssuedSupply"), Req::require_any())
+            .unwrap();
+        let mut iimpl = iimpl(&contract.schema, &iface);
+        iimpl
+            .global_state
+            .push(NamedType::with(GS_SUPPLY, tn!("IssuedSupply")))
+            .unwrap();
+        let pair = IfacePair::with(iface, iimpl);
+        contract.ifaces = tiny_bmap! { pair.iface_id() => pair };
+        let data = SmallBlob::try_from(declared.to_le_bytes().to_vec()).unwrap();
+        contract.genesis.globals.add_state(GS_SUPPLY, data.into()).unwrap();
+        contract
+    }
+
+    #[test]
+    fn supply() {
+        assert_eq!(supply_contract(100_000).supply_errors(), vec![]);
+        assert_eq!(supply_contract(100_001).supply_errors(), vec![SupplyError::SupplyMismatch {
+            declared: 100_001,
+            computed: 100_000
+        }]);
+        assert!(issuable().issue_contract().unwrap().supply_errors().is_empty());
+    }
+
+    #[test]
+    fn supply_concealed() {
+        let mut contract = supply_contract(100_000);
+        conceal_genesis_assets(&mut contract, usize::MAX);
+        let errors = contract.supply_errors();
+        assert_eq!(errors, vec![SupplyError::Unverifiable {
+            declared: 100_000,
+            revealed: 0,
+            concealed: 1
+        }]);
+        assert!(!errors[0].is_failure());
+    }
+
+    #[test]
+    fn supply_committed() {
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        let state = fungible::Revealed::with(100_000u64, BlindingFactor::try_from(one).unwrap());
+        let seal = GraphSeal::new(CloseMethod::TapretFirst, outpoint(0).txid, outpoint(0).vout);
+        let contract = required_globals(builder())
+            .set_chain(Chain::Bitcoin)
+            .add_fungible_seal("Assets", seal, state)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+
+        let mut concealed = with_supply(contract.clone(), 100_000);
+        conceal_genesis_assets(&mut concealed, usize::MAX);
+        assert_eq!(concealed.supply_errors(), vec![]);
+
+        let mut concealed = with_supply(contract, 100_001);
+        conceal_genesis_assets(&mut concealed, usize::MAX);
+        assert_eq!(concealed.supply_errors(), vec![SupplyError::Unverifiable {
+            declared: 100_001,
+            revealed: 0,
+            concealed: 1
+        }]);
+    }
+
+    struct NoTxs;
+
+    impl ResolveTx for NoTxs {
+        fn resolve_tx(&self, _txid: Txid) -> Result<Tx, TxResolverError> {
+            unreachable!("resource limits are checked before resolving transactions")
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn validate_async() {
+        use crate::fixtures::block_on;
+        use crate::resolvers::Blocking;
+
+        let contract = issuable().issue_contract().unwrap();
+        let validated = contract.clone().validate(&mut NoTxs).unwrap();
+        let expected = validated.validation_status().unwrap();
+
+        let resolver = Blocking(NoTxs);
+        let config = ValidationConfig::default();
+        let result = block_on(contract.validate_async(&resolver, &config)).unwrap();
+        let status = result.validation_status().unwrap();
+        assert_eq!(status.failures, expected.failures);
+        assert_eq!(status.warnings, expected.warnings);
+        assert_eq!(status.unresolved_txids, expected.unresolved_txids);
+        assert_eq!(status.validity(), Validity::Valid);
+    }
+
+    #[test]
+    fn resource_limits() {
+        let contract = issuable().issue_contract().unwrap();
+        let failures = |config: ValidationConfig| {
+            let invalid = contract.clone().validate_with(&mut NoTxs, &config).unwrap_err();
+            invalid.validation_status().unwrap().failures.clone()
+        };
+        assert_eq!(
+            failures(ValidationConfig {
+                max_operations: 0,
+                ..default!()
+            }),
+            vec![ResourceLimit::Operations.into()]
+        );
+        assert_eq!(
+            failures(ValidationConfig {
+                max_type_system_size: 0,
+                ..default!()
+            }),
+            vec![ResourceLimit::TypeSystemSize.into()]
+        );
+    }
+
+    #[test]
+    fn limited_read() {
+        let transfer = crate::fixtures::transfer_consignment();
+        let data = transfer.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let config = ValidationConfig::default();
+        let read = Consignment::<true>::strict_read_with(data.as_slice(), &config).unwrap();
+        assert_eq!(read.transfer_id(), transfer.transfer_id());
+
+        // the stream is cut right after the length of the first bundle, such
+        // that its subsequent decoding fails
+        let header = encoded_len(&transfer.version) +
+            encoded_len(&transfer.transfer) +
+            encoded_len(&transfer.schema) +
+            encoded_len(&transfer.ifaces) +
+            encoded_len(&transfer.genesis) +
+            encoded_len(&transfer.terminals);
+        let cut = &data[..header + 4 + encoded_len(&transfer.bundles[0].anchor) + 1];
+        assert!(matches!(
+            Consignment::<true>::strict_read_with(cut, &config).unwrap_err(),
+            LimitedReadError::Decode(_)
+        ));
+        let config = ValidationConfig {
+            max_operations: 1,
+            ..default!()
+        };
+        assert_eq!(
+            Consignment::<true>::strict_read_with(cut, &config).unwrap_err(),
+            LimitedReadError::Limit(ResourceLimit::Operations)
+        );
+        let config = ValidationConfig {
+            max_type_system_size: 0,
+            ..default!()
+        };
+        assert_eq!(
+            Consignment::<true>::strict_read_with(cut, &config).unwrap_err(),
+            LimitedReadError::Limit(ResourceLimit::TypeSystemSize)
+        );
+        let config = ValidationConfig {
+            max_validation_time: Duration::ZERO,
+            ..default!()
+        };
+        assert_eq!(
+            Consignment::<true>::strict_read_with(cut, &config).unwrap_err(),
+            LimitedReadError::Limit(ResourceLimit::ValidationTime)
+        );
+    }
+
+    struct UnknownTxs;
+
+    impl ResolveTx for UnknownTxs {
+        fn resolve_tx(&self, txid: Txid) -> Result<Tx, TxResolverError> {
+            Err(TxResolverError::Unknown(txid))
+        }
+    }
+
+    #[test]
+    fn report_kinds() {
+        assert_eq!(Validity::ValidExceptEndpoints.kind(), "valid_except_endpoints");
+        assert_eq!(Failure::SchemaRootHierarchy.kind(), "schema_root_hierarchy");
+        assert_eq!(Warning::Custom(s!("Some text")).kind(), "custom");
+        assert_eq!(Info::Custom(s!("Some text")).kind(), "custom");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn report_json() {
+        let transfer = crate::fixtures::transfer_consignment();
+        let contract_id = transfer.contract_id();
+        let invalid = transfer.validate(&mut UnknownTxs).unwrap_err();
+        let report = ValidationReport::with(invalid.validation_status().unwrap(), Some(contract_id));
+        assert_eq!(
+            report.to_json(),
+            r#"{
+  "contract_id": "DPyPzvHy9QBvEjDy6N5rFLDccMWtMpRXFj6tmZbaVGDE",
+  "validity": "unresolved_transactions",
+  "unresolved_txids": [
+    "5151515151515151515151515151515151515151515151515151515151515151"
+  ],
+  "unmined_endpoint_txids": [
+    "5252525252525252525252525252525252525252525252525252525252525252"
+  ],
+  "failures": [
+    {
+      "kind": "seal_no_witness_tx",
+      "message": "SealNoWitnessTx(Txid(Array<32>(5151515151515151515151515151515151515151515151515151515151515151)))"
+    }
+  ],
+  "warnings": [
+    {
+      "kind": "endpoint_transaction_missed",
+      "message": "EndpointTransactionMissed(Txid(Array<32>(5252525252525252525252525252525252525252525252525252525252525252)))"
+    }
+  ],
+  "info": []
+}"#
+        );
+    }
 }
@@ -0,0 +1,398 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deterministic fixtures for testing code working with RGB contracts: a
+//! minimal fungible schema, its interface and interface implementation, and
+//! helpers issuing and transferring contracts with fixed blinding factors.
+//!
+//! All the data produced by the fixtures, including contract and operation
+//! ids, are stable across releases of the library, such that they can be used
+//! in snapshot tests.
+
+use bp::dbc::{Anchor, Proof};
+use bp::seals::txout::CloseMethod;
+use bp::secp256k1::rand::{self, RngCore};
+use bp::{Chain, Outpoint, Txid};
+use commit_verify::{mpc, CommitVerify};
+use rgb::validation::AnchoredBundle;
+use rgb::{
+    fungible, AssignmentsType, FungibleType, GenesisSchema, GlobalStateSchema, GlobalStateType,
+    GraphSeal, Occurrences, OpId, Operation, Opout, PedersenCommitment, Schema, StateSchema,
+    SubSchema, Transition, TransitionBundle, TransitionSchema, TransitionType,
+};
+
+use crate::containers::{
+    Contract, ContractBuilder, EndpointSeal, Terminal, Transfer, TransitionBuilder,
+};
+use crate::interface::{rgb20, Iface, IfaceImpl, NamedType};
+use crate::stl::{ContractText, Nominal, Precision, StandardTypes};
+
+pub const GS_NOMINAL: GlobalStateType = 2000;
+pub const GS_CONTRACT: GlobalStateType = 2001;
+pub const OS_ASSETS: AssignmentsType = 4000;
+pub const TS_TRANSFER: TransitionType = 10000;
+
+/// Amount issued by [`two_hop_transfer`].
+pub const TRANSFER_AMOUNT: u64 = 100_000;
+
+/// Minimal fungible schema with a single type of owned state, transferred by
+/// a single type of state transition.
+pub fn schema() -> SubSchema {
+    let types = StandardTypes::new();
+
+    Schema {
+        ffv: none!(),
+        subset_of: None,
+        type_system: types.type_system(),
+        global_types: tiny_bmap! {
+            GS_NOMINAL => GlobalStateSchema {
+                sem_id: types.get("RGBContract.Nominal"),
+                max_items: 1,
+            },
+            GS_CONTRACT => GlobalStateSchema {
+                sem_id: types.get("RGBContract.ContractText"),
+                max_items: 1,
+            },
+        },
+        owned_types: tiny_bmap! {
+            OS_ASSETS => StateSchema::Fungible(FungibleType::Unsigned64Bit),
+        },
+        valency_types: none!(),
+        genesis: GenesisSchema {
+            metadata: None,
+            globals: tiny_bmap! {
+                GS_NOMINAL => Occurrences::Once,
+                GS_CONTRACT => Occurrences::Once,
+            },
+            assignments: tiny_bmap! {
+                OS_ASSETS => Occurrences::OnceOrMore,
+            },
+            valencies: none!(),
+        },
+        extensions: none!(),
+        transitions: tiny_bmap! {
+            TS_TRANSFER => TransitionSchema {
+                metadata: None,
+                globals: none!(),
+                inputs: tiny_bmap! {
+                    OS_ASSETS => Occurrences::OnceOrMore,
+                },
+                assignments: tiny_bmap! {
+                    OS_ASSETS => Occurrences::OnceOrMore,
+                },
+                valencies: none!(),
+            }
+        },
+        script: strict_dumb!(),
+    }
+}
+
+/// Interface implemented by the fixture [`schema`].
+pub fn iface() -> Iface { rgb20() }
+
+/// Implementation of the fixture [`iface`] for the fixture [`schema`].
+pub fn iimpl() -> IfaceImpl {
+    IfaceImpl {
+        version: 0,
+        schema_id: schema().schema_id(),
+        iface_id: iface().iface_id(),
+        global_state: tiny_bset! {
+            NamedType::with(GS_NOMINAL, tn!("Nominal")),
+            NamedType::with(GS_CONTRACT, tn!("ContractText")),
+        },
+        owned_state: tiny_bset! {
+            NamedType::with(OS_ASSETS, tn!("Assets")),
+        },
+        valencies: none!(),
+        transitions: tiny_bset! {
+            NamedType::with(TS_TRANSFER, tn!("Transfer")),
+        },
+        extensions: none!(),
+        chains: none!(),
+        close_methods: none!(),
+        media_types: none!(),
+    }
+}
+
+/// Outpoint of a fixture transaction.
+pub fn outpoint(vout: u32) -> Outpoint { Outpoint::new(Txid::from([0xA5; 32]), vout) }
+
+/// Random number generator producing the same sequence for the same seed,
+/// used for all blinding factors of the fixtures.
+struct FixtureRng(u64);
+
+impl RngCore for FixtureRng {
+    fn next_u32(&mut self) -> u32 { self.next_u64() as u32 }
+
+    // SplitMix64
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+fn allocation(
+    rng: &mut FixtureRng,
+    outpoint: Outpoint,
+    amount: u64,
+) -> (GraphSeal, fungible::Revealed) {
+    let mut seal = GraphSeal::new(CloseMethod::TapretFirst, outpoint.txid, outpoint.vout);
+    seal.blinding = rng.next_u64();
+    (seal, fungible::Revealed::new(amount, rng))
+}
+
+/// Issues contract under the fixture [`schema`] on the bitcoin mainnet,
+/// allocating the amounts to the outpoints.
+///
+/// # Panics
+///
+/// If no allocations are provided.
+pub fn issue(allocations: &[(Outpoint, u64)]) -> Contract {
+    let mut rng = FixtureRng(0);
+    let mut builder = ContractBuilder::with(iface(), schema(), iimpl())
+        .expect("fixture interface implementation is valid")
+        .set_chain(Chain::Bitcoin)
+        .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+        .expect("fixture nominal is valid")
+        .add_global_state("ContractText", ContractText::default())
+        .expect("fixture contract text is valid");
+    for (outpoint, amount) in allocations {
+        let (seal, state) = allocation(&mut rng, *outpoint, *amount);
+        builder = builder
+            .add_fungible_seal("Assets", seal, state)
+            .expect("fixture allocation is valid");
+    }
+    builder.issue_contract().expect("fixture contract is valid")
+}
+
+/// Contract with two consecutive transfers of the whole issued amount.
+#[derive(Clone, Debug)]
+pub struct TwoHopTransfer {
+    /// Contract allocating [`TRANSFER_AMOUNT`] to `outpoint(0)`.
+    pub contract: Contract,
+    /// Transition spending the genesis allocation to `outpoint(1)`.
+    pub first: Transition,
+    /// Transition spending the allocation of the first transition to
+    /// `outpoint(2)`.
+    pub second: Transition,
+}
+
+/// Constructs contract state transferred by two state transitions.
+///
+/// The transitions are not anchored, since anchors require real witness
+/// transactions; they are provided to test the code constructing bundles,
+/// consignments and contract history.
+pub fn two_hop_transfer() -> TwoHopTransfer {
+    let contract = issue(&[(outpoint(0), TRANSFER_AMOUNT)]);
+    let mut rng = FixtureRng(1);
+    let first = transfer(&mut rng, contract.genesis.id(), outpoint(1));
+    let second = transfer(&mut rng, first.id(), outpoint(2));
+    TwoHopTransfer {
+        contract,
+        first,
+        second,
+    }
+}
+
+fn transfer(rng: &mut FixtureRng, prev: OpId, beneficiary: Outpoint) -> Transition {
+    let (seal, state) = allocation(rng, beneficiary, TRANSFER_AMOUNT);
+    let commitment = PedersenCommitment::commit(&state);
+    TransitionBuilder::with(iface(), schema(), iimpl(), "Transfer")
+        .expect("fixture interface implementation is valid")
+        .add_fungible_input(Opout::new(prev, OS_ASSETS, 0), TRANSFER_AMOUNT)
+        .expect("fixture input is valid")
+        .add_fungible_revealed("Assets", seal, state, commitment)
+        .expect("fixture allocation is valid")
+        .complete_transition()
+        .expect("fixture transition is valid")
+}
+
+/// Id of the witness transaction of the `hop`-th transition of the
+/// [`transfer_consignment`], starting from 1.
+pub fn witness_txid(hop: u8) -> Txid { Txid::from([0x50 + hop; 32]) }
+
+/// Puts the transition into a bundle anchored to the witness transaction.
+///
+/// The anchor doesn't commit to the bundle, thus the bundle doesn't pass the
+/// validation against the actual witness transaction.
+pub fn anchor(transition: Transition, witness_txid: Txid) -> AnchoredBundle {
+    let opid = transition.id();
+    // the type of the bundle items is not exported by all versions of
+    // rgb-core, thus the item is filled in after the insertion
+    let mut bundle = TransitionBundle::from(tiny_bmap! { opid => strict_dumb!() });
+    let item = bundle.get_mut(&opid).expect("inserted item");
+    item.inputs = tiny_bset! { 0 };
+    item.transition = Some(transition);
+    AnchoredBundle {
+        anchor: Anchor {
+            txid: witness_txid,
+            mpc_proof: mpc::MerkleProof::default(),
+            dbc_proof: Proof::OpretFirst,
+        },
+        bundle,
+    }
+}
+
+/// [`two_hop_transfer`] which contract contains both transitions, anchored
+/// (see [`anchor`]) to the witness transactions `witness_txid(1)` and
+/// `witness_txid(2)`.
+pub fn anchored_two_hop_transfer() -> TwoHopTransfer {
+    let TwoHopTransfer {
+        mut contract,
+        first,
+        second,
+    } = two_hop_transfer();
+    contract
+        .bundles
+        .extend([anchor(first.clone(), witness_txid(1)), anchor(second.clone(), witness_txid(2))])
+        .expect("two bundles");
+    TwoHopTransfer {
+        contract,
+        first,
+        second,
+    }
+}
+
+/// Transfer consignment of the [`anchored_two_hop_transfer`], with the
+/// allocation of the second transition to `outpoint(2)` as the only terminal.
+pub fn transfer_consignment() -> Transfer {
+    let TwoHopTransfer {
+        contract, second, ..
+    } = anchored_two_hop_transfer();
+    let seal = second.assignments[&OS_ASSETS].to_confidential_seals()[0];
+    let terminal = Terminal {
+        bundle_id: contract.bundles[1].bundle.bundle_id(),
+        seal: EndpointSeal::ConcealedUtxo(seal),
+    };
+    contract.into_transfer([terminal])
+}
+
+/// Creates an empty temporary directory, unique for the test and the process
+/// running it.
+#[cfg(all(test, feature = "fs"))]
+pub(crate) fn test_dir(test: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("rgb-{test}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("temporary directory");
+    dir
+}
+
+/// Minimal executor running the future to completion on the current thread,
+/// which is parked until the future gets woken.
+#[cfg(all(test, feature = "async"))]
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) { self.0.unpark() }
+    }
+
+    let mut future = std::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::{StrictDeserialize, StrictSerialize};
+
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        assert_eq!(
+            schema().schema_id().to_string(),
+            "GjZzcX5F9ZohFosxGR2zTh6nwEEWr6XuxkN8f7darZQu"
+        );
+        assert_eq!(iimpl().impl_id().to_string(), "9G4FhMDWS6zpFo6jTwP37dx2Bs7xT7SM5JNiAKdnHpsG");
+        assert_eq!(
+            issue(&[(outpoint(0), 10)]).contract_id().to_string(),
+            "J7hpK1WC8uUDHt4JUqGqPyf3cQUsHuA9YZg2m36odFbY"
+        );
+
+        let allocations = [(outpoint(0), 10), (outpoint(1), 20)];
+        assert_eq!(issue(&allocations).contract_id(), issue(&allocations).contract_id());
+        assert_ne!(
+            issue(&allocations).contract_id(),
+            issue(&allocations[..1]).contract_id()
+        );
+
+        let transfer = two_hop_transfer();
+        assert_eq!(
+            transfer.contract.contract_id().to_string(),
+            "DPyPzvHy9QBvEjDy6N5rFLDccMWtMpRXFj6tmZbaVGDE"
+        );
+        assert_eq!(
+            transfer.first.id().to_string(),
+            "9d9bee4dcd9b20391a3964f659b008ed817c7a8bde47ee2812d5aafeb7c9a9f7"
+        );
+        assert_eq!(
+            transfer.second.id().to_string(),
+            "e9870eb00544df4088866ea6e5b6061d789454a0fa2efc0422f0c2d549560d62"
+        );
+        assert!(transfer.first.inputs.contains_key(&transfer.contract.genesis.id()));
+        assert!(transfer.second.inputs.contains_key(&transfer.first.id()));
+    }
+
+    #[test]
+    fn id_round_trip() {
+        let iface = iface();
+        let data = iface.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let decoded = Iface::from_strict_serialized::<{ usize::MAX }>(data).unwrap();
+        assert_eq!(decoded, iface);
+        assert_eq!(decoded.iface_id(), iface.iface_id());
+
+        let schema = schema();
+        let data = schema.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let decoded = SubSchema::from_strict_serialized::<{ usize::MAX }>(data).unwrap();
+        assert_eq!(decoded.schema_id(), schema.schema_id());
+
+        let iimpl = iimpl();
+        let data = iimpl.to_strict_serialized::<{ usize::MAX }>().unwrap();
+        let decoded = IfaceImpl::from_strict_serialized::<{ usize::MAX }>(data).unwrap();
+        assert_eq!(decoded.impl_id(), iimpl.impl_id());
+    }
+}
@@ -19,27 +19,57 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::str::FromStr;
+
 use amplify::confinement::{LargeOrdMap, LargeVec, SmallVec};
 use bp::Outpoint;
-use rgb::{attachment, AssignmentsType, ContractState};
-use strict_encoding::TypeName;
+use rgb::{attachment, AssignmentsType, AttachId, ContractState, OpId};
+use strict_encoding::{StrictDeserialize, TypeName};
 use strict_types::typify::TypedVal;
 use strict_types::{reify, StrictVal};
 
-use crate::interface::IfaceImpl;
+use crate::interface::{IfaceId, IfaceImpl};
+use crate::persistence::{sniff_media_type, AttachmentError, AttachmentStore};
+use crate::stl::{ContractName, MediaType, Nominal, Precision, StandardTypes, Ticker};
 use crate::LIB_NAME_RGB_STD;
 
+thread_local! {
+    /// Standard types used by the display helpers of [`ContractIface`], which
+    /// are compiled once per thread.
+    static STANDARD_TYPES: StandardTypes = StandardTypes::new();
+}
+
 #[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
 #[display(doc_comments)]
 pub enum ContractError {
     /// type name {0} is unknown to the contract interface
     TypeNameUnknown(TypeName),
 
+    /// contract state is provided for interface {0}, which doesn't match the
+    /// expected interface.
+    IfaceMismatch(IfaceId),
+
+    /// operation {0} is not known.
+    OperationUnknown(OpId),
+
+    /// operation state of type {0} doesn't match the contract schema.
+    SchemaMismatch(u16),
+
     #[from]
     #[display(inner)]
     Reify(reify::Error),
 }
 
+#[derive(Debug, Display, Error, From)]
+#[display(inner)]
+pub enum AttachmentQueryError {
+    #[from]
+    Contract(ContractError),
+
+    #[from]
+    Store(AttachmentError),
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum TypedState {
     Void,
@@ -54,6 +84,36 @@ pub struct OwnedState {
     pub state: TypedState,
 }
 
+/// Attachment referenced by the contract state, together with the information
+/// whether its data are present in the attachment store.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct AttachedFile {
+    pub id: AttachId,
+    pub owner: Outpoint,
+    /// Media type declared by the contract state; `None` if the declared
+    /// type is not a valid media type.
+    pub media_type: Option<MediaType>,
+    pub available: bool,
+    sniffed: Option<MediaType>,
+}
+
+impl AttachedFile {
+    /// Returns media type detected from the stored attachment data if it
+    /// contradicts the media type declared by the contract state.
+    ///
+    /// The mismatch is just a warning: the data are still valid since they
+    /// match the attachment id.
+    pub fn media_mismatch(&self) -> Option<&MediaType> {
+        let sniffed = self.sniffed.as_ref()?;
+        let covered = self
+            .media_type
+            .as_ref()
+            .map(|declared| declared.covers(sniffed))
+            .unwrap_or_default();
+        (!covered).then_some(sniffed)
+    }
+}
+
 /// Contract state is an in-memory structure providing API to read structured
 /// data from the [`rgb::ContractHistory`].
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -118,7 +178,107 @@ impl ContractIface {
         Ok(LargeVec::try_from_iter(state).expect("same or smaller collection size"))
     }
 
-    // TODO: Add rights, attachments and structured data APIs
+    /// Lists attachments under the owned state with the given name, which are
+    /// assigned to the outpoints passing the filter, checking whether their
+    /// data are already present in the attachment store.
+    pub fn attachment(
+        &self,
+        name: impl Into<TypeName>,
+        filter: impl Fn(&Outpoint) -> bool,
+        store: &impl AttachmentStore,
+    ) -> Result<Vec<AttachedFile>, AttachmentQueryError> {
+        let name = name.into();
+        let type_id = self
+            .iface
+            .assignments_type(&name)
+            .ok_or(ContractError::TypeNameUnknown(name))?;
+        let mut files = vec![];
+        for outp in self
+            .state
+            .attach()
+            .iter()
+            .filter(|outp| outp.opout.ty == type_id && filter(&outp.seal))
+        {
+            let id = outp.state.id;
+            let data = store.get(id)?;
+            files.push(AttachedFile {
+                id,
+                owner: outp.seal,
+                media_type: MediaType::from_str(&outp.state.media_type).ok(),
+                available: data.is_some(),
+                sniffed: data.as_deref().and_then(sniff_media_type),
+            });
+        }
+        Ok(files)
+    }
+
+    /// Decodes the first item of the global state with the given name, if the
+    /// state is present and has the standard `RGBContract` type with the given
+    /// name. Any failures are reported as `None`.
+    fn standard_global<T: StrictDeserialize>(
+        &self,
+        name: &'static str,
+        type_name: &'static str,
+    ) -> Option<T> {
+        let sem_id = STANDARD_TYPES.with(|types| types.get(type_name));
+        self.iface.global_first(&TypeName::from(name), |type_id| {
+            let type_schema = self.state.schema.global_types.get(&type_id)?;
+            if type_schema.sem_id != sem_id {
+                return None;
+            }
+            unsafe { self.state.global_unchecked(type_id) }
+                .into_iter()
+                .next()
+        })
+    }
+
+    fn display_nominal(&self) -> Option<Nominal> {
+        self.standard_global("Nominal", "RGBContract.Nominal")
+    }
+
+    /// Best-effort name of the contract for displaying in wallets, which works
+    /// with any interface.
+    ///
+    /// The name is taken from the global state named `Nominal`, if it has the
+    /// `RGBContract.Nominal` type; otherwise from the global state `Name` of
+    /// `RGBContract.ContractName` type. Interface-specific wrappers, like
+    /// [`crate::interface::Rgb20`], should be used when the interface is known.
+    pub fn display_name(&self) -> Option<String> {
+        if let Some(nominal) = self.display_nominal() {
+            return Some(nominal.name().to_string());
+        }
+        self.standard_global::<ContractName>("Name", "RGBContract.ContractName")
+            .map(|name| name.to_string())
+    }
+
+    /// Best-effort ticker of the contract for displaying in wallets, which
+    /// works with any interface.
+    ///
+    /// The ticker is taken from the global state named `Nominal`, if it has the
+    /// `RGBContract.Nominal` type; otherwise from the global state `Ticker` of
+    /// `RGBContract.Ticker` type.
+    pub fn display_ticker(&self) -> Option<String> {
+        if let Some(nominal) = self.display_nominal() {
+            return Some(nominal.ticker().to_string());
+        }
+        self.standard_global::<Ticker>("Ticker", "RGBContract.Ticker")
+            .map(|ticker| ticker.to_string())
+    }
+
+    /// Best-effort precision of the contract amounts for displaying in
+    /// wallets, which works with any interface.
+    ///
+    /// The precision is taken from the global state named `Nominal`, if it has
+    /// the `RGBContract.Nominal` type; otherwise from the global state
+    /// `Precision` of `RGBContract.Precision` type.
+    pub fn display_precision(&self) -> Option<Precision> {
+        if let Some(nominal) = self.display_nominal() {
+            return Some(nominal.precision());
+        }
+        self.standard_global("Precision", "RGBContract.Precision")
+    }
+
+    // TODO: Add rights and structured data APIs
     pub fn outpoint(
         &self,
         _outpoint: Outpoint,
@@ -126,3 +286,169 @@ impl ContractIface {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use amplify::confinement::Confined;
+    use strict_encoding::StrictSerialize;
+
+    use super::*;
+    use crate::containers::builder::test::{logo_builder, outpoint, texts, texts_builder, NoHeights};
+    use crate::containers::Contract;
+    use crate::fixtures::{self, GS_NOMINAL};
+    use crate::interface::NamedType;
+    use crate::persistence::attach_id;
+
+    #[derive(Default)]
+    struct MemStore(BTreeMap<AttachId, Vec<u8>>);
+
+    impl AttachmentStore for MemStore {
+        fn import_verified(&mut self, id: AttachId, data: &[u8]) -> Result<(), AttachmentError> {
+            let actual = attach_id(data);
+            if actual != id {
+                return Err(AttachmentError::Mismatch {
+                    expected: id,
+                    actual,
+                });
+            }
+            self.0.insert(id, data.to_vec());
+            Ok(())
+        }
+
+        fn get(&self, id: AttachId) -> Result<Option<Vec<u8>>, AttachmentError> {
+            Ok(self.0.get(&id).cloned())
+        }
+
+        fn has(&self, id: AttachId) -> Result<bool, AttachmentError> {
+            Ok(self.0.contains_key(&id))
+        }
+
+        fn len(&self) -> Result<usize, AttachmentError> { Ok(self.0.len()) }
+    }
+
+    fn contract_iface(contract: &Contract) -> ContractIface {
+        ContractIface {
+            state: ContractState {
+                schema: contract.schema.clone(),
+                history: contract.build_history(&mut NoHeights).unwrap(),
+            },
+            iface: contract.ifaces.values().next().unwrap().iimpl.clone(),
+        }
+    }
+
+    #[test]
+    fn global_order() {
+        let mut builder = texts_builder(3);
+        for text in texts(3) {
+            builder = builder.add_global_state("ContractText", text).unwrap();
+        }
+        let contract = builder.issue_contract().unwrap();
+        let iface = contract_iface(&contract);
+        let schema = &contract.schema;
+        let sem_id = schema
+            .global_types
+            .get(&iface.iface.global_type(&tn!("ContractText")).unwrap())
+            .unwrap()
+            .sem_id;
+        let expected = texts(3)
+            .into_iter()
+            .map(|text| {
+                let data = text.to_strict_serialized::<{ u16::MAX as usize }>().unwrap();
+                schema.type_system.reify(sem_id, data.as_ref()).unwrap().unbox()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(iface.global("ContractText").unwrap().into_inner(), expected);
+    }
+
+    #[test]
+    fn attachment_media() {
+        let png = b"\x89PNG\r\n\x1a\nlogo";
+        let pdf = b"%PDF-1.7 logo";
+        let image = MediaType::from("image/*");
+        let contract = logo_builder()
+            .add_attachment("Logo", outpoint(1), attach_id(png), MediaType::from(MediaType::PNG))
+            .unwrap()
+            .add_attachment("Logo", outpoint(2), attach_id(pdf), image.clone())
+            .unwrap()
+            .add_attachment("Logo", outpoint(3), attach_id(b"absent"), image.clone())
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        let mut store = MemStore::default();
+        store.put(png).unwrap();
+        store.put(pdf).unwrap();
+
+        let iface = contract_iface(&contract);
+        let mut files = iface.attachment("Logo", |_| true, &store).unwrap();
+        files.sort_by_key(|file| file.owner);
+        assert_eq!(files.len(), 3);
+
+        assert_eq!(files[0].id, attach_id(png));
+        assert_eq!(files[0].media_type, Some(MediaType::from(MediaType::PNG)));
+        assert!(files[0].available);
+        assert_eq!(files[0].media_mismatch(), None);
+
+        assert_eq!(files[1].media_type, Some(image.clone()));
+        assert!(files[1].available);
+        assert_eq!(files[1].media_mismatch(), Some(&MediaType::from(MediaType::PDF)));
+
+        assert_eq!(files[2].media_type, Some(image));
+        assert!(!files[2].available);
+        assert_eq!(files[2].media_mismatch(), None);
+
+        let owned = iface
+            .attachment("Logo", |owner| *owner == outpoint(2), &store)
+            .unwrap();
+        assert_eq!(owned, vec![files[1].clone()]);
+        assert!(matches!(
+            iface.attachment("Icon", |_| true, &store),
+            Err(AttachmentQueryError::Contract(ContractError::TypeNameUnknown(_)))
+        ));
+    }
+
+    /// Binds the global state of the contract nominal to the given name, or
+    /// removes the binding if no name is provided.
+    fn bind_nominal(iface: &mut ContractIface, name: Option<&'static str>) {
+        let global_state = iface
+            .iface
+            .global_state
+            .iter()
+            .filter(|nt| nt.id != GS_NOMINAL)
+            .cloned()
+            .chain(name.map(|name| NamedType::with(GS_NOMINAL, tn!(name))));
+        iface.iface.global_state = Confined::try_from_iter(global_state).unwrap();
+    }
+
+    #[test]
+    fn display_nominal() {
+        let contract = fixtures::issue(&[(outpoint(0), 10)]);
+        let iface = contract_iface(&contract);
+        assert_eq!(iface.display_name().as_deref(), Some("Test asset"));
+        assert_eq!(iface.display_ticker().as_deref(), Some("TEST"));
+        assert_eq!(iface.display_precision(), Some(Precision::CentiMicro));
+    }
+
+    #[test]
+    fn display_absent() {
+        let contract = fixtures::issue(&[(outpoint(0), 10)]);
+        let mut iface = contract_iface(&contract);
+        bind_nominal(&mut iface, None);
+        assert_eq!(iface.display_name(), None);
+        assert_eq!(iface.display_ticker(), None);
+        assert_eq!(iface.display_precision(), None);
+    }
+
+    #[test]
+    fn display_mistyped() {
+        let contract = fixtures::issue(&[(outpoint(0), 10)]);
+        let mut iface = contract_iface(&contract);
+        for name in ["Name", "Ticker", "Precision"] {
+            bind_nominal(&mut iface, Some(name));
+            assert_eq!(iface.display_name(), None);
+            assert_eq!(iface.display_ticker(), None);
+            assert_eq!(iface.display_precision(), None);
+        }
+    }
+}
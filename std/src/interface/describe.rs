@@ -0,0 +1,422 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Human-readable description of contract operations in terms of the contract
+//! interface.
+
+use std::fmt::{self, Display, Formatter};
+
+use amplify::confinement::TinyOrdSet;
+use amplify::hex::ToHex;
+use rgb::{
+    AssignmentsRef, GlobalState, GlobalStateType, GraphSeal, OpId, OpRef, Operation, Opout,
+    SchemaTypeIndex, SecretSeal, StateCommitment, StateData, StateSchema, Transition,
+};
+use strict_encoding::{StrictEncode, StrictWriter, TypeName};
+use strict_types::typify::TypedVal;
+use strict_types::StrictVal;
+
+use crate::containers::{assign_views, AssignView};
+use crate::interface::{ContractError, ContractIface, NamedType, TypedState};
+use crate::resolvers::OperationProvider;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display)]
+#[display(lowercase)]
+pub enum OperationKind {
+    Genesis,
+    Transition,
+    Extension,
+}
+
+/// Seal of an operation output, which may be known only by its commitment.
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[display(inner)]
+pub enum SealDescription {
+    Revealed(GraphSeal),
+    Concealed(SecretSeal),
+}
+
+/// State of an operation output. Concealed state is represented by the hex
+/// encoding of its commitment.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum StateDescription {
+    Revealed(TypedState),
+    Concealed(String),
+}
+
+impl Display for StateDescription {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StateDescription::Revealed(TypedState::Void) => f.write_str("void"),
+            StateDescription::Revealed(TypedState::Amount(amount)) => Display::fmt(amount, f),
+            StateDescription::Revealed(TypedState::Data(data)) => Display::fmt(data, f),
+            StateDescription::Revealed(TypedState::Attachment(attach)) => {
+                write!(f, "attachment {} ({})", attach.id, attach.media_type)
+            }
+            StateDescription::Concealed(commitment) => write!(f, "concealed {commitment}"),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct GlobalDescription {
+    pub ty: GlobalStateType,
+    /// Name of the global state in the interface; `None` if the state is not
+    /// a part of the interface.
+    pub name: Option<TypeName>,
+    pub value: StrictVal,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct InputDescription {
+    pub opout: Opout,
+    pub name: Option<TypeName>,
+    /// Seal of the spent output; `None` if the operation which has created
+    /// the output is not known to the operation provider.
+    pub seal: Option<SealDescription>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct OutputDescription {
+    pub opout: Opout,
+    pub name: Option<TypeName>,
+    pub seal: SealDescription,
+    pub state: StateDescription,
+}
+
+/// Description of a contract operation in the vocabulary of the contract
+/// interface, which is constructed by [`ContractIface::describe_operation`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct OperationDescription {
+    pub opid: OpId,
+    pub kind: OperationKind,
+    /// Name of the operation in the interface; `None` for the genesis and for
+    /// the operations not covered by the interface.
+    pub name: Option<TypeName>,
+    pub globals: Vec<GlobalDescription>,
+    pub inputs: Vec<InputDescription>,
+    pub outputs: Vec<OutputDescription>,
+}
+
+struct NameDisplay<'a, T: Display>(&'a Option<TypeName>, T);
+
+impl<T: Display> Display for NameDisplay<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(name) => Display::fmt(name, f),
+            None => write!(f, "#{}", self.1),
+        }
+    }
+}
+
+impl Display for OperationDescription {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => writeln!(f, "{name} {} {}", self.kind, self.opid)?,
+            None => writeln!(f, "{} {}", self.kind, self.opid)?,
+        }
+        if !self.globals.is_empty() {
+            writeln!(f, "  global state:")?;
+        }
+        for global in &self.globals {
+            writeln!(f, "    {}: {}", NameDisplay(&global.name, global.ty), global.value)?;
+        }
+        if !self.inputs.is_empty() {
+            writeln!(f, "  inputs:")?;
+        }
+        for input in &self.inputs {
+            let opout = input.opout;
+            let name = NameDisplay(&input.name, opout.ty);
+            write!(f, "    {name} {}/{}/{}", opout.op, opout.ty, opout.no)?;
+            match &input.seal {
+                Some(seal) => writeln!(f, " from {seal}")?,
+                None => writeln!(f)?,
+            }
+        }
+        if !self.outputs.is_empty() {
+            writeln!(f, "  outputs:")?;
+        }
+        for output in &self.outputs {
+            let name = NameDisplay(&output.name, output.opout.ty);
+            writeln!(f, "    #{} {name}: {} to {}", output.opout.no, output.state, output.seal)?;
+        }
+        Ok(())
+    }
+}
+
+impl ContractIface {
+    /// Describes the operation with the given id, which is taken from the
+    /// operation provider, using names from the interface implementation and
+    /// decoding state values with the contract type system.
+    ///
+    /// Errors with [`ContractError::OperationUnknown`] if the provider doesn't
+    /// know the operation.
+    pub fn describe_operation(
+        &self,
+        opid: OpId,
+        provider: &impl OperationProvider,
+    ) -> Result<OperationDescription, ContractError> {
+        let op = provider
+            .operation(opid)
+            .ok_or(ContractError::OperationUnknown(opid))?;
+        let (kind, name, globals, inputs) = match op {
+            OpRef::Genesis(genesis) => (OperationKind::Genesis, None, &genesis.globals, vec![]),
+            OpRef::Transition(transition) => (
+                OperationKind::Transition,
+                type_name(&self.iface.transitions, transition.transition_type),
+                &transition.globals,
+                self.describe_inputs(transition, provider),
+            ),
+            OpRef::Extension(extension) => (
+                OperationKind::Extension,
+                type_name(&self.iface.extensions, extension.extension_type),
+                &extension.globals,
+                vec![],
+            ),
+        };
+
+        Ok(OperationDescription {
+            opid,
+            kind,
+            name,
+            globals: self.describe_globals(globals)?,
+            inputs,
+            outputs: self.describe_outputs(opid, op.assignments())?,
+        })
+    }
+
+    fn describe_inputs(
+        &self,
+        transition: &Transition,
+        provider: &impl OperationProvider,
+    ) -> Vec<InputDescription> {
+        let mut inputs = vec![];
+        for (prev_id, prev) in transition.inputs.iter() {
+            let prev_views = provider
+                .operation(*prev_id)
+                .map(|prev_op| assign_views(prev_op.assignments()));
+            for (ty, nos) in prev.iter() {
+                for no in nos.iter() {
+                    inputs.push(InputDescription {
+                        opout: Opout::new(*prev_id, *ty, *no),
+                        name: type_name(&self.iface.owned_state, *ty),
+                        seal: prev_views.as_ref().and_then(|views| {
+                            views
+                                .iter()
+                                .find(|view| view.ty == *ty && view.no == *no)
+                                .map(describe_seal)
+                        }),
+                    });
+                }
+            }
+        }
+        inputs
+    }
+
+    fn describe_globals(
+        &self,
+        globals: &GlobalState,
+    ) -> Result<Vec<GlobalDescription>, ContractError> {
+        let schema = &self.state.schema;
+        let mut descriptions = vec![];
+        for (ty, values) in globals.iter() {
+            let sem_id = schema
+                .global_types
+                .get(ty)
+                .ok_or(ContractError::SchemaMismatch(*ty))?
+                .sem_id;
+            for value in values.iter() {
+                descriptions.push(GlobalDescription {
+                    ty: *ty,
+                    name: type_name(&self.iface.global_state, *ty),
+                    value: schema
+                        .type_system
+                        .reify(sem_id, value.as_ref())
+                        .map(TypedVal::unbox)?,
+                });
+            }
+        }
+        Ok(descriptions)
+    }
+
+    fn describe_outputs(
+        &self,
+        opid: OpId,
+        assignments: AssignmentsRef,
+    ) -> Result<Vec<OutputDescription>, ContractError> {
+        let schema = &self.state.schema;
+        assign_views(assignments)
+            .into_iter()
+            .map(|view| {
+                let state = match &view.state {
+                    Some(StateData::Void) => StateDescription::Revealed(TypedState::Void),
+                    Some(StateData::Fungible(state)) => {
+                        StateDescription::Revealed(TypedState::Amount(state.value.as_u64()))
+                    }
+                    Some(StateData::Structured(state)) => {
+                        let Some(StateSchema::Structured(sem_id)) =
+                            schema.owned_types.get(&view.ty)
+                        else {
+                            return Err(ContractError::SchemaMismatch(view.ty));
+                        };
+                        let value = schema.type_system.reify(*sem_id, state.as_ref())?;
+                        StateDescription::Revealed(TypedState::Data(value.unbox()))
+                    }
+                    Some(StateData::Attachment(state)) => {
+                        StateDescription::Revealed(TypedState::Attachment(state.clone()))
+                    }
+                    None => StateDescription::Concealed(commitment_hex(view.commitment)),
+                };
+                Ok(OutputDescription {
+                    opout: Opout::new(opid, view.ty, view.no),
+                    name: type_name(&self.iface.owned_state, view.ty),
+                    seal: describe_seal(&view),
+                    state,
+                })
+            })
+            .collect()
+    }
+}
+
+fn type_name<T: SchemaTypeIndex>(types: &TinyOrdSet<NamedType<T>>, id: T) -> Option<TypeName> {
+    types
+        .iter()
+        .find(|nt| nt.id == id)
+        .map(|nt| nt.name.clone())
+}
+
+fn commitment_hex(commitment: StateCommitment) -> String {
+    let mut data = vec![];
+    let writer = StrictWriter::with(usize::MAX, &mut data);
+    match commitment {
+        StateCommitment::Void => Ok(writer),
+        StateCommitment::Fungible(commitment) => commitment.strict_encode(writer),
+        StateCommitment::Structured(commitment) => commitment.strict_encode(writer),
+        StateCommitment::Attachment(commitment) => commitment.strict_encode(writer),
+    }
+    .expect("in-memory encoding doesn't fail");
+    data.to_hex()
+}
+
+fn describe_seal(view: &AssignView<GraphSeal>) -> SealDescription {
+    match view.seal {
+        Some(seal) => SealDescription::Revealed(seal),
+        None => SealDescription::Concealed(view.secret_seal),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+    use rgb::{ContractState, Genesis, Operation};
+
+    use super::*;
+    use crate::containers::builder::test::NoHeights;
+    use crate::fixtures::{self, TwoHopTransfer, GS_NOMINAL, OS_ASSETS, TRANSFER_AMOUNT};
+
+    struct Operations<'a>(&'a Genesis, &'a [Transition]);
+
+    impl OperationProvider for Operations<'_> {
+        fn operation(&self, opid: OpId) -> Option<OpRef> {
+            if opid == self.0.id() {
+                return Some(OpRef::Genesis(self.0));
+            }
+            self.1
+                .iter()
+                .find(|transition| transition.id() == opid)
+                .map(OpRef::from)
+        }
+    }
+
+    #[test]
+    fn describe_transfer() {
+        let TwoHopTransfer {
+            contract,
+            first,
+            second,
+        } = fixtures::two_hop_transfer();
+        let transitions = [first.clone(), second.clone()];
+        let provider = Operations(&contract.genesis, &transitions);
+        let iface = ContractIface {
+            state: ContractState {
+                schema: contract.schema.clone(),
+                history: contract.build_history(&mut NoHeights).unwrap(),
+            },
+            iface: fixtures::iimpl(),
+        };
+
+        let genesis = iface
+            .describe_operation(contract.genesis.id(), &provider)
+            .unwrap();
+        assert_eq!(genesis.kind, OperationKind::Genesis);
+        assert_eq!(genesis.globals.len(), 2);
+        assert_eq!(genesis.globals[0].name, Some(tn!("Nominal")));
+        assert!(genesis.inputs.is_empty());
+        assert_eq!(
+            genesis.outputs[0].state,
+            StateDescription::Revealed(TypedState::Amount(TRANSFER_AMOUNT))
+        );
+
+        let prev = iface.describe_operation(first.id(), &provider).unwrap();
+        let description = iface.describe_operation(second.id(), &provider).unwrap();
+        assert_eq!(description.kind, OperationKind::Transition);
+        assert_eq!(description.name, Some(tn!("Transfer")));
+        assert_eq!(description.inputs, vec![InputDescription {
+            opout: Opout::new(first.id(), OS_ASSETS, 0),
+            name: Some(tn!("Assets")),
+            seal: Some(prev.outputs[0].seal.clone()),
+        }]);
+        assert_eq!(description.outputs.len(), 1);
+        assert_eq!(description.outputs[0].name, Some(tn!("Assets")));
+        assert!(description.to_string().starts_with("Transfer transition"));
+
+        let unknown: OpId = strict_dumb!();
+        assert_eq!(
+            iface.describe_operation(unknown, &provider),
+            Err(ContractError::OperationUnknown(unknown))
+        );
+    }
+
+    #[test]
+    fn describe_schema_mismatch() {
+        let TwoHopTransfer { contract, .. } = fixtures::two_hop_transfer();
+        let provider = Operations(&contract.genesis, &[]);
+        let mut schema = contract.schema.clone();
+        schema.global_types = Confined::try_from_iter(
+            schema
+                .global_types
+                .into_iter()
+                .filter(|(ty, _)| *ty != GS_NOMINAL),
+        )
+        .unwrap();
+        let iface = ContractIface {
+            state: ContractState {
+                schema,
+                history: contract.build_history(&mut NoHeights).unwrap(),
+            },
+            iface: fixtures::iimpl(),
+        };
+        assert_eq!(
+            iface.describe_operation(contract.genesis.id(), &provider),
+            Err(ContractError::SchemaMismatch(GS_NOMINAL))
+        );
+    }
+}
@@ -0,0 +1,233 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Simplified descriptions of the strict types of the contract state, which
+//! are sufficient to generate user input forms for the state values.
+
+use rgb::SubSchema;
+use strict_encoding::{constants, Primitive, Sizing, TypeName};
+use strict_types::{SemId, Ty, TypeSystem};
+
+use crate::interface::{GlobalIface, Iface};
+
+/// Maximal depth of the nested types which are described; deeper types are
+/// described as [`SemTypeInfo::Opaque`].
+const MAX_DEPTH: usize = 32;
+
+/// ASCII strings are defined as lists of enumerations of the allowed
+/// characters; enumerations of at least this number of variants are treated
+/// as character sets.
+const MIN_CHARSET_LEN: usize = 26;
+
+/// Named field of a structure or a variant of a union.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct FieldInfo {
+    pub name: String,
+    pub info: SemTypeInfo,
+}
+
+/// Simplified description of a strict type.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub enum SemTypeInfo {
+    Unit,
+    Integer {
+        signed: bool,
+        bytes: u8,
+    },
+    Char,
+    /// String of characters, with the length bounds.
+    String {
+        min: u64,
+        max: u64,
+    },
+    /// Byte string, with the length bounds.
+    Bytes {
+        min: u64,
+        max: u64,
+    },
+    Enum(Vec<String>),
+    Optional(Box<SemTypeInfo>),
+    Struct(Vec<FieldInfo>),
+    Tuple(Vec<SemTypeInfo>),
+    Union(Vec<FieldInfo>),
+    /// List or set of items, with the length bounds.
+    List {
+        item: Box<SemTypeInfo>,
+        min: u64,
+        max: u64,
+        unique: bool,
+    },
+    /// Type which can't be described; its values are provided as opaque
+    /// strict-serialized bytes.
+    Opaque,
+}
+
+impl SemTypeInfo {
+    /// Describes the type with the given id from the type system. Types which
+    /// are absent in the type system, or which use type constructs not
+    /// supported by the description, are described as [`Self::Opaque`].
+    pub fn with(type_system: &TypeSystem, sem_id: SemId) -> Self {
+        Self::describe(type_system, sem_id, 0)
+    }
+
+    fn describe(type_system: &TypeSystem, sem_id: SemId, depth: usize) -> Self {
+        if depth > MAX_DEPTH {
+            return SemTypeInfo::Opaque;
+        }
+        let Some(ty) = type_system.get(&sem_id).map(|info| &info.ty) else {
+            return SemTypeInfo::Opaque;
+        };
+        let nested = |sem_id: &SemId| Self::describe(type_system, *sem_id, depth + 1);
+        match ty {
+            Ty::Primitive(primitive) => primitive_info(*primitive),
+            Ty::UnicodeChar => SemTypeInfo::Char,
+            Ty::Enum(variants) => {
+                SemTypeInfo::Enum(variants.iter().map(|variant| variant.name.to_string()).collect())
+            }
+            Ty::Union(variants) => {
+                let names = variants
+                    .iter()
+                    .map(|(variant, _)| variant.name.to_string())
+                    .collect::<Vec<_>>();
+                if names == ["none", "some"] {
+                    let some = variants.values().last().expect("two variants");
+                    return SemTypeInfo::Optional(Box::new(nested(some)));
+                }
+                SemTypeInfo::Union(
+                    variants
+                        .iter()
+                        .map(|(variant, sem_id)| FieldInfo {
+                            name: variant.name.to_string(),
+                            info: nested(sem_id),
+                        })
+                        .collect(),
+                )
+            }
+            Ty::Struct(fields) => SemTypeInfo::Struct(
+                fields
+                    .iter()
+                    .map(|field| FieldInfo {
+                        name: field.name.to_string(),
+                        info: nested(&field.ty),
+                    })
+                    .collect(),
+            ),
+            Ty::Tuple(fields) => SemTypeInfo::Tuple(fields.iter().map(nested).collect()),
+            Ty::Array(item, len) => list_info(nested(item), Sizing::fixed(*len as u64), false),
+            Ty::List(item, sizing) => list_info(nested(item), *sizing, false),
+            Ty::Set(item, sizing) => list_info(nested(item), *sizing, true),
+            Ty::Map(..) => SemTypeInfo::Opaque,
+        }
+    }
+}
+
+fn primitive_info(primitive: Primitive) -> SemTypeInfo {
+    let (signed, bytes) = match primitive {
+        constants::UNIT => return SemTypeInfo::Unit,
+        constants::U8 => (false, 1),
+        constants::U16 => (false, 2),
+        constants::U24 => (false, 3),
+        constants::U32 => (false, 4),
+        constants::U64 => (false, 8),
+        constants::U128 => (false, 16),
+        constants::U256 => (false, 32),
+        constants::I8 => (true, 1),
+        constants::I16 => (true, 2),
+        constants::I32 => (true, 4),
+        constants::I64 => (true, 8),
+        constants::I128 => (true, 16),
+        constants::I256 => (true, 32),
+        _ => return SemTypeInfo::Opaque,
+    };
+    SemTypeInfo::Integer { signed, bytes }
+}
+
+fn list_info(item: SemTypeInfo, sizing: Sizing, unique: bool) -> SemTypeInfo {
+    let Sizing { min, max } = sizing;
+    match item {
+        SemTypeInfo::Char if !unique => SemTypeInfo::String { min, max },
+        SemTypeInfo::Enum(ref chars) if !unique && chars.len() >= MIN_CHARSET_LEN => {
+            SemTypeInfo::String { min, max }
+        }
+        SemTypeInfo::Integer {
+            signed: false,
+            bytes: 1,
+        } if !unique => SemTypeInfo::Bytes { min, max },
+        item => SemTypeInfo::List {
+            item: Box::new(item),
+            min,
+            max,
+            unique,
+        },
+    }
+}
+
+impl Iface {
+    /// Describes type of the global state defined by the interface, resolving
+    /// it in the type system of the schema. Returns `None` if the interface
+    /// doesn't define the state; global state which type is not restricted
+    /// by the interface is described as [`SemTypeInfo::Opaque`].
+    pub fn global_type(&self, name: &TypeName, schema: &SubSchema) -> Option<SemTypeInfo> {
+        match self.global_state.get(name)?.info {
+            GlobalIface::Any => Some(SemTypeInfo::Opaque),
+            GlobalIface::Typed(sem_id) => Some(SemTypeInfo::with(&schema.type_system, sem_id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::containers::{BuilderError, ContractBuilder};
+    use crate::fixtures::{self, iface, schema};
+
+    #[test]
+    fn nominal_type() {
+        let builder = ContractBuilder::with(iface(), schema(), fixtures::iimpl()).unwrap();
+        let SemTypeInfo::Struct(fields) = builder.global_type("Nominal").unwrap() else {
+            panic!("nominal must be a structure");
+        };
+        let names = fields.iter().map(|field| field.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["ticker", "name", "details", "precision"]);
+        assert!(matches!(fields[2].info, SemTypeInfo::Optional(_)));
+        assert!(matches!(fields[3].info, SemTypeInfo::Enum(_)));
+
+        assert_eq!(
+            iface().global_type(&tn!("Nominal"), &schema()),
+            Some(SemTypeInfo::Struct(fields))
+        );
+        assert_eq!(iface().global_type(&tn!("Unknown"), &schema()), None);
+        assert_eq!(
+            builder.global_type("Unknown").unwrap_err(),
+            BuilderError::TypeNotFound(tn!("Unknown"))
+        );
+    }
+}
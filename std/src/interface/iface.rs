@@ -137,6 +137,40 @@ pub enum OwnedIface {
     Data(SemId),
 }
 
+/// Kind of the owned state, defining how it can be moved by the state
+/// transitions.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub enum OwnedKind {
+    /// Fungible amount, which can be split and re-assigned in parts.
+    #[display("spendable")]
+    Spendable,
+    /// Right which is exercised by closing its seal and can't be re-assigned.
+    #[display("one-time")]
+    OneTime,
+    /// Data or attachment, which can be re-assigned only as a whole.
+    #[display("data")]
+    Data,
+    /// State of any kind, which is defined by the schema.
+    #[display("any")]
+    Any,
+}
+
+impl OwnedIface {
+    pub fn kind(&self) -> OwnedKind {
+        match self {
+            OwnedIface::Any => OwnedKind::Any,
+            OwnedIface::Rights => OwnedKind::OneTime,
+            OwnedIface::Amount => OwnedKind::Spendable,
+            OwnedIface::AnyData | OwnedIface::AnyAttach | OwnedIface::Data(_) => OwnedKind::Data,
+        }
+    }
+}
+
 pub type TypeReqMap = TinyOrdMap<TypeName, Occurrences>;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -186,6 +220,27 @@ pub struct TransitionIface {
     pub valencies: TypeReqMap,
 }
 
+/// Supply and metadata features of the contracts implementing an interface,
+/// derived from the operations declared by the interface.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct IfaceFeatures {
+    /// Interface declares `Issue` operation, allowing secondary issuance.
+    pub inflatable: bool,
+    /// Interface declares `Burn` operation, allowing to destroy the state.
+    pub burnable: bool,
+    /// Interface declares `Replace` operation, allowing to burn the state
+    /// and re-issue it with the same operation.
+    pub replaceable: bool,
+    /// Interface declares `Rename` operation, allowing to update the contract
+    /// nominal.
+    pub renameable: bool,
+}
+
 /// Interface definition.
 #[derive(Clone, Eq, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
@@ -232,4 +287,121 @@ impl StrictDeserialize for Iface {}
 impl Iface {
     #[inline]
     pub fn iface_id(&self) -> IfaceId { self.commitment_id() }
+
+    /// Detects features of the contracts implementing the interface from the
+    /// names of the operations it declares. Contracts are also inflatable
+    /// if the interface declares an operation issuing new fungible state,
+    /// see [`Iface::inflates`].
+    pub fn features(&self) -> IfaceFeatures {
+        let declares = |name: &'static str| {
+            let name = TypeName::from(name);
+            self.transitions.contains_key(&name) || self.extensions.contains_key(&name)
+        };
+        IfaceFeatures {
+            inflatable: declares("Issue") ||
+                self.transitions.keys().any(|name| self.inflates(name)) ||
+                self.extensions.keys().any(|name| self.inflates(name)),
+            burnable: declares("Burn"),
+            replaceable: declares("Replace"),
+            renameable: declares("Rename"),
+        }
+    }
+
+    /// Returns kind of the owned state defined by the interface, or `None` if
+    /// the interface doesn't define the state.
+    pub fn owned_kind(&self, name: &TypeName) -> Option<OwnedKind> {
+        self.owned_state.get(name).map(OwnedIface::kind)
+    }
+
+    /// Checks whether the operation with the given name issues new fungible
+    /// state. A transition does so if it assigns state of
+    /// [`OwnedKind::Spendable`] kind which it doesn't spend, exercising
+    /// instead the inflation rights or allowance declared as its inputs; an
+    /// extension does so if it assigns any state of this kind.
+    pub fn inflates(&self, op: &TypeName) -> bool {
+        let spendable = |name: &TypeName| self.owned_kind(name) == Some(OwnedKind::Spendable);
+        if let Some(extension) = self.extensions.get(op) {
+            return extension.assignments.keys().any(spendable);
+        }
+        let Some(transition) = self.transitions.get(op) else {
+            return false;
+        };
+        !transition.inputs.is_empty() &&
+            transition
+                .assignments
+                .keys()
+                .any(|name| spendable(name) && !transition.inputs.contains_key(name))
+    }
+
+    /// Returns the default operation transferring the fungible state of the
+    /// contracts implementing the interface, together with the name of the
+    /// transferred state. By convention of the interface standards this is
+    /// the transition named `Transfer`, which assigns state of
+    /// [`OwnedKind::Spendable`] kind.
+    pub fn default_transfer(&self) -> Option<(&TypeName, &TypeName)> {
+        let transfer: TypeName = tn!("Transfer");
+        let (op_name, transition) = self.transitions.get_key_value(&transfer)?;
+        let state_name = transition
+            .assignments
+            .keys()
+            .find(|name| self.owned_kind(name) == Some(OwnedKind::Spendable))?;
+        Some((op_name, state_name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::interface::rgb20;
+
+    #[test]
+    fn owned_kind() {
+        let mut iface = rgb20();
+        iface.owned_state = tiny_bmap! {
+            tn!("Assets") => OwnedIface::Amount,
+            tn!("Burn") => OwnedIface::Rights,
+            tn!("Logo") => OwnedIface::AnyAttach,
+            tn!("Other") => OwnedIface::Any,
+        };
+        assert_eq!(iface.owned_kind(&tn!("Assets")), Some(OwnedKind::Spendable));
+        assert_eq!(iface.owned_kind(&tn!("Burn")), Some(OwnedKind::OneTime));
+        assert_eq!(iface.owned_kind(&tn!("Logo")), Some(OwnedKind::Data));
+        assert_eq!(iface.owned_kind(&tn!("Other")), Some(OwnedKind::Any));
+        assert_eq!(iface.owned_kind(&tn!("Unknown")), None);
+        assert_eq!(OwnedIface::Rights.kind().to_string(), "one-time");
+    }
+
+    #[test]
+    fn inflates() {
+        let mut iface = rgb20();
+        assert!(!iface.inflates(&tn!("Transfer")));
+        assert!(!iface.inflates(&tn!("Unknown")));
+
+        let transfer = iface.transitions.get(&TypeName::from("Transfer")).unwrap().clone();
+        iface.owned_state.insert(tn!("Allowance"), OwnedIface::Amount).unwrap();
+        let issue = TransitionIface {
+            inputs: tiny_bmap! { tn!("Allowance") => Occurrences::Once },
+            ..transfer.clone()
+        };
+        iface.transitions.insert(tn!("Mint"), issue).unwrap();
+        iface.transitions.insert(tn!("Issue"), transfer).unwrap();
+        assert!(iface.inflates(&tn!("Mint")));
+        assert!(!iface.inflates(&tn!("Issue")));
+    }
+
+    #[test]
+    fn features() {
+        assert_eq!(rgb20().features(), IfaceFeatures::default());
+
+        let mut iface = rgb20();
+        let transfer = iface.transitions.get(&TypeName::from("Transfer")).unwrap().clone();
+        iface.transitions.insert(tn!("Burn"), transfer.clone()).unwrap();
+        iface.transitions.insert(tn!("Rename"), transfer).unwrap();
+        assert_eq!(iface.features(), IfaceFeatures {
+            inflatable: false,
+            burnable: true,
+            replaceable: false,
+            renameable: true,
+        });
+    }
 }
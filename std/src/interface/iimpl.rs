@@ -19,22 +19,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
-use amplify::confinement::{TinyOrdMap, TinyOrdSet};
+use amplify::confinement::{Confined, TinyOrdMap, TinyOrdSet};
 use amplify::{Bytes32, RawArray};
 use baid58::{Baid58ParseError, FromBaid58, ToBaid58};
+use bp::seals::txout::CloseMethod;
+use bp::Chain;
 use commit_verify::{CommitStrategy, CommitmentId};
 use rgb::{
-    AssignmentsType, ExtensionType, GlobalStateType, SchemaId, SchemaTypeIndex, SubSchema,
-    TransitionType, ValencyType,
+    data, AssignmentsType, ExtensionType, GlobalStateType, SchemaId, SchemaTypeIndex, StateSchema,
+    SubSchema, TransitionType, ValencyType,
 };
 use strict_types::encoding::{
     StrictDecode, StrictDeserialize, StrictEncode, StrictSerialize, StrictType, TypeName,
 };
+use strict_types::ast::EnumVariants;
+use strict_types::{KeyTy, SemId, Ty, TypeSystem};
 
 use crate::interface::iface::IfaceId;
-use crate::interface::Iface;
+use crate::interface::{GlobalIface, Iface, OwnedIface};
+use crate::stl::MediaType;
 use crate::LIB_NAME_RGB_STD;
 
 /// Interface identifier.
@@ -122,6 +129,9 @@ impl SchemaIfaces {
     serde(crate = "serde_crate", rename_all = "camelCase")
 )]
 pub struct IfaceImpl {
+    /// Version of the implementation, increased by its vendor with each new
+    /// release of the implementation for the same interface.
+    pub version: u16,
     pub schema_id: SchemaId,
     pub iface_id: IfaceId,
     pub global_state: TinyOrdSet<NamedType<GlobalStateType>>,
@@ -129,6 +139,15 @@ pub struct IfaceImpl {
     pub valencies: TinyOrdSet<NamedType<ValencyType>>,
     pub transitions: TinyOrdSet<NamedType<TransitionType>>,
     pub extensions: TinyOrdSet<NamedType<ExtensionType>>,
+    /// Chains on which contracts under the schema may be issued with this
+    /// interface. Empty set means that all chains are allowed.
+    pub chains: TinyOrdSet<Chain>,
+    /// Seal close methods which may be used by contracts under the schema with
+    /// this interface. Empty set means that all methods are allowed.
+    pub close_methods: TinyOrdSet<CloseMethod>,
+    /// Media types allowed for the attachments of each attachment state type.
+    /// State types which are absent from the map allow any media type.
+    pub media_types: TinyOrdMap<AssignmentsType, TinyOrdSet<MediaType>>,
 }
 
 impl CommitStrategy for IfaceImpl {
@@ -154,15 +173,329 @@ impl IfaceImpl {
             .map(|nt| nt.id)
     }
 
+    /// Decodes the first item of the global state with the given interface
+    /// name, which `state` provides for the implementing global state type.
+    /// Any failures are reported as `None`.
+    pub(crate) fn global_first<'a, T: StrictDeserialize>(
+        &self,
+        name: &TypeName,
+        state: impl FnOnce(GlobalStateType) -> Option<&'a data::Revealed>,
+    ) -> Option<T> {
+        let data = state(self.global_type(name)?)?;
+        let data = Confined::try_from(data.as_ref().to_vec()).ok()?;
+        T::from_strict_serialized::<{ u16::MAX as usize }>(data).ok()
+    }
+
     pub fn assignments_type(&self, name: &TypeName) -> Option<AssignmentsType> {
         self.owned_state
             .iter()
             .find(|nt| &nt.name == name)
             .map(|nt| nt.id)
     }
+
+    pub fn transition_type(&self, name: &TypeName) -> Option<TransitionType> {
+        self.transitions
+            .iter()
+            .find(|nt| &nt.name == name)
+            .map(|nt| nt.id)
+    }
+
+    pub fn extension_type(&self, name: &TypeName) -> Option<ExtensionType> {
+        self.extensions
+            .iter()
+            .find(|nt| &nt.name == name)
+            .map(|nt| nt.id)
+    }
+
+    pub fn valency_type(&self, name: &TypeName) -> Option<ValencyType> {
+        self.valencies
+            .iter()
+            .find(|nt| &nt.name == name)
+            .map(|nt| nt.id)
+    }
+
+    /// Checks whether this implementation has a newer version than the other
+    /// one. Returns `None` if the implementations are made for different
+    /// interfaces and can't be compared.
+    pub fn is_newer_than(&self, other: &IfaceImpl) -> Option<bool> {
+        if self.iface_id != other.iface_id {
+            return None;
+        }
+        Some(self.version > other.version)
+    }
+
+    /// Checks whether contracts using this implementation may be issued on the
+    /// given chain.
+    pub fn is_chain_allowed(&self, chain: Chain) -> bool {
+        self.chains.is_empty() || self.chains.contains(&chain)
+    }
+
+    /// Checks whether seals of contracts using this implementation may be
+    /// closed with the given method.
+    pub fn is_method_allowed(&self, method: CloseMethod) -> bool {
+        self.close_methods.is_empty() || self.close_methods.contains(&method)
+    }
+
+    /// Checks whether attachments of the given state type may have the given
+    /// media type.
+    pub fn is_media_allowed(&self, ty: AssignmentsType, media_type: &MediaType) -> bool {
+        match self.media_types.get(&ty) {
+            None => true,
+            Some(allowed) => allowed.iter().any(|mt| mt.covers(media_type)),
+        }
+    }
+
+    /// Seal close method used when it is not provided explicitly: the
+    /// [`SealSpec::DEFAULT_METHOD`] if it is allowed, or the first of the
+    /// allowed methods otherwise.
+    ///
+    /// [`SealSpec::DEFAULT_METHOD`]: crate::containers::SealSpec::DEFAULT_METHOD
+    pub fn preferred_method(&self) -> CloseMethod {
+        let default = crate::containers::SealSpec::DEFAULT_METHOD;
+        if self.is_method_allowed(default) {
+            return default;
+        }
+        self.close_methods
+            .iter()
+            .next()
+            .copied()
+            .unwrap_or(default)
+    }
+}
+
+/// Errors of interface implementation not matching the interface or the
+/// schema it binds to.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ImplError {
+    /// interface implementation maps type {0} which is not a part of the
+    /// interface.
+    IfaceTypeUnknown(TypeName),
+
+    /// interface implementation maps type {0} to a schema type which is absent
+    /// from the schema.
+    SchemaTypeUnknown(TypeName),
+
+    /// interface requires global state {0}, which is not implemented.
+    RequiredAbsent(TypeName),
+
+    /// interface defines state {name} with strict type {iface_sem}, which is
+    /// not compatible with the strict type {schema_sem} of the schema state
+    /// it is mapped to.
+    SemanticMismatch {
+        name: TypeName,
+        iface_sem: SemId,
+        schema_sem: SemId,
+    },
+
+    /// interface expects state {0} of a kind different from the kind of the
+    /// schema state it is mapped to.
+    StateKindMismatch(TypeName),
+
+    /// schema allows inflation of the fungible state with operation of type
+    /// {0}, while the interface doesn't declare the contracts inflatable.
+    UndeclaredInflation(u16),
+}
+
+/// All errors found by [`IfaceImpl::validate_interface_semantics`], one for
+/// each offending member of the interface implementation.
+#[derive(Clone, Eq, PartialEq, Debug, Error)]
+pub struct ImplErrors(pub Vec<ImplError>);
+
+impl Display for ImplErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (no, err) in self.0.iter().enumerate() {
+            if no > 0 {
+                f.write_str("\n")?;
+            }
+            Display::fmt(err, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl IfaceImpl {
+    /// Validates that the semantic of each state type defined by the interface
+    /// matches the state definition in the schema, to which this
+    /// implementation maps the interface.
+    ///
+    /// The validation doesn't stop at the first error, reporting all the
+    /// offending members of the implementation.
+    pub fn validate_interface_semantics(
+        &self,
+        iface: &Iface,
+        schema: &SubSchema,
+    ) -> Result<(), ImplErrors> {
+        let mut errors = vec![];
+
+        for (name, req) in &iface.global_state {
+            if req.required && self.global_type(name).is_none() {
+                errors.push(ImplError::RequiredAbsent(name.clone()));
+            }
+        }
+
+        for NamedType { id, name } in &self.global_state {
+            let Some(req) = iface.global_state.get(name) else {
+                errors.push(ImplError::IfaceTypeUnknown(name.clone()));
+                continue;
+            };
+            let Some(global_schema) = schema.global_types.get(id) else {
+                errors.push(ImplError::SchemaTypeUnknown(name.clone()));
+                continue;
+            };
+            if let GlobalIface::Typed(iface_sem) = req.info {
+                errors.extend(
+                    check_sem_compatible(&schema.type_system, name, iface_sem, global_schema.sem_id)
+                        .err(),
+                );
+            }
+        }
+
+        for NamedType { id, name } in &self.owned_state {
+            let Some(owned_iface) = iface.owned_state.get(name) else {
+                errors.push(ImplError::IfaceTypeUnknown(name.clone()));
+                continue;
+            };
+            let Some(state_schema) = schema.owned_types.get(id) else {
+                errors.push(ImplError::SchemaTypeUnknown(name.clone()));
+                continue;
+            };
+            match (owned_iface, state_schema) {
+                (OwnedIface::Any, _) |
+                (OwnedIface::Rights, StateSchema::Declarative) |
+                (OwnedIface::Amount, StateSchema::Fungible(_)) |
+                (OwnedIface::AnyData, StateSchema::Structured(_)) |
+                (OwnedIface::AnyAttach, StateSchema::Attachment) => {}
+                (OwnedIface::Data(iface_sem), StateSchema::Structured(schema_sem)) => errors.extend(
+                    check_sem_compatible(&schema.type_system, name, *iface_sem, *schema_sem).err(),
+                ),
+                _ => errors.push(ImplError::StateKindMismatch(name.clone())),
+            }
+        }
+
+        if !iface.features().inflatable {
+            if let Some(op) = inflation_op(schema) {
+                errors.push(ImplError::UndeclaredInflation(op));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ImplErrors(errors))
+        }
+    }
+}
+
+/// Checks that the state of the schema type can be read as the interface
+/// type: either the types are the same, or both are known to the schema type
+/// system and differ only by their names, having the same encoding.
+fn check_sem_compatible(
+    type_system: &TypeSystem,
+    name: &TypeName,
+    iface_sem: SemId,
+    schema_sem: SemId,
+) -> Result<(), ImplError> {
+    if same_layout(type_system, iface_sem, schema_sem) {
+        return Ok(());
+    }
+    Err(ImplError::SemanticMismatch {
+        name: name.clone(),
+        iface_sem,
+        schema_sem,
+    })
+}
+
+/// Compares the structure of the two types from the type system, ignoring
+/// the names of the fields and variants. Types with the same structure have
+/// the same encoding, though their semantic ids differ.
+fn same_layout(type_system: &TypeSystem, a: SemId, b: SemId) -> bool {
+    fn tags(variants: &EnumVariants) -> Vec<u8> {
+        variants.iter().map(|variant| variant.tag).collect()
+    }
+
+    if a == b {
+        return true;
+    }
+    let (Some(a), Some(b)) = (type_system.get(&a), type_system.get(&b)) else {
+        return false;
+    };
+    let same = |a: &SemId, b: &SemId| same_layout(type_system, *a, *b);
+    match (&a.ty, &b.ty) {
+        (Ty::Primitive(a), Ty::Primitive(b)) => a == b,
+        (Ty::UnicodeChar, Ty::UnicodeChar) => true,
+        (Ty::Enum(a), Ty::Enum(b)) => tags(a) == tags(b),
+        (Ty::Union(a), Ty::Union(b)) => {
+            a.len() == b.len() &&
+                a.iter()
+                    .zip(b.iter())
+                    .all(|((va, a), (vb, b))| va.tag == vb.tag && same(a, b))
+        }
+        (Ty::Tuple(a), Ty::Tuple(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same(a, b))
+        }
+        (Ty::Struct(a), Ty::Struct(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same(&a.ty, &b.ty))
+        }
+        (Ty::Array(a, la), Ty::Array(b, lb)) => la == lb && same(a, b),
+        (Ty::List(a, sa), Ty::List(b, sb)) | (Ty::Set(a, sa), Ty::Set(b, sb)) => {
+            sa == sb && same(a, b)
+        }
+        (Ty::Map(ka, a, sa), Ty::Map(kb, b, sb)) => {
+            let same_key = match (ka, kb) {
+                (KeyTy::Enum(ka), KeyTy::Enum(kb)) => tags(ka) == tags(kb),
+                (ka, kb) => ka == kb,
+            };
+            same_key && sa == sb && same(a, b)
+        }
+        _ => false,
+    }
 }
 
-// TODO: Implement validation of implementation against interface requirements
+/// Finds operation type of the schema which creates fungible state without
+/// spending any other fungible state, exercising instead the rights which
+/// originate from the genesis: its valencies or its owned state, or the
+/// rights assigned by operations exercising them, at any depth.
+fn inflation_op(schema: &SubSchema) -> Option<u16> {
+    let fungible = |ty: &AssignmentsType| {
+        matches!(schema.owned_types.get(ty), Some(StateSchema::Fungible(_)))
+    };
+
+    let genesis = &schema.genesis;
+    let mut owned = genesis.assignments.keys().copied().collect::<BTreeSet<_>>();
+    let mut valencies = genesis.valencies.iter().copied().collect::<BTreeSet<_>>();
+    loop {
+        let (known_owned, known_valencies) = (owned.len(), valencies.len());
+        for extension in schema.extensions.values() {
+            if extension.redeems.iter().any(|ty| valencies.contains(ty)) {
+                owned.extend(extension.assignments.keys());
+                valencies.extend(extension.valencies.iter());
+            }
+        }
+        for transition in schema.transitions.values() {
+            if transition.inputs.keys().any(|ty| owned.contains(ty)) {
+                owned.extend(transition.assignments.keys());
+                valencies.extend(transition.valencies.iter());
+            }
+        }
+        if (owned.len(), valencies.len()) == (known_owned, known_valencies) {
+            break;
+        }
+    }
+
+    let extension = schema.extensions.iter().find(|(_, extension)| {
+        extension.assignments.keys().any(fungible) &&
+            extension.redeems.iter().any(|ty| valencies.contains(ty))
+    });
+    let transition = schema.transitions.iter().find(|(_, transition)| {
+        transition.assignments.keys().any(fungible) &&
+            !transition.inputs.keys().any(fungible) &&
+            transition.inputs.keys().any(|ty| owned.contains(ty))
+    });
+    extension
+        .map(|(ty, _)| *ty)
+        .or_else(|| transition.map(|(ty, _)| *ty))
+}
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
@@ -182,3 +515,160 @@ impl IfacePair {
 
     pub fn iface_id(&self) -> IfaceId { self.iface.iface_id() }
 }
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+    use rgb::{Occurrences, TransitionSchema};
+    use strict_types::typelib::LibBuilder;
+    use strict_types::typesys::SystemBuilder;
+
+    use super::*;
+    use crate::containers::builder::test::{iimpl, schema};
+    use crate::fixtures::{GS_CONTRACT, GS_NOMINAL, OS_ASSETS};
+    use crate::interface::rgb20;
+
+    #[test]
+    fn newer_impl() {
+        let old = iimpl(&schema(), &rgb20());
+        let new = IfaceImpl {
+            version: 1,
+            ..old.clone()
+        };
+        assert_eq!(new.is_newer_than(&old), Some(true));
+        assert_eq!(old.is_newer_than(&new), Some(false));
+        assert_eq!(old.is_newer_than(&old), Some(false));
+
+        let mut other = new;
+        other.iface_id = IfaceId::from([0xA5; 32]);
+        assert_eq!(other.is_newer_than(&old), None);
+        assert_eq!(old.is_newer_than(&other), None);
+    }
+
+    #[test]
+    fn sem_mismatch() {
+        let schema = schema();
+        let iface = rgb20();
+        let iimpl = iimpl(&schema, &iface);
+        assert_eq!(iimpl.validate_interface_semantics(&iface, &schema), Ok(()));
+
+        let global_state = Confined::try_from_iter(iimpl.global_state.iter().map(|nt| {
+            let id = if nt.id == GS_NOMINAL { GS_CONTRACT } else { GS_NOMINAL };
+            NamedType::with(id, nt.name.clone())
+        }))
+        .unwrap();
+        let misbound = IfaceImpl {
+            global_state,
+            ..iimpl
+        };
+        let mismatch = |name: TypeName, schema_type: GlobalStateType| {
+            let GlobalIface::Typed(iface_sem) = iface.global_state.get(&name).unwrap().info else {
+                panic!("{name} must be typed by the interface");
+            };
+            let schema_sem = schema.global_types.get(&schema_type).unwrap().sem_id;
+            ImplError::SemanticMismatch {
+                name,
+                iface_sem,
+                schema_sem,
+            }
+        };
+        assert_eq!(
+            misbound.validate_interface_semantics(&iface, &schema),
+            Err(ImplErrors(vec![
+                mismatch(tn!("ContractText"), GS_NOMINAL),
+                mismatch(tn!("Nominal"), GS_CONTRACT)
+            ]))
+        );
+    }
+
+    #[test]
+    fn sem_renamed_fields() {
+        const LIB: &str = "RenamedFields";
+
+        #[derive(Clone, Eq, PartialEq, Debug, Default)]
+        #[derive(StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = LIB)]
+        struct Point {
+            x: u8,
+            y: u16,
+        }
+
+        #[derive(Clone, Eq, PartialEq, Debug, Default)]
+        #[derive(StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = LIB)]
+        struct Coord {
+            lat: u8,
+            lon: u16,
+        }
+
+        #[derive(Clone, Eq, PartialEq, Debug, Default)]
+        #[derive(StrictType, StrictEncode, StrictDecode)]
+        #[strict_type(lib = LIB)]
+        struct Swapped {
+            x: u16,
+            y: u8,
+        }
+
+        let lib = LibBuilder::new(libname!(LIB))
+            .process::<Point>()
+            .unwrap()
+            .process::<Coord>()
+            .unwrap()
+            .process::<Swapped>()
+            .unwrap()
+            .compile(none!())
+            .unwrap();
+        let type_system = SystemBuilder::new().import(lib).unwrap().finalize().unwrap();
+        let sem_id = |name: &str| type_system.id_by_name(&format!("{LIB}.{name}")).unwrap();
+        let (point, coord, swapped) = (sem_id("Point"), sem_id("Coord"), sem_id("Swapped"));
+        assert_ne!(point, coord);
+
+        let name = tn!("Point");
+        assert_eq!(check_sem_compatible(&type_system, &name, point, coord), Ok(()));
+        assert_eq!(
+            check_sem_compatible(&type_system, &name, point, swapped),
+            Err(ImplError::SemanticMismatch {
+                name,
+                iface_sem: point,
+                schema_sem: swapped,
+            })
+        );
+    }
+
+    #[test]
+    fn inflation_hops() {
+        const OS_RIGHTS: AssignmentsType = 4001;
+        const OS_RENEWED: AssignmentsType = 4002;
+        const TS_RENEW: TransitionType = 10001;
+        const TS_INFLATE: TransitionType = 10002;
+        let transition = |input: AssignmentsType, output: AssignmentsType| TransitionSchema {
+            metadata: None,
+            globals: none!(),
+            inputs: tiny_bmap! { input => Occurrences::Once },
+            assignments: tiny_bmap! { output => Occurrences::Once },
+            valencies: none!(),
+        };
+
+        let mut schema = schema();
+        schema.owned_types.insert(OS_RIGHTS, StateSchema::Declarative).unwrap();
+        schema.owned_types.insert(OS_RENEWED, StateSchema::Declarative).unwrap();
+        schema
+            .transitions
+            .insert(TS_INFLATE, transition(OS_RENEWED, OS_ASSETS))
+            .unwrap();
+        assert_eq!(inflation_op(&schema), None);
+
+        schema
+            .transitions
+            .insert(TS_RENEW, transition(OS_RIGHTS, OS_RENEWED))
+            .unwrap();
+        assert_eq!(inflation_op(&schema), None);
+
+        schema
+            .genesis
+            .assignments
+            .insert(OS_RIGHTS, Occurrences::Once)
+            .unwrap();
+        assert_eq!(inflation_op(&schema), Some(TS_INFLATE));
+    }
+}
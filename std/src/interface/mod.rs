@@ -26,12 +26,25 @@
 mod iface;
 mod iimpl;
 mod contract;
+mod describe;
+mod form;
+mod path;
 mod rgb20;
+mod spec;
 
-pub use contract::{ContractIface, OwnedState, TypedState};
+pub use contract::{
+    AttachedFile, AttachmentQueryError, ContractError, ContractIface, OwnedState, TypedState,
+};
+pub use describe::{
+    GlobalDescription, InputDescription, OperationDescription, OperationKind, OutputDescription,
+    SealDescription, StateDescription,
+};
+pub use form::{FieldInfo, SemTypeInfo};
 pub use iface::{
-    ExtensionIface, GenesisIface, GlobalIface, Iface, IfaceId, OwnedIface, Req, TransitionIface,
-    TypeReqMap,
+    ExtensionIface, GenesisIface, GlobalIface, Iface, IfaceFeatures, IfaceId, OwnedIface, OwnedKind,
+    Req, TransitionIface, TypeReqMap,
 };
-pub use iimpl::{IfaceImpl, IfacePair, ImplId, NamedType, SchemaIfaces};
-pub use rgb20::rgb20;
+pub use iimpl::{IfaceImpl, IfacePair, ImplError, ImplErrors, ImplId, NamedType, SchemaIfaces};
+pub use path::PathError;
+pub use rgb20::{rgb20, Rgb20};
+pub use spec::{ArgSpec, OperationSpec};
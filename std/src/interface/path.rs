@@ -0,0 +1,340 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Navigation of the serialized contract state with paths, which allows to
+//! read a single field of a nested value without decoding the fields following
+//! it. The fields preceding the requested one are still decoded, since the
+//! serialized data don't encode their length.
+//!
+//! Path is a sequence of segments separated by dots, where each segment is
+//! either a name of a structure field, or an index of a tuple field or a
+//! collection item. Optional values are unwrapped when the path continues
+//! into them. Empty path refers to the value itself.
+
+use std::{io, iter};
+
+use amplify::confinement::Confined;
+use rgb::GlobalStateType;
+use strict_encoding::{StrictDeserialize, TypeName};
+use strict_types::typify::TypedVal;
+use strict_types::{reify, SemId, StrictVal, Ty, TypeSystem};
+
+use crate::interface::{ContractError, ContractIface};
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum PathError {
+    #[from]
+    #[display(inner)]
+    Contract(ContractError),
+
+    /// global state {0} has no values.
+    NoValue(TypeName),
+
+    /// global state type {0} is not defined by the contract schema.
+    UnknownGlobal(GlobalStateType),
+
+    /// type {0} is absent in the type system of the contract schema.
+    UnknownType(SemId),
+
+    /// path segment '{1}' at position {0} doesn't name a field of the
+    /// structure.
+    UnknownField(usize, String),
+
+    /// path segment '{1}' at position {0} is not a valid index of the tuple
+    /// field or the collection item.
+    InvalidIndex(usize, String),
+
+    /// path segment '{1}' at position {0} continues into an absent optional
+    /// value.
+    NoneValue(usize, String),
+
+    /// path segment '{1}' at position {0} continues into a value which has no
+    /// fields or items.
+    NotNavigable(usize, String),
+
+    /// state data don't match their type at the path segment '{1}' at
+    /// position {0}.
+    InvalidData(usize, String),
+
+    /// value at path '{0}' doesn't match the requested type.
+    TypeMismatch(String),
+}
+
+impl ContractIface {
+    /// Reads value at the path within the first value of the global state
+    /// with the given name. See the [module documentation](self) for the
+    /// path syntax.
+    pub fn global_path(
+        &self,
+        name: impl Into<TypeName>,
+        path: &str,
+    ) -> Result<StrictVal, PathError> {
+        self.global_path_val(name.into(), path).map(TypedVal::unbox)
+    }
+
+    /// Reads value at the path like [`Self::global_path`], decoding it into
+    /// the type `T`.
+    pub(crate) fn global_path_typed<T: StrictDeserialize>(
+        &self,
+        name: impl Into<TypeName>,
+        path: &str,
+    ) -> Result<T, PathError> {
+        let (_, data) = self.global_path_data(name.into(), path)?;
+        Confined::try_from(data)
+            .ok()
+            .and_then(|data| T::from_strict_serialized::<{ u16::MAX as usize }>(data).ok())
+            .ok_or_else(|| PathError::TypeMismatch(path.to_owned()))
+    }
+
+    fn global_path_val(&self, name: TypeName, path: &str) -> Result<TypedVal, PathError> {
+        self.global_path_data(name, path).map(|(val, _)| val)
+    }
+
+    /// Reads value at the path, returning it together with its serialized
+    /// data.
+    fn global_path_data(
+        &self,
+        name: TypeName,
+        path: &str,
+    ) -> Result<(TypedVal, Vec<u8>), PathError> {
+        let type_system = &self.state.schema.type_system;
+        let type_id = self
+            .iface
+            .global_type(&name)
+            .ok_or_else(|| ContractError::TypeNameUnknown(name.clone()))?;
+        let type_schema = self
+            .state
+            .schema
+            .global_types
+            .get(&type_id)
+            .ok_or(PathError::UnknownGlobal(type_id))?;
+        let state = unsafe { self.state.global_unchecked(type_id) };
+        let revealed = state.into_iter().next().ok_or(PathError::NoValue(name))?;
+        let (sem_id, data) = navigate(type_system, type_schema.sem_id, revealed.as_ref(), path)?;
+        let (val, len) = load(type_system, sem_id, data).map_err(ContractError::from)?;
+        Ok((val, data[..len].to_vec()))
+    }
+}
+
+/// Decodes value of the type `sem_id` serialized at the beginning of the data,
+/// returning it together with the length of its serialization. Unlike
+/// [`TypeSystem::reify`], the data may continue after the value.
+fn load(
+    type_system: &TypeSystem,
+    sem_id: SemId,
+    data: &[u8],
+) -> Result<(TypedVal, usize), reify::Error> {
+    let mut cursor = io::Cursor::new(data);
+    let val = type_system.load(sem_id, &mut cursor)?;
+    Ok((val, cursor.position() as usize))
+}
+
+/// Resolves the path within the value of the type `sem_id` serialized at the
+/// beginning of the data, returning type of the value at the path and the
+/// data starting with it.
+fn navigate<'data>(
+    type_system: &TypeSystem,
+    sem_id: SemId,
+    data: &'data [u8],
+    path: &str,
+) -> Result<(SemId, &'data [u8]), PathError> {
+    let segments = path.split('.').filter(|_| !path.is_empty());
+    segments
+        .enumerate()
+        .try_fold((sem_id, data), |value, (pos, segment)| step(type_system, value, pos, segment))
+}
+
+fn step<'data>(
+    type_system: &TypeSystem,
+    (mut sem_id, mut data): (SemId, &'data [u8]),
+    pos: usize,
+    segment: &str,
+) -> Result<(SemId, &'data [u8]), PathError> {
+    let fail = |error: fn(usize, String) -> PathError| error(pos, segment.to_owned());
+    let index = || {
+        segment
+            .parse::<usize>()
+            .map_err(|_| fail(PathError::InvalidIndex))
+    };
+    let skip = |types: &mut dyn Iterator<Item = SemId>, data: &'data [u8]| {
+        skip_values(type_system, types, data).ok_or_else(|| fail(PathError::InvalidData))
+    };
+    loop {
+        let ty = type_system
+            .get(&sem_id)
+            .map(|info| &info.ty)
+            .ok_or(PathError::UnknownType(sem_id))?;
+        match ty {
+            Ty::Union(variants) if is_optional(ty) => {
+                let (tag, rest) = data
+                    .split_first()
+                    .ok_or_else(|| fail(PathError::InvalidData))?;
+                if *tag == 0 {
+                    return Err(fail(PathError::NoneValue));
+                }
+                sem_id = *variants.values().last().expect("two variants");
+                data = rest;
+            }
+            Ty::Struct(fields) => {
+                let no = fields
+                    .iter()
+                    .position(|field| field.name.to_string() == segment)
+                    .ok_or_else(|| fail(PathError::UnknownField))?;
+                let field = fields.iter().nth(no).expect("position is found").ty;
+                let data = skip(&mut fields.iter().take(no).map(|field| field.ty), data)?;
+                return Ok((field, data));
+            }
+            Ty::Tuple(fields) => {
+                let no = index()?;
+                let Some(field) = fields.iter().nth(no).copied() else {
+                    return Err(fail(PathError::InvalidIndex));
+                };
+                let data = skip(&mut fields.iter().take(no).copied(), data)?;
+                return Ok((field, data));
+            }
+            Ty::Array(item, len) => {
+                let no = index()?;
+                if no >= *len as usize {
+                    return Err(fail(PathError::InvalidIndex));
+                }
+                let data = skip(&mut iter::repeat(*item).take(no), data)?;
+                return Ok((*item, data));
+            }
+            Ty::List(item, sizing) | Ty::Set(item, sizing) => {
+                let no = index()?;
+                let (len, rest) =
+                    read_len(data, sizing.max).ok_or_else(|| fail(PathError::InvalidData))?;
+                if no as u64 >= len {
+                    return Err(fail(PathError::InvalidIndex));
+                }
+                let data = skip(&mut iter::repeat(*item).take(no), rest)?;
+                return Ok((*item, data));
+            }
+            _ => return Err(fail(PathError::NotNavigable)),
+        }
+    }
+}
+
+/// Detects optional values, which are unions of the `none` and `some`
+/// variants, like in [`crate::interface::SemTypeInfo::Optional`].
+fn is_optional(ty: &Ty<SemId>) -> bool {
+    let Ty::Union(variants) = ty else {
+        return false;
+    };
+    let names = variants
+        .iter()
+        .map(|(variant, _)| variant.name.to_string())
+        .collect::<Vec<_>>();
+    names == ["none", "some"]
+}
+
+/// Reads length prefix of a collection with the given maximal length, which
+/// is encoded with the minimal number of bytes able to hold the maximum.
+fn read_len(data: &[u8], max: u64) -> Option<(u64, &[u8])> {
+    let width = match max {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        0x1_0000..=0xFF_FFFF => 3,
+        0x100_0000..=0xFFFF_FFFF => 4,
+        _ => 8,
+    };
+    let prefix = data.get(..width)?;
+    let mut bytes = [0u8; 8];
+    bytes[..width].copy_from_slice(prefix);
+    Some((u64::from_le_bytes(bytes), &data[width..]))
+}
+
+/// Skips serialized values of the given types, returning the remaining data.
+/// Since the data don't encode the length of the values, each of the skipped
+/// values is fully decoded to find where it ends. Returns `None` if the data
+/// don't match the types.
+fn skip_values<'data>(
+    type_system: &TypeSystem,
+    types: &mut dyn Iterator<Item = SemId>,
+    mut data: &'data [u8],
+) -> Option<&'data [u8]> {
+    for sem_id in types {
+        let (_, len) = load(type_system, sem_id, data).ok()?;
+        data = &data[len..];
+    }
+    Some(data)
+}
+
+#[cfg(test)]
+mod test {
+    use rgb::ContractState;
+
+    use super::*;
+    use crate::containers::builder::test::NoHeights;
+    use crate::fixtures::{self, outpoint, GS_NOMINAL};
+    use crate::interface::Rgb20;
+    use crate::stl::{Nominal, Precision, Ticker};
+
+    #[test]
+    fn global_path() {
+        let contract = fixtures::issue(&[(outpoint(0), 10)]);
+        let iface = ContractIface {
+            state: ContractState {
+                schema: contract.schema.clone(),
+                history: contract.build_history(&mut NoHeights).unwrap(),
+            },
+            iface: fixtures::iimpl(),
+        };
+
+        let nominal = iface.global("Nominal").unwrap().into_inner().remove(0);
+        assert_eq!(iface.global_path("Nominal", "").unwrap(), nominal);
+        let ticker = iface.global_path_typed::<Ticker>("Nominal", "ticker").unwrap();
+        assert_eq!(ticker.to_string(), "TEST");
+        let precision = iface.global_path_typed::<Precision>("Nominal", "precision");
+        assert_eq!(precision.unwrap(), Precision::CentiMicro);
+        let rgb20 = Rgb20::with(iface.clone()).unwrap();
+        assert_eq!(rgb20.ticker(), "TEST");
+        assert_eq!(rgb20.name(), "Test asset");
+        assert_eq!(rgb20.nominal(), Nominal::new("TEST", "Test asset", Precision::CentiMicro));
+
+        assert_eq!(
+            iface.global_path("Nominal", "details.0"),
+            Err(PathError::NoneValue(1, s!("0")))
+        );
+        assert_eq!(
+            iface.global_path("Nominal", "supply"),
+            Err(PathError::UnknownField(0, s!("supply")))
+        );
+        assert_eq!(
+            iface.global_path("Nominal", "precision.0"),
+            Err(PathError::NotNavigable(1, s!("0")))
+        );
+        assert!(matches!(
+            iface.global_path("Unknown", ""),
+            Err(PathError::Contract(ContractError::TypeNameUnknown(_)))
+        ));
+
+        let mut other = iface;
+        let mut global_types = other.state.schema.global_types.into_inner();
+        global_types.remove(&GS_NOMINAL);
+        other.state.schema.global_types = Confined::try_from(global_types).unwrap();
+        assert_eq!(
+            other.global_path("Nominal", "ticker"),
+            Err(PathError::UnknownGlobal(GS_NOMINAL))
+        );
+    }
+}
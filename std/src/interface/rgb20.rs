@@ -19,10 +19,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::ops::Deref;
+
 use rgb::Occurrences;
+use strict_encoding::StrictDeserialize;
 
-use crate::interface::{GenesisIface, Iface, OwnedIface, Req, TransitionIface};
-use crate::stl::StandardTypes;
+use crate::interface::{
+    ContractError, ContractIface, GenesisIface, Iface, IfaceFeatures, OwnedIface, Req,
+    TransitionIface,
+};
+use crate::stl::{ContractName, Nominal, Precision, StandardTypes, Ticker};
 
 pub fn rgb20() -> Iface {
     let types = StandardTypes::new();
@@ -65,10 +71,62 @@ pub fn rgb20() -> Iface {
     }
 }
 
+/// Contract state accessed through the RGB20 interface.
+///
+/// Unlike [`ContractIface::display_name`] and other best-effort readers, the
+/// wrapper reads the state by the exact names defined by the interface.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Rgb20(ContractIface);
+
+impl Deref for Rgb20 {
+    type Target = ContractIface;
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+impl Rgb20 {
+    pub fn with(contract: ContractIface) -> Result<Self, ContractError> {
+        if contract.iface.iface_id != rgb20().iface_id() {
+            return Err(ContractError::IfaceMismatch(contract.iface.iface_id));
+        }
+        Ok(Rgb20(contract))
+    }
+
+    /// # Panics
+    ///
+    /// If data are corrupted and contract schema doesn't match interface
+    /// implementations.
+    pub fn nominal(&self) -> Nominal {
+        self.nominal_field("")
+    }
+
+    /// Supply features of the asset, which are the features of the RGB20
+    /// interface.
+    pub fn features(&self) -> IfaceFeatures { rgb20().features() }
+
+    pub fn name(&self) -> String { self.nominal_field::<ContractName>("name").to_string() }
+    pub fn ticker(&self) -> String { self.nominal_field::<Ticker>("ticker").to_string() }
+    pub fn precision(&self) -> Precision { self.nominal_field("precision") }
+
+    /// Reads a single field of the `Nominal` global state, or the whole value
+    /// for the empty path.
+    ///
+    /// # Panics
+    ///
+    /// If data are corrupted and contract schema doesn't match interface
+    /// implementations.
+    fn nominal_field<T: StrictDeserialize>(&self, path: &str) -> T {
+        self.0
+            .global_path_typed("Nominal", path)
+            .expect("RGB20 interface requires Nominal global state")
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::str::FromStr;
+
     use super::*;
-    use crate::containers::BindleContent;
+    use crate::containers::{Bindle, BindleContent};
 
     const RGB20: &str = include_str!("../../tests/data/rgb20.asc.rgb");
 
@@ -79,4 +137,10 @@ mod test {
     fn iface_bindle() {
         assert_eq!(format!("{}", rgb20().bindle()), RGB20);
     }
+
+    #[test]
+    fn iface_bindle_parse() {
+        let bindle = Bindle::<Iface>::from_str(RGB20).unwrap();
+        assert_eq!(bindle.unbindle(), rgb20());
+    }
 }
@@ -0,0 +1,224 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Specifications of the operations provided by an interface, used to build
+//! user interfaces for arbitrary contracts.
+
+use std::iter;
+
+use rgb::Occurrences;
+use strict_encoding::TypeName;
+use strict_types::SemId;
+
+use crate::interface::{GlobalIface, Iface, IfaceImpl, OperationKind, OwnedIface, Req, TypeReqMap};
+
+/// Argument of an interface operation.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ArgSpec<Info> {
+    pub name: TypeName,
+    /// Definition of the state or valency in the interface; `None` if the
+    /// interface doesn't define the name used by the operation.
+    pub info: Option<Info>,
+    pub occurrences: Occurrences,
+    /// Type id used by the schema for the argument, if an interface
+    /// implementation is provided and it implements the state or valency.
+    pub type_id: Option<u16>,
+}
+
+impl ArgSpec<Req<GlobalIface>> {
+    /// Semantic type of the global state, if the interface restricts it.
+    pub fn sem_id(&self) -> Option<SemId> {
+        match self.info.as_ref()?.info {
+            GlobalIface::Any => None,
+            GlobalIface::Typed(sem_id) => Some(sem_id),
+        }
+    }
+}
+
+impl ArgSpec<OwnedIface> {
+    /// Semantic type of the owned structured data, if the interface
+    /// restricts it.
+    pub fn sem_id(&self) -> Option<SemId> {
+        match self.info.as_ref()? {
+            OwnedIface::Data(sem_id) => Some(*sem_id),
+            _ => None,
+        }
+    }
+}
+
+/// Specification of an interface operation, listing its arguments.
+///
+/// Interfaces don't keep documentation for the operations, so the
+/// specification provides just the names and types.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct OperationSpec {
+    /// Name of the operation; `None` for the genesis.
+    pub name: Option<TypeName>,
+    pub kind: OperationKind,
+    /// Transition or extension type used by the schema, if an interface
+    /// implementation is provided and it implements the operation.
+    pub type_id: Option<u16>,
+    pub metadata: Option<SemId>,
+    pub globals: Vec<ArgSpec<Req<GlobalIface>>>,
+    /// State spent by the operation, which is present for state transitions
+    /// only.
+    pub inputs: Vec<ArgSpec<OwnedIface>>,
+    pub assignments: Vec<ArgSpec<OwnedIface>>,
+    /// Valencies redeemed by the operation, which is present for state
+    /// extensions only.
+    pub redeems: Vec<ArgSpec<Req<()>>>,
+    pub valencies: Vec<ArgSpec<Req<()>>>,
+}
+
+impl Iface {
+    /// Lists all operations of the interface, starting with the genesis and
+    /// followed by state transitions and state extensions.
+    pub fn operations(&self) -> impl Iterator<Item = OperationSpec> + '_ {
+        self.operation_specs(None)
+    }
+
+    /// Lists all operations of the interface like [`Self::operations`],
+    /// adding the type ids used by the schema of the interface
+    /// implementation.
+    pub fn operations_with<'a>(
+        &'a self,
+        iimpl: &'a IfaceImpl,
+    ) -> impl Iterator<Item = OperationSpec> + 'a {
+        self.operation_specs(Some(iimpl))
+    }
+
+    fn operation_specs<'a>(
+        &'a self,
+        iimpl: Option<&'a IfaceImpl>,
+    ) -> impl Iterator<Item = OperationSpec> + 'a {
+        let genesis = OperationSpec {
+            name: None,
+            kind: OperationKind::Genesis,
+            type_id: None,
+            metadata: self.genesis.metadata,
+            globals: self.global_args(&self.genesis.global, iimpl),
+            inputs: vec![],
+            assignments: self.owned_args(&self.genesis.assignments, iimpl),
+            redeems: vec![],
+            valencies: self.valency_args(&self.genesis.valencies, iimpl),
+        };
+        let transitions = self.transitions.iter().map(move |(name, spec)| OperationSpec {
+            name: Some(name.clone()),
+            kind: OperationKind::Transition,
+            type_id: iimpl.and_then(|iimpl| iimpl.transition_type(name)),
+            metadata: spec.metadata,
+            globals: self.global_args(&spec.globals, iimpl),
+            inputs: self.owned_args(&spec.inputs, iimpl),
+            assignments: self.owned_args(&spec.assignments, iimpl),
+            redeems: vec![],
+            valencies: self.valency_args(&spec.valencies, iimpl),
+        });
+        let extensions = self.extensions.iter().map(move |(name, spec)| OperationSpec {
+            name: Some(name.clone()),
+            kind: OperationKind::Extension,
+            type_id: iimpl.and_then(|iimpl| iimpl.extension_type(name)),
+            metadata: spec.metadata,
+            globals: self.global_args(&spec.globals, iimpl),
+            inputs: vec![],
+            assignments: self.owned_args(&spec.assignments, iimpl),
+            redeems: self.valency_args(&spec.redeems, iimpl),
+            valencies: self.valency_args(&spec.valencies, iimpl),
+        });
+        iter::once(genesis).chain(transitions).chain(extensions)
+    }
+
+    fn global_args(
+        &self,
+        reqs: &TypeReqMap,
+        iimpl: Option<&IfaceImpl>,
+    ) -> Vec<ArgSpec<Req<GlobalIface>>> {
+        args(reqs, |name| {
+            let info = self.global_state.get(name).cloned();
+            (info, iimpl.and_then(|iimpl| iimpl.global_type(name)))
+        })
+    }
+
+    fn owned_args(&self, reqs: &TypeReqMap, iimpl: Option<&IfaceImpl>) -> Vec<ArgSpec<OwnedIface>> {
+        args(reqs, |name| {
+            let info = self.owned_state.get(name).cloned();
+            (info, iimpl.and_then(|iimpl| iimpl.assignments_type(name)))
+        })
+    }
+
+    fn valency_args(&self, reqs: &TypeReqMap, iimpl: Option<&IfaceImpl>) -> Vec<ArgSpec<Req<()>>> {
+        args(reqs, |name| {
+            let info = self.valencies.get(name).cloned();
+            (info, iimpl.and_then(|iimpl| iimpl.valency_type(name)))
+        })
+    }
+}
+
+fn args<Info>(
+    reqs: &TypeReqMap,
+    resolve: impl Fn(&TypeName) -> (Option<Info>, Option<u16>),
+) -> Vec<ArgSpec<Info>> {
+    reqs.iter()
+        .map(|(name, occurrences)| {
+            let (info, type_id) = resolve(name);
+            ArgSpec {
+                name: name.clone(),
+                info,
+                occurrences: occurrences.clone(),
+                type_id,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::{self, GS_NOMINAL, OS_ASSETS, TS_TRANSFER};
+    use crate::interface::rgb20;
+
+    #[test]
+    fn rgb20_operations() {
+        let iface = rgb20();
+        let ops = iface.operations().collect::<Vec<_>>();
+        assert_eq!(ops.len(), 2);
+
+        let genesis = &ops[0];
+        assert_eq!(genesis.kind, OperationKind::Genesis);
+        assert_eq!(genesis.name, None);
+        assert_eq!(genesis.globals.len(), 2);
+        assert_eq!(genesis.globals[1].name, tn!("Nominal"));
+        assert_eq!(genesis.globals[1].occurrences, Occurrences::Once);
+        assert!(genesis.globals[1].sem_id().is_some());
+        assert_eq!(genesis.globals[1].type_id, None);
+
+        let transfer = &ops[1];
+        assert_eq!(transfer.kind, OperationKind::Transition);
+        assert_eq!(transfer.name, Some(tn!("Transfer")));
+        assert_eq!(transfer.inputs[0].info, Some(OwnedIface::Amount));
+        assert_eq!(transfer.inputs[0].occurrences, Occurrences::OnceOrMore);
+
+        let iimpl = fixtures::iimpl();
+        let ops = iface.operations_with(&iimpl).collect::<Vec<_>>();
+        assert_eq!(ops[0].globals[1].type_id, Some(GS_NOMINAL));
+        assert_eq!(ops[1].type_id, Some(TS_TRANSFER));
+        assert_eq!(ops[1].assignments[0].type_id, Some(OS_ASSETS));
+    }
+}
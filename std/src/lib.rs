@@ -83,7 +83,13 @@ pub mod interface;
 pub mod containers;
 pub mod persistence;
 pub mod resolvers;
+pub mod rng;
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+#[cfg(any(test, feature = "vectors"))]
+pub mod vectors;
 
 pub use bp::{Chain, Outpoint, Txid};
+pub use containers::issue;
 
 pub const LIB_NAME_RGB_STD: &str = "RGBStd";
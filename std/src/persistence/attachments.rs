@@ -0,0 +1,203 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Content-addressed storage of the contract attachments.
+
+use std::io;
+
+use amplify::{Bytes32, Wrapper};
+use commit_verify::Sha256;
+use rgb::AttachId;
+
+use crate::stl::MediaType;
+
+/// Computes attachment id from the attachment data.
+pub fn attach_id(data: &[u8]) -> AttachId {
+    let mut engine = Sha256::default();
+    engine.input_raw(data);
+    AttachId::from_inner(Bytes32::from(engine.finish()))
+}
+
+#[derive(Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum AttachmentError {
+    /// attachment data does not match the expected id {expected}; the actual
+    /// data id is {actual}.
+    Mismatch { expected: AttachId, actual: AttachId },
+
+    #[from]
+    #[display(inner)]
+    Io(io::Error),
+}
+
+/// Detects media type of the attachment data from the well-known file
+/// signatures. Returns `None` if the format is not recognized.
+pub fn sniff_media_type(data: &[u8]) -> Option<MediaType> {
+    const SIGNATURES: [(&[u8], &str); 6] = [
+        (b"\x89PNG\r\n\x1a\n", MediaType::PNG),
+        (b"\xFF\xD8\xFF", MediaType::JPEG),
+        (b"GIF87a", MediaType::GIF),
+        (b"GIF89a", MediaType::GIF),
+        (b"%PDF-", MediaType::PDF),
+        (b"PK\x03\x04", MediaType::ZIP),
+    ];
+    if let Some((_, media_type)) = SIGNATURES
+        .iter()
+        .find(|(signature, _)| data.starts_with(signature))
+    {
+        return Some(MediaType::from(*media_type));
+    }
+    match data.iter().find(|byte| !byte.is_ascii_whitespace()) {
+        Some(b'{') | Some(b'[') if std::str::from_utf8(data).is_ok() => {
+            Some(MediaType::from(MediaType::JSON))
+        }
+        _ => None,
+    }
+}
+
+/// Storage of the attachment blobs addressed by their [`AttachId`].
+pub trait AttachmentStore {
+    /// Stores attachment data, returning its id.
+    fn put(&mut self, data: &[u8]) -> Result<AttachId, AttachmentError> {
+        let id = attach_id(data);
+        self.import_verified(id, data)?;
+        Ok(id)
+    }
+
+    /// Stores attachment data received out-of-band, which are expected to have
+    /// a specific id. Fails with [`AttachmentError::Mismatch`] if the data do
+    /// not hash to that id.
+    fn import_verified(&mut self, id: AttachId, data: &[u8]) -> Result<(), AttachmentError>;
+
+    /// Retrieves attachment data, if known to the store.
+    fn get(&self, id: AttachId) -> Result<Option<Vec<u8>>, AttachmentError>;
+
+    /// Checks whether the attachment data are known to the store.
+    fn has(&self, id: AttachId) -> Result<bool, AttachmentError>;
+
+    /// Number of attachments kept by the store.
+    fn len(&self) -> Result<usize, AttachmentError>;
+
+    fn is_empty(&self) -> Result<bool, AttachmentError> { self.len().map(|len| len == 0) }
+}
+
+#[cfg(feature = "fs")]
+pub use _fs::FsAttachmentStore;
+
+#[cfg(feature = "fs")]
+mod _fs {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    /// Checks that the data hash to the expected attachment id.
+    fn check_id(expected: AttachId, data: &[u8]) -> Result<(), AttachmentError> {
+        let actual = attach_id(data);
+        if actual != expected {
+            return Err(AttachmentError::Mismatch { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Attachment store keeping each attachment in a separate file inside
+    /// some directory, using the attachment id as the file name.
+    #[derive(Clone, Eq, PartialEq, Debug)]
+    pub struct FsAttachmentStore {
+        dir: PathBuf,
+    }
+
+    impl FsAttachmentStore {
+        /// Opens the store, creating the directory if it doesn't exist.
+        pub fn open(dir: impl AsRef<Path>) -> Result<Self, io::Error> {
+            let dir = dir.as_ref().to_owned();
+            fs::create_dir_all(&dir)?;
+            Ok(FsAttachmentStore { dir })
+        }
+
+        pub fn dir(&self) -> &Path { &self.dir }
+
+        fn path(&self, id: AttachId) -> PathBuf { self.dir.join(id.to_string()) }
+    }
+
+    impl AttachmentStore for FsAttachmentStore {
+        fn import_verified(&mut self, id: AttachId, data: &[u8]) -> Result<(), AttachmentError> {
+            check_id(id, data)?;
+            fs::write(self.path(id), data)?;
+            Ok(())
+        }
+
+        fn get(&self, id: AttachId) -> Result<Option<Vec<u8>>, AttachmentError> {
+            match fs::read(self.path(id)) {
+                Ok(data) => Ok(Some(data)),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        }
+
+        fn has(&self, id: AttachId) -> Result<bool, AttachmentError> {
+            Ok(self.path(id).is_file())
+        }
+
+        fn len(&self) -> Result<usize, AttachmentError> {
+            let mut count = 0;
+            for entry in fs::read_dir(&self.dir)? {
+                if entry?.file_type()?.is_file() {
+                    count += 1;
+                }
+            }
+            Ok(count)
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use crate::fixtures::test_dir;
+
+        #[test]
+        fn put_get() {
+            let dir = test_dir("attachment-store");
+            let mut store = FsAttachmentStore::open(dir).unwrap();
+            assert!(store.is_empty().unwrap());
+
+            let id = store.put(b"attachment").unwrap();
+            assert_eq!(id, attach_id(b"attachment"));
+            assert!(store.has(id).unwrap());
+            assert_eq!(store.get(id).unwrap(), Some(b"attachment".to_vec()));
+            assert_eq!(store.len().unwrap(), 1);
+
+            assert_eq!(sniff_media_type(b"attachment"), None);
+            assert_eq!(sniff_media_type(b"%PDF-1.7"), Some(MediaType::from(MediaType::PDF)));
+            assert_eq!(sniff_media_type(b" {\"a\": 1}"), Some(MediaType::from(MediaType::JSON)));
+
+            let other = attach_id(b"other");
+            assert!(!store.has(other).unwrap());
+            assert_eq!(store.get(other).unwrap(), None);
+            assert!(matches!(
+                store.import_verified(other, b"attachment"),
+                Err(AttachmentError::Mismatch { expected, actual })
+                    if expected == other && actual == id
+            ));
+            assert_eq!(store.len().unwrap(), 1);
+        }
+    }
+}
@@ -21,7 +21,7 @@
 
 use rgb::{validation, ContractId, SubSchema};
 
-use crate::containers::{Bindle, Cert, ContentId, Contract};
+use crate::containers::{Bindle, Cert, ContentId, Contract, Transfer};
 use crate::interface::{ContractIface, Iface, IfaceId, IfaceImpl};
 use crate::resolvers::ResolveHeight;
 
@@ -29,6 +29,7 @@ pub trait Inventory {
     type ImportError: std::error::Error;
     type ConsignError: std::error::Error;
     type InternalError: std::error::Error;
+    type LookupError: std::error::Error;
 
     fn import_sigs<I>(&mut self, content_id: ContentId, sigs: I) -> Result<(), Self::ImportError>
     where
@@ -58,6 +59,16 @@ pub trait Inventory {
     where
         R::Error: 'static;
 
+    /// Accepts a validated transfer consignment, merging it with the already
+    /// known data of the same contract.
+    fn accept_transfer<R: ResolveHeight>(
+        &mut self,
+        transfer: Transfer,
+        resolver: &mut R,
+    ) -> Result<validation::Status, Self::ImportError>
+    where
+        R::Error: 'static;
+
     /// # Safety
     ///
     /// Calling this method may lead to including into the stash asset
@@ -75,18 +86,18 @@ pub trait Inventory {
         contract_id: ContractId,
     ) -> Result<Bindle<Contract>, Self::InternalError>;
 
+    fn export_iface(&mut self, iface_id: IfaceId) -> Result<Bindle<Iface>, Self::InternalError>;
+
+    /// Constructs contract interface for the contract. Fails if the contract
+    /// schema has multiple implementations of the interface and none of them
+    /// was chosen for the contract.
     fn contract_iface(
         &mut self,
         contract_id: ContractId,
         iface_id: IfaceId,
-    ) -> Result<ContractIface, Self::InternalError>;
+    ) -> Result<ContractIface, Self::LookupError>;
 
     /*
     fn consign(&mut self) -> Result<Transfer, Self::ConsignError>;
-
-    fn accept<const TYPE: bool>(
-        &mut self,
-        consignment: Consignment<TYPE>,
-    ) -> Result<(), Self::ImportError>;
      */
 }
@@ -36,6 +36,14 @@
 mod stash;
 pub mod stock;
 mod inventory;
+mod attachments;
+#[cfg(feature = "fs")]
+mod store;
 
+#[cfg(feature = "fs")]
+pub use attachments::FsAttachmentStore;
+pub use attachments::{attach_id, sniff_media_type, AttachmentError, AttachmentStore};
 pub use inventory::Inventory;
 pub use stash::Stash;
+#[cfg(feature = "fs")]
+pub use store::{ScanIssue, ScanReport, SchemaStore};
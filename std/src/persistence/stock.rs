@@ -19,13 +19,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use amplify::confinement::{self, Confined, SmallOrdMap, TinyOrdMap};
-use rgb::validation::{Validity, Warning};
-use rgb::{validation, ContractHistory, ContractId, ContractState, SchemaId, SubSchema};
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::Hash;
+use std::{iter, mem};
 
-use crate::containers::{Bindle, Cert, ContentId, ContentSigs, Contract};
-use crate::interface::{ContractIface, Iface, IfaceId, IfaceImpl, IfacePair, SchemaIfaces};
-use crate::persistence::Inventory;
+use amplify::confinement::{self, Confined, MediumBlob, SmallOrdMap, TinyOrdMap};
+use amplify::{Bytes32, Wrapper};
+use bp::{Chain, Outpoint};
+use commit_verify::Sha256;
+use rgb::validation::{AnchoredBundle, Validity, Warning};
+use rgb::{
+    validation, Assign, Assignments, AssignmentsType, AttachId, ContractHistory, ContractId,
+    ContractState, Extension, GraphSeal, Operation, Opout, SchemaId, SecretSeal, SubSchema,
+    Transition, TypedAssigns,
+};
+use strict_encoding::{StrictDeserialize, StrictEncode, StrictSerialize, StrictWriter, TypeName};
+
+use crate::containers::{
+    Beneficiary, Bindle, BindleContent, BuilderError, Cert, ContentId, ContentSigs, Contract,
+    ContractBuilder, Disclosure, ForgeError, IntegrityError, MergeError, Ready, SealView, Transfer,
+    TransitionBuilder, VoutSeal, WatchItem,
+};
+use crate::interface::{
+    ContractIface, Iface, IfaceId, IfaceImpl, IfacePair, ImplId, SchemaIfaces,
+};
+use crate::persistence::{attach_id, AttachmentError, AttachmentStore, Inventory};
+#[cfg(feature = "async")]
+use crate::resolvers::{AsyncResolveHeight, WitnessFetch};
 use crate::resolvers::ResolveHeight;
 use crate::LIB_NAME_RGB_STD;
 
@@ -37,6 +57,14 @@ pub enum IfaceImplError {
 
     /// interface implementation references unknown interface {0::<0}
     UnknownIface(IfaceId),
+
+    /// schema doesn't implement interface {0::<0}, thus there is no
+    /// implementation to upgrade.
+    NotImplemented(IfaceId),
+
+    /// interface implementation {0::<0} is not newer than the implementation
+    /// it upgrades.
+    NotNewer(ImplId),
 }
 
 #[derive(Debug, Display, Error, From)]
@@ -59,6 +87,19 @@ pub enum Error {
     /// you'd like to take the risc, call `import_contract_force`.
     TerminalsUnmined,
 
+    /// contract is issued on a chain different from the one used by the stock.
+    #[display("contract is issued on {found} while the stock operates on {expected}")]
+    ChainMismatch { expected: Chain, found: Chain },
+
+    #[from]
+    Integrity(IntegrityError),
+
+    #[from]
+    Merge(MergeError),
+
+    #[from]
+    Forge(ForgeError),
+
     #[from]
     Confinement(confinement::Error),
 
@@ -67,6 +108,194 @@ pub enum Error {
 
     #[from]
     HeightResolver(Box<dyn std::error::Error>),
+
+    #[from]
+    Attachment(AttachmentError),
+
+    #[from]
+    Internal(InternalError),
+}
+
+#[derive(Clone, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum BuilderLookupError {
+    /// schema {0::<0} is unknown.
+    UnknownSchema(SchemaId),
+
+    /// contract {0::<0} is unknown.
+    UnknownContract(ContractId),
+
+    /// schema {1::<0} doesn't implement interface named {0}.
+    UnknownIface(TypeName, SchemaId),
+
+    /// schema {1::<0} has multiple implementations for interfaces named {0}.
+    AmbiguousIface(TypeName, SchemaId),
+
+    /// contract doesn't assign state of type {ty} to {outpoint}.
+    UnknownInput {
+        outpoint: Outpoint,
+        ty: AssignmentsType,
+    },
+
+    /// contract assigns multiple states of the same type to the outpoint; the
+    /// input must be chosen among the candidates.
+    AmbiguousInput { candidates: Vec<Opout> },
+
+    #[from]
+    #[display(inner)]
+    Forge(ForgeError),
+}
+
+#[derive(Clone, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum TransferError {
+    /// contract {0::<0} doesn't implement an interface with the default
+    /// transfer operation.
+    NoDefaultTransfer(ContractId),
+
+    /// contract {contract_id::<0} allocates state to the spent outpoint
+    /// {outpoint}, which can't be moved by a blank transition.
+    BlankUnsupported {
+        contract_id: ContractId,
+        outpoint: Outpoint,
+    },
+
+    /// transfer doesn't conserve the fungible state: its inputs spend
+    /// {inputs} in total, while its outputs assign {outputs}.
+    ValueNotConserved { inputs: u64, outputs: u64 },
+
+    #[from]
+    #[display(inner)]
+    Lookup(BuilderLookupError),
+
+    #[display(inner)]
+    Builder(BuilderError),
+}
+
+impl From<BuilderError> for TransferError {
+    fn from(err: BuilderError) -> Self {
+        match err {
+            BuilderError::BalanceMismatch { inputs, outputs } => {
+                TransferError::ValueNotConserved { inputs, outputs }
+            }
+            err => TransferError::Builder(err),
+        }
+    }
+}
+
+/// State transitions of a transfer prepared with [`Stock::transfer`], which
+/// must be committed to by a witness transaction spending the `inputs`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PreparedTransfer {
+    pub contract_id: ContractId,
+    /// Seal of the beneficiary, which becomes the terminal of the transfer.
+    pub beneficiary: Beneficiary,
+    /// Transition assigning the transferred amount to the beneficiary and the
+    /// change to the change seal.
+    pub transition: Transition,
+    /// Blank transitions of other contracts, moving their state from the
+    /// spent outpoints to the change seal.
+    pub blanks: BTreeMap<ContractId, Transition>,
+    /// Outpoints which must be spent by the witness transaction.
+    pub inputs: BTreeSet<Outpoint>,
+}
+
+/// Default transfer operation of the interface implemented by a contract, see
+/// [`Iface::default_transfer`].
+struct DefaultTransfer {
+    iface_name: TypeName,
+    op_name: TypeName,
+    state_name: TypeName,
+    state_type: AssignmentsType,
+}
+
+#[derive(Clone, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum ContractIfaceError {
+    /// contract schema has multiple implementations of the requested interface;
+    /// the implementation must be chosen with `Stock::set_preferred_impl`.
+    AmbiguousImpl { candidates: BTreeSet<ImplId> },
+
+    /// interface implementation {0::<0} is not known for the contract schema
+    /// {1::<0}.
+    UnknownImpl(ImplId, SchemaId),
+
+    #[from]
+    #[display(inner)]
+    Confinement(confinement::Error),
+
+    #[from]
+    #[display(inner)]
+    Internal(InternalError),
+}
+
+/// Violation of the stock internal invariants detected by
+/// [`Stock::check_integrity`].
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[display(doc_comments)]
+pub enum IntegrityIssue {
+    /// schema is stored under id {0::<0} which doesn't match its content.
+    SchemaIdMismatch(SchemaId),
+
+    /// interface is stored under id {0::<0} which doesn't match its content.
+    IfaceIdMismatch(IfaceId),
+
+    /// interface implementation is stored under id {0::<0} which doesn't
+    /// match its content.
+    ImplIdMismatch(ImplId),
+
+    /// implementation for schema {0::<0} is stored under interface id {1::<0}
+    /// which doesn't match the implemented interface.
+    ImplIfaceMismatch(SchemaId, IfaceId),
+
+    /// contract is stored under id {0::<0} which doesn't match its content.
+    ContractIdMismatch(ContractId),
+
+    /// schema {1::<0} of contract {0::<0} is absent.
+    SchemaAbsent(ContractId, SchemaId),
+
+    /// state history of contract {0::<0} is absent or doesn't match the
+    /// contract.
+    HistoryInvalid(ContractId),
+
+    /// state history is kept for unknown contract {0::<0}.
+    StaleHistory(ContractId),
+
+    /// preferred implementation {1::<0} of contract {0::<0} refers to unknown
+    /// contract or implementation.
+    StalePreferredImpl(ContractId, ImplId),
+
+    /// interface {1::<0} of implementation {0::<0} is absent.
+    IfaceAbsent(ImplId, IfaceId),
+}
+
+impl IntegrityIssue {
+    /// Detects whether the issue can be fixed by [`Stock::repair`], which is
+    /// the case when the affected data are cached by the stock or can be
+    /// recovered from the contracts it keeps. Other issues mean loss of data
+    /// which must be imported once again.
+    pub fn is_repairable(&self) -> bool { !matches!(self, IntegrityIssue::IfaceAbsent(..)) }
+}
+
+/// Report on the stock integrity check.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct IntegrityReport {
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    /// Checks whether no issues were found.
+    pub fn is_consistent(&self) -> bool { self.issues.is_empty() }
+
+    /// Issues which can be fixed by [`Stock::repair`].
+    pub fn repairable(&self) -> impl Iterator<Item = &IntegrityIssue> {
+        self.issues.iter().filter(|issue| issue.is_repairable())
+    }
+
+    /// Issues meaning loss of data, which can't be repaired.
+    pub fn data_loss(&self) -> impl Iterator<Item = &IntegrityIssue> {
+        self.issues.iter().filter(|issue| !issue.is_repairable())
+    }
 }
 
 /// Stock is an in-memory inventory (stash, index, contract state) usefult for
@@ -75,18 +304,39 @@ pub enum Error {
 #[derive(StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = LIB_NAME_RGB_STD)]
 pub struct Stock {
+    chain: Chain,
+
     // stash
     schemata: TinyOrdMap<SchemaId, SchemaIfaces>,
     ifaces: TinyOrdMap<IfaceId, Iface>,
     contracts: TinyOrdMap<ContractId, Contract>,
     sigs: SmallOrdMap<ContentId, ContentSigs>,
 
+    // implementations of the interfaces for schemata which already have an
+    // implementation of the same interface, and per-contract choice of them
+    alt_impls: TinyOrdMap<ImplId, IfaceImpl>,
+    preferred_impls: TinyOrdMap<ContractId, TinyOrdMap<IfaceId, ImplId>>,
+
     // state
     history: TinyOrdMap<ContractId, ContractHistory>,
     // index
 }
 
 impl Stock {
+    /// Current version of the stock serialization format.
+    pub const VERSION: u8 = 1;
+
+    /// Constructs empty stock operating on contracts issued on the given chain.
+    pub fn new(chain: Chain) -> Self {
+        Stock {
+            chain,
+            ..default!()
+        }
+    }
+
+    /// Returns the chain which contracts of this stock are issued on.
+    pub fn chain(&self) -> Chain { self.chain }
+
     fn import_sigs_internal<I>(
         &mut self,
         content_id: ContentId,
@@ -105,14 +355,846 @@ impl Stock {
                 self.sigs.insert(content_id, ContentSigs::from(sigs)).ok();
             }
         }
-        Ok(())
+        Ok(())
+    }
+}
+
+impl Stock {
+    /// Computes snapshot of the contract state as of the given block height,
+    /// replaying only the operations with witness transactions mined at or
+    /// below the height. Genesis is always included.
+    ///
+    /// Witness transaction heights are resolved with `resolver`; the
+    /// snapshot can't be produced if any of them can't be resolved. With the
+    /// `serde` feature the snapshot can be exported for reporting purposes.
+    pub fn contract_state_at<R: ResolveHeight>(
+        &self,
+        contract_id: ContractId,
+        height: u32,
+        resolver: &mut R,
+    ) -> Result<ContractState, Error>
+    where
+        R::Error: 'static,
+    {
+        let contract = self
+            .contracts
+            .get(&contract_id)
+            .ok_or(InternalError::NoContract(contract_id))?;
+        let history = contract
+            .build_history_until(resolver, height)
+            .map_err(|err| Error::HeightResolver(Box::new(err)))?;
+        Ok(ContractState {
+            schema: contract.schema.clone(),
+            history,
+        })
+    }
+
+    /// Discloses the history of the state allocated to the seals, revealing
+    /// the state known to the stock only for these seals. See
+    /// [`Contract::disclose_seals`] for the details.
+    pub fn disclose_seals(
+        &self,
+        contract_id: ContractId,
+        seals: &[Outpoint],
+    ) -> Result<Disclosure, Error> {
+        let contract = self
+            .contracts
+            .get(&contract_id)
+            .ok_or(InternalError::NoContract(contract_id))?;
+        Ok(contract.disclose_seals(seals))
+    }
+
+    /// Lists unspent allocations of all contracts known to the stock, which
+    /// seals must be watched on chain by the wallet. The list is computed
+    /// from the stored contracts and thus reflects all accepted transfers.
+    pub fn watch_list(&self) -> Vec<WatchItem> {
+        self.contracts.values().flat_map(Contract::watch_list).collect()
+    }
+
+    /// Constructs contract builder for the schema and interface known to the
+    /// stock. The builder is set to the chain the stock operates on.
+    pub fn contract_builder(
+        &self,
+        schema_id: SchemaId,
+        iface_name: impl Into<TypeName>,
+    ) -> Result<ContractBuilder<Ready>, BuilderLookupError> {
+        let (schema, iface, iimpl) = self.resolve_iface(schema_id, iface_name.into())?;
+        Ok(ContractBuilder::with(iface, schema, iimpl)?.set_chain(self.chain))
+    }
+
+    /// Constructs state transition builder for an operation `op_name` of the
+    /// interface implemented by a contract known to the stock.
+    pub fn transition_builder(
+        &self,
+        contract_id: ContractId,
+        iface_name: impl Into<TypeName>,
+        op_name: impl Into<TypeName>,
+    ) -> Result<TransitionBuilder, BuilderLookupError> {
+        let schema_id = self
+            .contracts
+            .get(&contract_id)
+            .ok_or(BuilderLookupError::UnknownContract(contract_id))?
+            .schema_id();
+        let (schema, iface, iimpl) = self.resolve_iface(schema_id, iface_name.into())?;
+        Ok(TransitionBuilder::with(iface, schema, iimpl, op_name)?)
+    }
+
+    /// Resolves output of the contract operations assigning state of type
+    /// `ty` to the outpoint, which can be added as an input to the
+    /// [`TransitionBuilder`] with [`TransitionBuilder::add_checked_input`].
+    ///
+    /// If the outpoint is assigned multiple states of the type, all of them are
+    /// returned as candidates in [`BuilderLookupError::AmbiguousInput`] error.
+    pub fn resolve_input(
+        &self,
+        contract_id: ContractId,
+        outpoint: Outpoint,
+        ty: AssignmentsType,
+    ) -> Result<Opout, BuilderLookupError> {
+        let contract = self
+            .contracts
+            .get(&contract_id)
+            .ok_or(BuilderLookupError::UnknownContract(contract_id))?;
+        let mut candidates = contract.outputs_at(outpoint);
+        candidates.retain(|opout| opout.ty == ty);
+        match candidates.as_slice() {
+            [] => Err(BuilderLookupError::UnknownInput { outpoint, ty }),
+            [opout] => Ok(*opout),
+            _ => Err(BuilderLookupError::AmbiguousInput { candidates }),
+        }
+    }
+
+    fn resolve_iface(
+        &self,
+        schema_id: SchemaId,
+        iface_name: TypeName,
+    ) -> Result<(SubSchema, Iface, IfaceImpl), BuilderLookupError> {
+        let schema_ifaces = self
+            .schemata
+            .get(&schema_id)
+            .ok_or(BuilderLookupError::UnknownSchema(schema_id))?;
+        let mut candidates = schema_ifaces
+            .iimpls
+            .iter()
+            .filter_map(|(id, iimpl)| self.ifaces.get(id).map(|iface| (iface, iimpl)))
+            .filter(|(iface, _)| iface.name == iface_name);
+        let Some((iface, iimpl)) = candidates.next() else {
+            return Err(BuilderLookupError::UnknownIface(iface_name, schema_id));
+        };
+        if candidates.next().is_some() {
+            return Err(BuilderLookupError::AmbiguousIface(iface_name, schema_id));
+        }
+        Ok((schema_ifaces.schema.clone(), iface.clone(), iimpl.clone()))
+    }
+}
+
+impl Stock {
+    /// Prepares transfer of `amount` of the fungible state of the contract to
+    /// the beneficiary, using the default transfer operation of the contract
+    /// interface (see [`Iface::default_transfer`]).
+    ///
+    /// The spent allocations are selected largest first among the unspent
+    /// allocations with the seals known to the stock, which are assumed to be
+    /// controlled by the wallet. The change is assigned to `change_seal`. If
+    /// `blanks` is set, the state of other contracts allocated to the spent
+    /// outpoints is moved to the change seal with blank transitions, which
+    /// can move only the fungible state of the default transfer operation.
+    ///
+    /// The transitions are not anchored: the wallet must construct the
+    /// witness transaction spending [`PreparedTransfer::inputs`] and commit
+    /// to the transitions in it, after which the transfer can be consigned.
+    /// Transitions spending and assigning different amounts of the fungible
+    /// state are never produced: they are rejected with
+    /// [`TransferError::ValueNotConserved`].
+    pub fn transfer(
+        &self,
+        contract_id: ContractId,
+        beneficiary: Beneficiary,
+        amount: u64,
+        change_seal: impl Into<GraphSeal>,
+        blanks: bool,
+    ) -> Result<PreparedTransfer, TransferError> {
+        let change_seal = change_seal.into();
+        let contract = self
+            .contracts
+            .get(&contract_id)
+            .ok_or(BuilderLookupError::UnknownContract(contract_id))?;
+        let default = self
+            .default_transfer(contract)
+            .ok_or(TransferError::NoDefaultTransfer(contract_id))?;
+        let coins = select_coins(contract, default.state_type, amount);
+        let inputs = coins
+            .iter()
+            .filter_map(|item| match item.seal {
+                SealView::Revealed(_, outpoint) => Some(outpoint),
+                SealView::Concealed(..) => None,
+            })
+            .collect::<BTreeSet<_>>();
+
+        // the builder can't assign state to a blinded seal, thus it is
+        // assigned to a placeholder seal replaced after the completion
+        let seal = match beneficiary {
+            Beneficiary::WitnessVout(seal) => GraphSeal::from(seal),
+            Beneficiary::ConcealedUtxo(_) => GraphSeal::from(VoutSeal::new(change_seal.method, 0)),
+        };
+        let mut transition = self
+            .default_builder(contract_id, &default, &coins)?
+            .add_fungible_state(default.state_name.clone(), seal, amount)?
+            .complete_with_change(default.state_name, change_seal)?;
+        if let Beneficiary::ConcealedUtxo(secret) = beneficiary {
+            blind_assignment(&mut transition, default.state_type, seal, secret);
+        }
+
+        let blanks = if blanks {
+            self.compose_blanks(contract_id, &inputs, change_seal)?
+        } else {
+            none!()
+        };
+
+        Ok(PreparedTransfer {
+            contract_id,
+            beneficiary,
+            transition,
+            blanks,
+            inputs,
+        })
+    }
+
+    /// Composes blank transitions of the contracts other than `contract_id`,
+    /// moving their state allocated to the spent outpoints to the change seal.
+    fn compose_blanks(
+        &self,
+        contract_id: ContractId,
+        inputs: &BTreeSet<Outpoint>,
+        change_seal: GraphSeal,
+    ) -> Result<BTreeMap<ContractId, Transition>, TransferError> {
+        let mut blanks = BTreeMap::new();
+        for (id, other) in self.contracts.iter().filter(|(id, _)| **id != contract_id) {
+            let mut allocations = other.watch_list();
+            allocations.retain(|item| {
+                matches!(item.seal, SealView::Revealed(_, outpoint) if inputs.contains(&outpoint))
+            });
+            let Some(item) = allocations.first() else {
+                continue;
+            };
+            let unsupported = |item: &WatchItem| TransferError::BlankUnsupported {
+                contract_id: *id,
+                outpoint: match item.seal {
+                    SealView::Revealed(_, outpoint) => outpoint,
+                    SealView::Concealed(..) => unreachable!("only revealed seals are retained"),
+                },
+            };
+            let Some(default) = self.default_transfer(other) else {
+                return Err(unsupported(item));
+            };
+            if let Some(item) = allocations
+                .iter()
+                .find(|item| item.opout.ty != default.state_type || item.amount.is_none())
+            {
+                return Err(unsupported(item));
+            }
+            let blank = self
+                .default_builder(*id, &default, &allocations)?
+                .complete_with_change(default.state_name, change_seal)?;
+            blanks.insert(*id, blank);
+        }
+        Ok(blanks)
+    }
+
+    fn default_transfer(&self, contract: &Contract) -> Option<DefaultTransfer> {
+        let schema_ifaces = self.schemata.get(&contract.schema_id())?;
+        schema_ifaces.iimpls.iter().find_map(|(iface_id, iimpl)| {
+            let iface = self.ifaces.get(iface_id)?;
+            let (op_name, state_name) = iface.default_transfer()?;
+            Some(DefaultTransfer {
+                iface_name: iface.name.clone(),
+                op_name: op_name.clone(),
+                state_name: state_name.clone(),
+                state_type: iimpl.assignments_type(state_name)?,
+            })
+        })
+    }
+
+    fn default_builder(
+        &self,
+        contract_id: ContractId,
+        default: &DefaultTransfer,
+        allocations: &[WatchItem],
+    ) -> Result<TransitionBuilder, TransferError> {
+        let mut builder = self.transition_builder(
+            contract_id,
+            default.iface_name.clone(),
+            default.op_name.clone(),
+        )?;
+        for item in allocations {
+            let amount = item.amount.expect("allocations with known amounts are selected");
+            builder = builder.add_fungible_input(item.opout, amount)?;
+        }
+        Ok(builder)
+    }
+}
+
+/// Selects unspent allocations of the fungible state of type `ty` with known
+/// seals and amounts, largest first, until they cover the `amount`.
+fn select_coins(contract: &Contract, ty: AssignmentsType, amount: u64) -> Vec<WatchItem> {
+    let mut candidates = contract.watch_list();
+    candidates.retain(|item| {
+        item.opout.ty == ty && item.amount.is_some() && matches!(item.seal, SealView::Revealed(..))
+    });
+    candidates.sort_by(|a, b| b.amount.cmp(&a.amount).then(a.opout.cmp(&b.opout)));
+    let mut selected = 0u64;
+    candidates
+        .into_iter()
+        .take_while(|item| {
+            let take = selected < amount;
+            selected = selected.saturating_add(item.amount.unwrap_or_default());
+            take
+        })
+        .collect()
+}
+
+/// Replaces the fungible state assignment to the `placeholder` seal with the
+/// assignment of the same state to the blinded seal.
+fn blind_assignment(
+    transition: &mut Transition,
+    ty: AssignmentsType,
+    placeholder: GraphSeal,
+    blinded: SecretSeal,
+) {
+    let mut assignments = mem::take(&mut transition.assignments).into_inner();
+    if let Some(TypedAssigns::Fungible(assigns)) = assignments.get_mut(&ty) {
+        for assign in assigns.iter_mut() {
+            if let Assign::Revealed { seal, state } = assign {
+                if *seal == placeholder {
+                    let state = *state;
+                    *assign = Assign::ConfidentialSeal {
+                        seal: blinded,
+                        state,
+                    };
+                }
+            }
+        }
+    }
+    transition.assignments = Assignments::from_inner(assignments);
+}
+
+impl Stock {
+    /// Constructs contract interface using the specific interface
+    /// implementation, which must be known for the contract schema.
+    pub fn contract_iface_by_impl(
+        &self,
+        contract_id: ContractId,
+        impl_id: ImplId,
+    ) -> Result<ContractIface, ContractIfaceError> {
+        let schema_id = self.contract_schema_id(contract_id)?;
+        let iimpl = self
+            .find_impl(schema_id, impl_id)
+            .ok_or(ContractIfaceError::UnknownImpl(impl_id, schema_id))?;
+        Ok(self.compose_iface(contract_id, iimpl.clone())?)
+    }
+
+    /// Sets interface implementation which will be used for the contract by
+    /// [`Inventory::contract_iface`] when the contract schema has multiple
+    /// implementations of the same interface. The choice is persisted in the
+    /// stock.
+    pub fn set_preferred_impl(
+        &mut self,
+        contract_id: ContractId,
+        impl_id: ImplId,
+    ) -> Result<(), ContractIfaceError> {
+        let schema_id = self.contract_schema_id(contract_id)?;
+        let iface_id = self
+            .find_impl(schema_id, impl_id)
+            .ok_or(ContractIfaceError::UnknownImpl(impl_id, schema_id))?
+            .iface_id;
+        let mut preferred = self
+            .preferred_impls
+            .get(&contract_id)
+            .cloned()
+            .unwrap_or_default();
+        preferred.insert(iface_id, impl_id)?;
+        self.preferred_impls.insert(contract_id, preferred)?;
+        Ok(())
+    }
+
+    /// Upgrades implementation of the interface used by the contract to a
+    /// newer version published by the issuer. The new implementation must be
+    /// made for the contract schema and the same interface; it replaces the
+    /// previous implementation in the name lookups for the schema and gets
+    /// attached to the stored contract.
+    pub fn upgrade_impl(&mut self, contract_id: ContractId, iimpl: IfaceImpl) -> Result<(), Error> {
+        let schema_id = self.contract_schema_id(contract_id)?;
+        let iface_id = iimpl.iface_id;
+        if iimpl.schema_id != schema_id {
+            return Err(IntegrityError::ImplSchemaMismatch(iface_id).into());
+        }
+        let iface = self
+            .ifaces
+            .get(&iface_id)
+            .ok_or(IfaceImplError::UnknownIface(iface_id))?;
+        let current = self
+            .schemata
+            .get(&schema_id)
+            .ok_or(InternalError::NoSchema(schema_id))?
+            .iimpls
+            .get(&iface_id)
+            .ok_or(IfaceImplError::NotImplemented(iface_id))?;
+        let impl_id = iimpl.impl_id();
+        if iimpl.is_newer_than(current) != Some(true) {
+            return Err(IfaceImplError::NotNewer(impl_id).into());
+        }
+        let contract = self
+            .contracts
+            .get(&contract_id)
+            .ok_or(InternalError::NoContract(contract_id))?
+            .clone()
+            .attach_iface(IfacePair::with(iface.clone(), iimpl.clone()))?;
+
+        let previous = self
+            .schemata
+            .get_mut(&schema_id)
+            .expect("schema presence is checked above")
+            .iimpls
+            .insert(iface_id, iimpl)?
+            .expect("implementation presence is checked above");
+        remove_item(&mut self.alt_impls, &impl_id);
+        if let Some(preferred) = self.preferred_impls.get_mut(&contract_id) {
+            remove_item(preferred, &iface_id);
+        }
+        // Other contracts may still prefer the previous implementation
+        let previous_id = previous.impl_id();
+        if self
+            .preferred_impls
+            .values()
+            .any(|preferred| preferred.values().any(|id| *id == previous_id))
+        {
+            self.alt_impls.insert(previous_id, previous)?;
+        }
+        self.contracts.insert(contract_id, contract)?;
+        Ok(())
+    }
+
+    fn contract_schema_id(&self, contract_id: ContractId) -> Result<SchemaId, InternalError> {
+        self.history
+            .get(&contract_id)
+            .map(ContractHistory::schema_id)
+            .ok_or(InternalError::NoContract(contract_id))
+    }
+
+    /// Lists all implementations of the interface known for the schema.
+    fn iface_impls(
+        &self,
+        schema_id: SchemaId,
+        iface_id: IfaceId,
+    ) -> Result<Vec<&IfaceImpl>, InternalError> {
+        let schema_ifaces = self
+            .schemata
+            .get(&schema_id)
+            .ok_or(InternalError::NoSchema(schema_id))?;
+        Ok(schema_ifaces
+            .iimpls
+            .get(&iface_id)
+            .into_iter()
+            .chain(
+                self.alt_impls
+                    .values()
+                    .filter(|iimpl| iimpl.schema_id == schema_id && iimpl.iface_id == iface_id),
+            )
+            .collect())
+    }
+
+    fn find_impl(&self, schema_id: SchemaId, impl_id: ImplId) -> Option<&IfaceImpl> {
+        self.schemata
+            .get(&schema_id)
+            .into_iter()
+            .flat_map(|schema_ifaces| schema_ifaces.iimpls.values())
+            .chain(self.alt_impls.get(&impl_id))
+            .filter(|iimpl| iimpl.schema_id == schema_id)
+            .find(|iimpl| iimpl.impl_id() == impl_id)
+    }
+
+    fn compose_iface(
+        &self,
+        contract_id: ContractId,
+        iimpl: IfaceImpl,
+    ) -> Result<ContractIface, InternalError> {
+        let history = self
+            .history
+            .get(&contract_id)
+            .ok_or(InternalError::NoContract(contract_id))?
+            .clone();
+        let schema_id = history.schema_id();
+        let schema_ifaces = self
+            .schemata
+            .get(&schema_id)
+            .ok_or(InternalError::NoSchema(schema_id))?;
+        let state = ContractState {
+            schema: schema_ifaces.schema.clone(),
+            history,
+        };
+        Ok(ContractIface {
+            state,
+            iface: iimpl,
+        })
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ExportError {
+    /// contract {0::<0} is unknown.
+    UnknownContract(ContractId),
+
+    /// export cursor is issued for a different contract {0::<0}.
+    ContractMismatch(ContractId),
+
+    /// export cursor doesn't match the operations of the contract known to
+    /// the stock; the cursor is either tampered or produced by another stock.
+    CursorMismatch,
+}
+
+/// Frontier of the contract operations which were already exported, returned
+/// by [`Stock::export_contract_delta`] together with the exported data.
+///
+/// The cursor commits to the ids of all exported bundles and extensions in
+/// their order, such that a modified cursor, or a cursor which doesn't match
+/// the contract history, is detected when the next delta is exported.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_STD)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct ExportCursor {
+    contract_id: ContractId,
+    bundles: u32,
+    extensions: u32,
+    digest: Bytes32,
+}
+
+impl StrictSerialize for ExportCursor {}
+impl StrictDeserialize for ExportCursor {}
+
+impl ExportCursor {
+    const TAG: &'static [u8] = b"urn:lnpbp:rgb:export-cursor:v01#2310";
+
+    /// Constructs cursor for a contract none of which operations were
+    /// exported yet.
+    pub fn new(contract_id: ContractId) -> Self { Self::with(contract_id, &[], &[]) }
+
+    /// Constructs cursor for the full export of the contract. Consumers
+    /// applying the exported deltas in order may compare it with the cursor
+    /// returned for the last delta to check that they have reconstructed the
+    /// same contract.
+    pub fn with_contract(contract: &Contract) -> Self {
+        Self::with(contract.contract_id(), &contract.bundles, &contract.extensions)
+    }
+
+    fn with(
+        contract_id: ContractId,
+        bundles: &[AnchoredBundle],
+        extensions: &[Extension],
+    ) -> Self {
+        let mut data = Self::TAG.to_vec();
+        let mut write = || -> Result<_, std::io::Error> {
+            let mut writer = StrictWriter::with(usize::MAX, &mut data);
+            writer = contract_id.strict_encode(writer)?;
+            for anchored_bundle in bundles {
+                writer = anchored_bundle.bundle.bundle_id().strict_encode(writer)?;
+            }
+            for extension in extensions {
+                writer = extension.id().strict_encode(writer)?;
+            }
+            Ok(())
+        };
+        write().expect("writing to memory doesn't fail");
+        let mut engine = Sha256::default();
+        engine.input_raw(&data);
+        ExportCursor {
+            contract_id,
+            bundles: bundles.len() as u32,
+            extensions: extensions.len() as u32,
+            digest: Bytes32::from(engine.finish()),
+        }
+    }
+
+    pub fn contract_id(&self) -> ContractId { self.contract_id }
+
+    /// Number of the contract bundles which were exported.
+    pub fn bundles(&self) -> u32 { self.bundles }
+
+    /// Number of the contract state extensions which were exported.
+    pub fn extensions(&self) -> u32 { self.extensions }
+}
+
+impl Stock {
+    /// Exports contract operations which were not exported before the cursor
+    /// was produced, returning the cursor for the next export.
+    ///
+    /// The delta always contains genesis, schema and interfaces, such that it
+    /// can be validated when it is merged into the previously exported data;
+    /// the previous bundles spent by the new ones are not repeated. Applying
+    /// the deltas in order with [`Contract::merge`] produces the same contract
+    /// as the full export. Attachments and signatures are exported in full.
+    pub fn export_contract_delta(
+        &self,
+        contract_id: ContractId,
+        since: ExportCursor,
+    ) -> Result<(Contract, ExportCursor), ExportError> {
+        if since.contract_id != contract_id {
+            return Err(ExportError::ContractMismatch(since.contract_id));
+        }
+        let contract = self
+            .contracts
+            .get(&contract_id)
+            .ok_or(ExportError::UnknownContract(contract_id))?;
+        let (bundles, extensions) = (since.bundles as usize, since.extensions as usize);
+        if bundles > contract.bundles.len() ||
+            extensions > contract.extensions.len() ||
+            ExportCursor::with(
+                contract_id,
+                &contract.bundles[..bundles],
+                &contract.extensions[..extensions],
+            ) != since
+        {
+            return Err(ExportError::CursorMismatch);
+        }
+
+        let mut delta = contract.clone();
+        delta.bundles = Confined::try_from_iter(contract.bundles[bundles..].iter().cloned())
+            .expect("same or smaller collection size");
+        delta.extensions =
+            Confined::try_from_iter(contract.extensions[extensions..].iter().cloned())
+                .expect("same or smaller collection size");
+        delta.reset_validation_status();
+        Ok((delta, ExportCursor::with_contract(contract)))
+    }
+}
+
+impl Stock {
+    /// Lists attachments referenced by the contract state which data were not
+    /// provided with the consignments and thus must be fetched out-of-band.
+    pub fn missing_attachments(
+        &self,
+        contract_id: ContractId,
+    ) -> Result<BTreeSet<AttachId>, InternalError> {
+        let contract = self
+            .contracts
+            .get(&contract_id)
+            .ok_or(InternalError::NoContract(contract_id))?;
+        Ok(Self::contract_missing_attachments(contract))
+    }
+
+    /// Adds data of the missing contract attachments which are present in the
+    /// attachment store, returning ids of the added attachments.
+    pub fn load_attachments(
+        &mut self,
+        contract_id: ContractId,
+        store: &impl AttachmentStore,
+    ) -> Result<BTreeSet<AttachId>, Error> {
+        let missing = self.missing_attachments(contract_id)?;
+        let contract = self
+            .contracts
+            .get_mut(&contract_id)
+            .ok_or(InternalError::NoContract(contract_id))?;
+        let mut added = BTreeSet::new();
+        for id in missing {
+            let Some(data) = store.get(id)? else {
+                continue;
+            };
+            let actual = attach_id(&data);
+            if actual != id {
+                return Err(AttachmentError::Mismatch {
+                    expected: id,
+                    actual,
+                }
+                .into());
+            }
+            contract.attachments.insert(id, MediumBlob::try_from(data)?)?;
+            added.insert(id);
+        }
+        Ok(added)
+    }
+
+    fn contract_missing_attachments(contract: &Contract) -> BTreeSet<AttachId> {
+        contract
+            .attachment_ids()
+            .into_iter()
+            .filter(|id| !contract.attachments.contains_key(id))
+            .collect()
+    }
+}
+
+impl Stock {
+    /// Verifies internal invariants of the stock, which may be broken after
+    /// crashes or by modification of the stored data from outside of this
+    /// library. The stock is not modified; the repairable issues can be fixed
+    /// with [`Stock::repair`].
+    pub fn check_integrity(&self) -> IntegrityReport {
+        let mut issues = vec![];
+        let iface_known =
+            |iface_id: IfaceId| self.ifaces.values().any(|iface| iface.iface_id() == iface_id);
+
+        for (schema_id, schema_ifaces) in &self.schemata {
+            if schema_ifaces.schema.schema_id() != *schema_id {
+                issues.push(IntegrityIssue::SchemaIdMismatch(*schema_id));
+            }
+            for (iface_id, iimpl) in &schema_ifaces.iimpls {
+                if iimpl.iface_id != *iface_id {
+                    issues.push(IntegrityIssue::ImplIfaceMismatch(*schema_id, *iface_id));
+                }
+                if !iface_known(iimpl.iface_id) {
+                    issues.push(IntegrityIssue::IfaceAbsent(iimpl.impl_id(), iimpl.iface_id));
+                }
+            }
+        }
+        for (iface_id, iface) in &self.ifaces {
+            if iface.iface_id() != *iface_id {
+                issues.push(IntegrityIssue::IfaceIdMismatch(*iface_id));
+            }
+        }
+        for (impl_id, iimpl) in &self.alt_impls {
+            if iimpl.impl_id() != *impl_id {
+                issues.push(IntegrityIssue::ImplIdMismatch(*impl_id));
+            }
+            if !iface_known(iimpl.iface_id) {
+                issues.push(IntegrityIssue::IfaceAbsent(*impl_id, iimpl.iface_id));
+            }
+        }
+
+        for (contract_id, contract) in &self.contracts {
+            if contract.contract_id() != *contract_id {
+                issues.push(IntegrityIssue::ContractIdMismatch(*contract_id));
+            }
+            let schema_id = contract.schema_id();
+            if !self
+                .schemata
+                .values()
+                .any(|schema_ifaces| schema_ifaces.schema.schema_id() == schema_id)
+            {
+                issues.push(IntegrityIssue::SchemaAbsent(*contract_id, schema_id));
+            }
+            if self.history.get(contract_id).map(ContractHistory::schema_id) != Some(schema_id) {
+                issues.push(IntegrityIssue::HistoryInvalid(*contract_id));
+            }
+        }
+        for contract_id in self.history.keys() {
+            if !self.contracts.contains_key(contract_id) {
+                issues.push(IntegrityIssue::StaleHistory(*contract_id));
+            }
+        }
+        for (contract_id, preferred) in &self.preferred_impls {
+            for impl_id in preferred.values() {
+                if !self.is_impl_known(*contract_id, *impl_id) {
+                    issues.push(IntegrityIssue::StalePreferredImpl(*contract_id, *impl_id));
+                }
+            }
+        }
+
+        IntegrityReport { issues }
+    }
+
+    /// Fixes all repairable issues which may be found by
+    /// [`Stock::check_integrity`]: the data are re-indexed under their actual
+    /// ids, schemata absent from the stock are recovered from the contracts,
+    /// contract state histories are rebuilt, and the stale entries are
+    /// removed. Heights of the witness transactions for the rebuilt histories
+    /// are resolved with `resolver`.
+    ///
+    /// Returns report on the issues remaining after the repair, which all
+    /// represent loss of data.
+    pub fn repair<R: ResolveHeight>(&mut self, resolver: &mut R) -> Result<IntegrityReport, Error>
+    where
+        R::Error: 'static,
+    {
+        let mut schemata = TinyOrdMap::default();
+        for (_, mut schema_ifaces) in mem::take(&mut self.schemata) {
+            schema_ifaces.iimpls =
+                rekey(mem::take(&mut schema_ifaces.iimpls), |iimpl| iimpl.iface_id)?;
+            schemata.insert(schema_ifaces.schema.schema_id(), schema_ifaces)?;
+        }
+        self.schemata = schemata;
+        self.ifaces = rekey(mem::take(&mut self.ifaces), Iface::iface_id)?;
+        self.alt_impls = rekey(mem::take(&mut self.alt_impls), IfaceImpl::impl_id)?;
+        self.contracts = rekey(mem::take(&mut self.contracts), Contract::contract_id)?;
+
+        let absent = self
+            .contracts
+            .values()
+            .filter(|contract| !self.schemata.contains_key(&contract.schema_id()))
+            .map(|contract| contract.schema.clone())
+            .collect::<Vec<_>>();
+        for schema in absent {
+            self.schemata.insert(schema.schema_id(), SchemaIfaces::new(schema))?;
+        }
+
+        let stale = self
+            .history
+            .keys()
+            .filter(|contract_id| !self.contracts.contains_key(*contract_id))
+            .copied()
+            .collect::<Vec<_>>();
+        for contract_id in stale {
+            remove_item(&mut self.history, &contract_id);
+        }
+        for (contract_id, contract) in &self.contracts {
+            let schema_id = contract.schema_id();
+            if self.history.get(contract_id).map(ContractHistory::schema_id) != Some(schema_id) {
+                let history = contract
+                    .build_history(resolver)
+                    .map_err(|err| Error::HeightResolver(Box::new(err)))?;
+                self.history.insert(*contract_id, history)?;
+            }
+        }
+
+        let mut preferred_impls = TinyOrdMap::default();
+        for (contract_id, preferred) in mem::take(&mut self.preferred_impls) {
+            let preferred = TinyOrdMap::try_from_iter(
+                preferred
+                    .into_iter()
+                    .filter(|(_, impl_id)| self.is_impl_known(contract_id, *impl_id)),
+            )?;
+            if !preferred.is_empty() {
+                preferred_impls.insert(contract_id, preferred)?;
+            }
+        }
+        self.preferred_impls = preferred_impls;
+
+        Ok(self.check_integrity())
+    }
+
+    fn is_impl_known(&self, contract_id: ContractId, impl_id: ImplId) -> bool {
+        self.contracts
+            .get(&contract_id)
+            .and_then(|contract| self.find_impl(contract.schema_id(), impl_id))
+            .is_some()
     }
 }
 
+/// Removes the item from the map, including the last one which can't be
+/// removed with [`Confined::remove`].
+fn remove_item<K: Ord + Hash, V>(map: &mut TinyOrdMap<K, V>, key: &K) -> Option<V> {
+    let mut inner = mem::take(map).into_inner();
+    let item = inner.remove(key);
+    *map = Confined::try_from(inner).expect("same or smaller collection size");
+    item
+}
+
+/// Re-indexes the map under the ids computed from its values.
+fn rekey<K: Ord + Hash, V>(
+    map: TinyOrdMap<K, V>,
+    id: impl Fn(&V) -> K,
+) -> Result<TinyOrdMap<K, V>, confinement::Error> {
+    Confined::try_from_iter(map.into_iter().map(|(_, value)| (id(&value), value)))
+}
+
 impl Inventory for Stock {
     type ImportError = Error;
     type ConsignError = Error;
     type InternalError = InternalError;
+    type LookupError = ContractIfaceError;
 
     fn import_sigs<I>(&mut self, content_id: ContentId, sigs: I) -> Result<(), Self::ImportError>
     where
@@ -189,10 +1271,30 @@ impl Inventory for Stock {
             return Err(IfaceImplError::UnknownSchema(iimpl.schema_id).into());
         };
         // TODO: Do interface check on internal consistency
-        if schema_ifaces.iimpls.insert(iface_id, iimpl)?.is_some() {
-            status.add_warning(Warning::Custom(format!(
-                "interface implementation {impl_id::<0} is already known",
-            )));
+        match schema_ifaces.iimpls.get(&iface_id).map(IfaceImpl::impl_id) {
+            None => {
+                schema_ifaces.iimpls.insert(iface_id, iimpl)?;
+            }
+            Some(known_id) if known_id == impl_id => {
+                status.add_warning(Warning::Custom(format!(
+                    "interface implementation {impl_id::<0} is already known",
+                )));
+            }
+            Some(known_id) => {
+                let schema_id = iimpl.schema_id;
+                if self.alt_impls.insert(impl_id, iimpl)?.is_some() {
+                    status.add_warning(Warning::Custom(format!(
+                        "interface implementation {impl_id::<0} is already known",
+                    )));
+                } else {
+                    status.add_warning(Warning::Custom(format!(
+                        "interface implementation {impl_id::<0} binds schema {schema_id::<0} to \
+                         interface {iface_id::<0}, which is already implemented by \
+                         {known_id::<0}; contracts of the schema will require explicit choice \
+                         of the implementation",
+                    )));
+                }
+            }
         }
 
         let content_id = ContentId::IfaceImpl(impl_id);
@@ -213,6 +1315,17 @@ impl Inventory for Stock {
         self._import_contract(contract, resolver, false)
     }
 
+    fn accept_transfer<R: ResolveHeight>(
+        &mut self,
+        transfer: Transfer,
+        resolver: &mut R,
+    ) -> Result<validation::Status, Self::ImportError>
+    where
+        R::Error: 'static,
+    {
+        self._import_contract(transfer.into_contract(), resolver, false)
+    }
+
     unsafe fn import_contract_force<R: ResolveHeight>(
         &mut self,
         contract: Contract,
@@ -231,47 +1344,417 @@ impl Inventory for Stock {
         todo!()
     }
 
+    fn export_iface(&mut self, iface_id: IfaceId) -> Result<Bindle<Iface>, InternalError> {
+        let mut bindle = self
+            .ifaces
+            .get(&iface_id)
+            .ok_or(InternalError::NoIface(iface_id))?
+            .clone()
+            .bindle();
+        if let Some(sigs) = self.sigs.get(&ContentId::Iface(iface_id)) {
+            for cert in sigs.iter() {
+                // Do not bother if we can't export all the sigs
+                bindle.add_cert(cert.clone()).ok();
+            }
+        }
+        Ok(bindle)
+    }
+
     fn contract_iface(
         &mut self,
         contract_id: ContractId,
         iface_id: IfaceId,
-    ) -> Result<ContractIface, InternalError> {
-        let history = self
-            .history
+    ) -> Result<ContractIface, ContractIfaceError> {
+        let schema_id = self.contract_schema_id(contract_id)?;
+        let preferred = self
+            .preferred_impls
             .get(&contract_id)
-            .ok_or(InternalError::NoContract(contract_id))?
-            .clone();
-        let schema_id = history.schema_id();
-        let schema_ifaces = self
-            .schemata
-            .get(&schema_id)
-            .ok_or(InternalError::NoSchema(schema_id))?;
-        let state = ContractState {
-            schema: schema_ifaces.schema.clone(),
-            history,
+            .and_then(|preferred| preferred.get(&iface_id))
+            .copied();
+        let candidates = self.iface_impls(schema_id, iface_id)?;
+        let iimpl = match (preferred, candidates.as_slice()) {
+            (Some(impl_id), _) => candidates
+                .iter()
+                .find(|iimpl| iimpl.impl_id() == impl_id)
+                .ok_or(InternalError::NoImpl(impl_id))?,
+            (None, []) => return Err(InternalError::NoIfaceImpl(iface_id, schema_id).into()),
+            (None, [iimpl]) => iimpl,
+            (None, _) => {
+                return Err(ContractIfaceError::AmbiguousImpl {
+                    candidates: candidates.iter().map(|iimpl| iimpl.impl_id()).collect(),
+                });
+            }
         };
-        let iimpl = schema_ifaces
-            .iimpls
-            .get(&iface_id)
-            .ok_or(InternalError::NoIfaceImpl(iface_id, schema_id))?
-            .clone();
-        Ok(ContractIface {
-            state,
-            iface: iimpl,
-        })
+        Ok(self.compose_iface(contract_id, (*iimpl).clone())?)
+    }
+}
+
+#[cfg(feature = "fs")]
+mod _fs {
+    use std::io::{Read, Write};
+    use std::path::Path;
+    use std::{fs, io};
+
+    use strict_encoding::{StrictDecode, StrictEncode, StrictReader, StrictWriter};
+
+    use super::*;
+    use crate::containers::{
+        ConsignmentV1, IfaceImplV1, LoadError, Migrated, Migration, MigrationError,
+    };
+
+    /// Layout of the schemata of the stock v0, which interface implementations
+    /// used the v1 container layout.
+    #[derive(Clone, Debug)]
+    #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+    #[strict_type(lib = LIB_NAME_RGB_STD, rename = "SchemaIfaces")]
+    struct SchemaIfacesV0 {
+        schema: SubSchema,
+        iimpls: TinyOrdMap<IfaceId, IfaceImplV1>,
+    }
+
+    impl From<SchemaIfacesV0> for SchemaIfaces {
+        fn from(legacy: SchemaIfacesV0) -> Self {
+            let iimpls = legacy
+                .iimpls
+                .into_iter()
+                .map(|(id, iimpl)| (id, IfaceImpl::from(iimpl)));
+            SchemaIfaces {
+                schema: legacy.schema,
+                iimpls: Confined::try_from_iter(iimpls).expect("same collection size"),
+            }
+        }
+    }
+
+    /// Layout of the stock v0, as it was serialized before the stock format
+    /// got versioned: without the stock chain and with the data in the v1
+    /// container layout.
+    #[derive(Clone, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = LIB_NAME_RGB_STD, rename = "Stock")]
+    struct StockV0 {
+        schemata: TinyOrdMap<SchemaId, SchemaIfacesV0>,
+        ifaces: TinyOrdMap<IfaceId, Iface>,
+        contracts: TinyOrdMap<ContractId, ConsignmentV1<false>>,
+        sigs: SmallOrdMap<ContentId, ContentSigs>,
+        history: TinyOrdMap<ContractId, ContractHistory>,
+    }
+
+    impl TryFrom<StockV0> for Stock {
+        type Error = MigrationError;
+
+        /// Migrates legacy stock, taking the stock chain from the chain of the
+        /// contracts it contains.
+        fn try_from(legacy: StockV0) -> Result<Self, Self::Error> {
+            let mut chains = legacy
+                .contracts
+                .values()
+                .map(|contract| contract.genesis.chain);
+            let chain = chains.next().unwrap_or_default();
+            if let Some(other) = chains.find(|other| *other != chain) {
+                return Err(MigrationError::AmbiguousChain(chain, other));
+            }
+            let schemata = legacy
+                .schemata
+                .into_iter()
+                .map(|(id, schema_ifaces)| (id, SchemaIfaces::from(schema_ifaces)));
+            let contracts = legacy
+                .contracts
+                .into_iter()
+                .map(|(id, contract)| (id, Contract::from(contract)));
+            Ok(Stock {
+                chain,
+                schemata: Confined::try_from_iter(schemata).expect("same collection size"),
+                ifaces: legacy.ifaces,
+                contracts: Confined::try_from_iter(contracts).expect("same collection size"),
+                sigs: legacy.sigs,
+                alt_impls: none!(),
+                preferred_impls: none!(),
+                history: legacy.history,
+            })
+        }
+    }
+
+    impl Stock {
+        /// Magic bytes used in saving/restoring stock from a file.
+        pub const MAGIC: [u8; 4] = *b"STCK";
+
+        /// Loads stock from a file, migrating data saved in the legacy stock
+        /// formats and reporting which migrations were run.
+        pub fn load(path: impl AsRef<Path>) -> Result<Migrated<Self>, LoadError> {
+            let mut magic = [0u8; 7];
+            let mut file = fs::File::open(path)?;
+            file.read_exact(&mut magic)?;
+            if magic[..3] != *b"RGB" || magic[3..] != Self::MAGIC {
+                return Err(LoadError::InvalidMagic);
+            }
+            let mut data = vec![];
+            file.read_to_end(&mut data)?;
+            Ok(Self::decode_migrated(&data)?)
+        }
+
+        /// Saves stock to a file using the current stock format version.
+        pub fn save(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+            let mut file = fs::File::create(path)?;
+            file.write_all(b"RGB")?;
+            file.write_all(&Self::MAGIC)?;
+            file.write_all(&[Self::VERSION])?;
+            let writer = StrictWriter::with(usize::MAX, file);
+            self.strict_encode(writer)?;
+            Ok(())
+        }
+
+        /// Reads stock from serialized data (following the file magic bytes),
+        /// detecting the stock version and migrating legacy layouts.
+        fn decode_migrated(data: &[u8]) -> Result<Migrated<Self>, MigrationError> {
+            let (version, data) = data.split_first().ok_or(MigrationError::NoVersion)?;
+            let mut reader = StrictReader::with(usize::MAX, data);
+            match *version {
+                0 => {
+                    let legacy = StockV0::strict_decode(&mut reader)?;
+                    Ok(Migrated {
+                        data: Stock::try_from(legacy)?,
+                        migrations: vec![Migration::StockV0ToV1],
+                    })
+                }
+                Self::VERSION => Ok(Migrated::current(Stock::strict_decode(&mut reader)?)),
+                found => Err(MigrationError::UnsupportedVersion {
+                    format: "stock",
+                    found,
+                    min: 0,
+                    max: Self::VERSION,
+                }),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use std::str::FromStr;
+
+        use super::*;
+        use crate::containers::ContainerVer;
+        use crate::fixtures::test_dir;
+
+        /// Stock with a single contract, saved with the v0 stock format by the
+        /// library before the stock format got versioned.
+        const STOCK_V0: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/stock-v0.stock");
+
+        fn stock_v0() -> StockV0 {
+            let data = fs::read(STOCK_V0).unwrap();
+            StockV0::strict_decode(&mut StrictReader::with(usize::MAX, &data[8..])).unwrap()
+        }
+
+        #[test]
+        fn migrate_v0() {
+            let migrated = Stock::load(STOCK_V0).unwrap();
+            assert_eq!(migrated.migrations, vec![Migration::StockV0ToV1]);
+            let stock = migrated.into_inner();
+            assert_eq!(stock.chain(), Chain::Testnet3);
+            let id = ContractId::from_str("CC1Z5Mmpoz8ZZVAka2V4WH3x43UxEPEWsdSnsrj7py4h").unwrap();
+            let contract = stock.contracts.get(&id).unwrap();
+            assert_eq!(contract.version, ContainerVer::V2);
+            assert_eq!(contract.contract_id(), id);
+            assert!(stock.history.contains_key(&id));
+            let schema_ifaces = stock.schemata.get(&contract.schema_id()).unwrap();
+            assert_eq!(schema_ifaces.iimpls.len(), 1);
+            assert!(schema_ifaces.iimpls.values().all(|iimpl| iimpl.version == 0));
+
+            let path = test_dir("stock-migrate-v0").join("stock.stock");
+            stock.save(&path).unwrap();
+            let reloaded = Stock::load(&path).unwrap();
+            assert!(!reloaded.is_migrated());
+            assert!(reloaded.into_inner().contracts.contains_key(&id));
+        }
+
+        #[test]
+        fn migrate_ambiguous_chain() {
+            let mut legacy = stock_v0();
+            let mut contract = legacy.contracts.values().next().unwrap().clone();
+            contract.genesis.chain = Chain::Bitcoin;
+            let id = contract.genesis.contract_id();
+            legacy.contracts.insert(id, contract).unwrap();
+            assert!(matches!(
+                Stock::try_from(legacy),
+                Err(MigrationError::AmbiguousChain(a, b))
+                    if [a, b] == [Chain::Testnet3, Chain::Bitcoin] ||
+                        [a, b] == [Chain::Bitcoin, Chain::Testnet3]
+            ));
+        }
+
+        #[test]
+        fn stock_unsupported() {
+            assert_eq!(
+                Stock::decode_migrated(&[2]).unwrap_err(),
+                MigrationError::UnsupportedVersion {
+                    format: "stock",
+                    found: 2,
+                    min: 0,
+                    max: 1
+                }
+            );
+        }
     }
 }
 
 impl Stock {
+    /// Imports multiple contracts at once; transfers can be imported after
+    /// being converted with [`Transfer::into_contract`].
+    ///
+    /// All containers are checked and the state history of their contracts
+    /// is built before the stock gets modified, such that none of the
+    /// contracts is imported if any of them fails. Schemata and interfaces
+    /// shared by the containers are imported once, and the history is built
+    /// once per contract even if several containers of the same contract
+    /// are provided.
+    ///
+    /// Returns validation status for each of the containers, in the order
+    /// they were provided.
+    pub fn import_many<R: ResolveHeight>(
+        &mut self,
+        contracts: impl IntoIterator<Item = Contract>,
+        resolver: &mut R,
+    ) -> Result<Vec<validation::Status>, Error>
+    where
+        R::Error: 'static,
+    {
+        self.import_batch(contracts, resolver, false)
+    }
+
     fn _import_contract<R: ResolveHeight>(
         &mut self,
-        mut contract: Contract,
+        contract: Contract,
         resolver: &mut R,
         force: bool,
     ) -> Result<validation::Status, Error>
     where
         R::Error: 'static,
     {
+        let mut statuses = self.import_batch(iter::once(contract), resolver, force)?;
+        Ok(statuses.pop().expect("single contract is imported"))
+    }
+
+    fn import_batch<R: ResolveHeight>(
+        &mut self,
+        contracts: impl IntoIterator<Item = Contract>,
+        resolver: &mut R,
+        force: bool,
+    ) -> Result<Vec<validation::Status>, Error>
+    where
+        R::Error: 'static,
+    {
+        let mut statuses = vec![];
+        // Index of the status returned for the last version of each contract
+        let mut status_index = BTreeMap::<ContractId, usize>::new();
+        let mut staged = BTreeMap::<ContractId, Contract>::new();
+        for mut contract in contracts {
+            let mut status = self.check_importable(&contract, force)?;
+            let id = contract.contract_id();
+
+            let prev_contract = match staged.remove(&id) {
+                Some(prev_contract) => Some(prev_contract),
+                None => self.contracts.get(&id).cloned(),
+            };
+            if let Some(mut prev_contract) = prev_contract {
+                prev_contract.merge(contract)?;
+                contract = prev_contract;
+                status.add_warning(Warning::Custom(format!(
+                    "contract {id::<0} was merged with previously known contract version",
+                )));
+            }
+
+            for attach_id in Self::contract_missing_attachments(&contract) {
+                status.add_warning(Warning::Custom(format!(
+                    "attachment {attach_id} is referenced by contract {id::<0} but its data are \
+                     not known",
+                )));
+            }
+
+            staged.insert(id, contract);
+            status_index.insert(id, statuses.len());
+            statuses.push(status);
+        }
+
+        let mut histories = BTreeMap::new();
+        for (id, contract) in &staged {
+            let history = contract
+                .build_history(resolver)
+                .map_err(|err| Error::HeightResolver(Box::new(err)))?;
+            histories.insert(*id, history);
+        }
+
+        let added = staged
+            .keys()
+            .filter(|id| !self.contracts.contains_key(*id))
+            .count();
+        let added_history = staged
+            .keys()
+            .filter(|id| !self.history.contains_key(*id))
+            .count();
+        for len in [self.contracts.len() + added, self.history.len() + added_history] {
+            if len > u8::MAX as usize {
+                return Err(confinement::Error::Oversize {
+                    len,
+                    max_len: u8::MAX as usize,
+                }
+                .into());
+            }
+        }
+
+        // Schemata, interfaces and signatures are imported into a copy of the
+        // stock, which replaces the stock data only once all of them succeed
+        let mut next = Stock {
+            chain: self.chain,
+            schemata: self.schemata.clone(),
+            ifaces: self.ifaces.clone(),
+            sigs: self.sigs.clone(),
+            alt_impls: self.alt_impls.clone(),
+            ..default!()
+        };
+        let mut known_schemata = BTreeSet::new();
+        let mut known_impls = BTreeSet::new();
+        for (id, contract) in staged.iter_mut() {
+            let status = &mut statuses[status_index[id]];
+            if known_schemata.insert(contract.schema_id()) {
+                *status += next.import_schema(contract.schema.clone())?;
+            }
+            for IfacePair { iface, iimpl } in contract.ifaces.values() {
+                if known_impls.insert(iimpl.impl_id()) {
+                    *status += next.import_iface(iface.clone())?;
+                    *status += next.import_iface_impl(iimpl.clone())?;
+                }
+            }
+            for (content_id, sigs) in mem::take(&mut contract.signatures) {
+                // Do not bother if we can't import all the sigs
+                next.import_sigs_internal(content_id, sigs).ok();
+            }
+        }
+
+        self.schemata = next.schemata;
+        self.ifaces = next.ifaces;
+        self.sigs = next.sigs;
+        self.alt_impls = next.alt_impls;
+        for (id, contract) in staged {
+            let history = histories.remove(&id).expect("history is built for each contract");
+            self.history.insert(id, history).expect("history size is checked");
+            self.contracts.insert(id, contract).expect("number of contracts is checked");
+        }
+
+        Ok(statuses)
+    }
+
+    fn check_importable(
+        &self,
+        contract: &Contract,
+        force: bool,
+    ) -> Result<validation::Status, Error> {
+        if contract.genesis.chain != self.chain {
+            return Err(Error::ChainMismatch {
+                expected: self.chain,
+                found: contract.genesis.chain,
+            });
+        }
+        contract.verify_integrity()?;
+
         let mut status = validation::Status::new();
         match contract.validation_status() {
             None => return Err(Error::NotValidated),
@@ -295,34 +1778,45 @@ impl Stock {
             }
             _ => {}
         }
+        Ok(status)
+    }
+}
 
-        let id = contract.contract_id();
-
-        self.import_schema(contract.schema.clone())?;
-        for IfacePair { iface, iimpl } in contract.ifaces.values() {
-            self.import_iface(iface.clone())?;
-            self.import_iface_impl(iimpl.clone())?;
-        }
-        for (content_id, sigs) in contract.signatures {
-            // Do not bother if we can't import all the sigs
-            self.import_sigs_internal(content_id, sigs).ok();
+#[cfg(feature = "async")]
+impl Stock {
+    /// Async version of [`Inventory::import_contract`], which resolves the
+    /// witness heights with an async resolver. The contract is imported in
+    /// the same way as with the blocking resolvers.
+    pub async fn import_contract_async<R: AsyncResolveHeight>(
+        &mut self,
+        contract: Contract,
+        resolver: &mut R,
+    ) -> Result<validation::Status, Error>
+    where
+        R::Error: Clone + 'static,
+    {
+        let mut txids = contract.witness_txids();
+        if let Some(known) = self.contracts.get(&contract.contract_id()) {
+            txids.extend(known.witness_txids());
         }
-        contract.signatures = none!();
-
-        // TODO: Update existing contract state
-        let history = contract
-            .build_history(resolver)
-            .map_err(|err| Error::HeightResolver(Box::new(err)))?;
-        self.history.insert(id, history)?;
-
-        // TODO: Merge contracts
-        if self.contracts.insert(id, contract)?.is_some() {
-            status.add_warning(Warning::Custom(format!(
-                "contract {id::<0} has replaced previously known contract version",
-            )));
+        let mut fetch = WitnessFetch::new(txids);
+        while let Some(txid) = fetch.next_txid() {
+            let result = resolver.resolve_height_async(txid).await;
+            fetch.provide(txid, result);
         }
+        self._import_contract(contract, &mut fetch, false)
+    }
 
-        Ok(status)
+    /// Async version of [`Inventory::accept_transfer`].
+    pub async fn accept_transfer_async<R: AsyncResolveHeight>(
+        &mut self,
+        transfer: Transfer,
+        resolver: &mut R,
+    ) -> Result<validation::Status, Error>
+    where
+        R::Error: Clone + 'static,
+    {
+        self.import_contract_async(transfer.into_contract(), resolver).await
     }
 }
 
@@ -338,6 +1832,479 @@ pub enum InternalError {
     /// schema is absent - {0::<0}.
     NoSchema(SchemaId),
 
+    /// interface is absent - {0::<0}.
+    NoIface(IfaceId),
+
     /// interface {0::<0} is not implemented for the schema {1::<0}.
     NoIfaceImpl(IfaceId, SchemaId),
+
+    /// interface implementation is absent - {0::<0}.
+    NoImpl(ImplId),
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use bp::seals::txout::CloseMethod;
+    use bp::{Tx, Txid};
+    use commit_verify::Conceal;
+    use rgb::validation::{ResolveTx, TxResolverError};
+
+    use super::*;
+    use crate::containers::builder::test::{
+        issuable, issuance_contract, issuance_extension, issuance_schema, NoHeights,
+    };
+    use crate::fixtures::{self, iface, iimpl, outpoint, schema, GS_CONTRACT, OS_ASSETS};
+    use crate::interface::{ImplError, ImplErrors, NamedType};
+
+    struct NoTxs;
+
+    impl ResolveTx for NoTxs {
+        fn resolve_tx(&self, _txid: Txid) -> Result<Tx, TxResolverError> {
+            unreachable!("genesis-only contracts have no witness transactions")
+        }
+    }
+
+    fn validated(contract: Contract) -> Contract {
+        contract
+            .validate(&mut NoTxs)
+            .expect("genesis-only contract is valid")
+    }
+
+    #[test]
+    fn ambiguous_impl() {
+        let contract = issuable().issue_contract().unwrap();
+        let id = contract.contract_id();
+        let IfacePair { iface, iimpl } = contract.ifaces.values().next().unwrap().clone();
+        let iface_id = iface.iface_id();
+        let newer = IfaceImpl {
+            version: 1,
+            ..iimpl.clone()
+        };
+
+        let mut stock = Stock::new(Chain::Bitcoin);
+        stock.import_schema(contract.schema.clone()).unwrap();
+        stock.import_iface(iface).unwrap();
+        assert!(stock.import_iface_impl(iimpl.clone()).unwrap().warnings.is_empty());
+        assert_eq!(stock.import_iface_impl(newer.clone()).unwrap().warnings.len(), 1);
+        let history = contract.build_history(&mut NoHeights).unwrap();
+        stock.history.insert(id, history).unwrap();
+        stock.contracts.insert(id, contract).unwrap();
+
+        let candidates = bset! { iimpl.impl_id(), newer.impl_id() };
+        assert!(matches!(
+            stock.contract_iface(id, iface_id),
+            Err(ContractIfaceError::AmbiguousImpl { candidates: found }) if found == candidates
+        ));
+        assert_eq!(stock.contract_iface_by_impl(id, newer.impl_id()).unwrap().iface, newer);
+
+        stock.set_preferred_impl(id, newer.impl_id()).unwrap();
+        assert_eq!(stock.contract_iface(id, iface_id).unwrap().iface, newer);
+        stock.set_preferred_impl(id, iimpl.impl_id()).unwrap();
+        assert_eq!(stock.contract_iface(id, iface_id).unwrap().iface, iimpl);
+    }
+
+    #[test]
+    fn upgrade_impl() {
+        let contract = issuable().issue_contract().unwrap();
+        let id = contract.contract_id();
+        let IfacePair { iface, iimpl } = contract.ifaces.values().next().unwrap().clone();
+        let iface_id = iface.iface_id();
+
+        let mut stock = Stock::new(Chain::Bitcoin);
+        stock.import_schema(contract.schema.clone()).unwrap();
+        stock.import_iface(iface).unwrap();
+        stock.import_iface_impl(iimpl.clone()).unwrap();
+        let history = contract.build_history(&mut NoHeights).unwrap();
+        stock.history.insert(id, history).unwrap();
+        stock.contracts.insert(id, contract).unwrap();
+
+        assert!(matches!(
+            stock.upgrade_impl(id, iimpl.clone()),
+            Err(Error::IfaceImpl(IfaceImplError::NotNewer(_)))
+        ));
+
+        let mut conflicting = iimpl.clone();
+        conflicting.version = 1;
+        conflicting.global_state = Confined::try_from_iter(iimpl.global_state.iter().map(|nt| {
+            if nt.name == tn!("Nominal") {
+                NamedType::with(GS_CONTRACT, nt.name.clone())
+            } else {
+                nt.clone()
+            }
+        }))
+        .unwrap();
+        assert!(matches!(
+            stock.upgrade_impl(id, conflicting),
+            Err(Error::Forge(ForgeError::Semantics(ImplErrors(errors)))) if matches!(
+                errors.as_slice(),
+                [ImplError::SemanticMismatch { name, .. }] if *name == tn!("Nominal")
+            )
+        ));
+
+        let mut newer = iimpl;
+        newer.version = 1;
+        stock.upgrade_impl(id, newer.clone()).unwrap();
+        assert_eq!(stock.contract_iface(id, iface_id).unwrap().iface, newer);
+        assert_eq!(stock.contracts.get(&id).unwrap().ifaces.get(&iface_id).unwrap().iimpl, newer);
+    }
+
+    #[test]
+    fn resolve_input() {
+        let contract = issuable()
+            .add_fungible_state("Assets", outpoint(0), 50)
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(1), 10)
+            .unwrap()
+            .issue_contract()
+            .unwrap();
+        let id = contract.contract_id();
+        let mut stock = Stock::new(Chain::Bitcoin);
+        stock.contracts.insert(id, contract.clone()).unwrap();
+
+        let opout = stock.resolve_input(id, outpoint(1), OS_ASSETS).unwrap();
+        let transition = TransitionBuilder::with(iface(), schema(), iimpl(), "Transfer")
+            .unwrap()
+            .add_checked_input(opout, &contract)
+            .unwrap()
+            .complete_with_change("Assets", outpoint(2))
+            .unwrap();
+        assert!(transition.inputs.contains_key(&contract.genesis.id()));
+
+        let Err(BuilderLookupError::AmbiguousInput { candidates }) =
+            stock.resolve_input(id, outpoint(0), OS_ASSETS)
+        else {
+            panic!("two assignments to the same outpoint must be ambiguous");
+        };
+        assert_eq!(candidates.len(), 2);
+        assert!(matches!(
+            stock.resolve_input(id, outpoint(3), OS_ASSETS),
+            Err(BuilderLookupError::UnknownInput { .. })
+        ));
+
+        let unknown = Opout::new(contract.genesis.id(), OS_ASSETS, 3);
+        assert_eq!(
+            TransitionBuilder::with(iface(), schema(), iimpl(), "Transfer")
+                .unwrap()
+                .add_checked_input(unknown, &contract)
+                .unwrap_err(),
+            BuilderError::UnknownInput(unknown)
+        );
+    }
+
+    #[test]
+    fn export_contract_delta() {
+        let contract = issuance_contract(issuance_schema(), true);
+        let extension = issuance_extension(&contract).unwrap();
+        let id = contract.contract_id();
+        let mut stock = Stock::new(Chain::Bitcoin);
+        stock.contracts.insert(id, contract.clone()).unwrap();
+
+        let (mut exported, cursor) = stock
+            .export_contract_delta(id, ExportCursor::new(id))
+            .unwrap();
+        assert_eq!(exported.genesis, contract.genesis);
+        assert_eq!((cursor.bundles(), cursor.extensions()), (0, 0));
+        assert_eq!(cursor, ExportCursor::with_contract(&contract));
+
+        stock.contracts.get_mut(&id).unwrap().extensions.push(extension.clone()).unwrap();
+        let (delta, next) = stock.export_contract_delta(id, cursor).unwrap();
+        assert_eq!(delta.extensions.len(), 1);
+        assert_eq!(delta.extensions[0].id(), extension.id());
+        assert_eq!(next.extensions(), 1);
+        let (empty, last) = stock.export_contract_delta(id, next).unwrap();
+        assert!(empty.extensions.is_empty());
+        assert_eq!(last, next);
+
+        exported.merge(delta).unwrap();
+        assert_eq!(ExportCursor::with_contract(&exported), next);
+        let data = next.to_strict_serialized::<{ u16::MAX as usize }>().unwrap();
+        let decoded = ExportCursor::from_strict_serialized::<{ u16::MAX as usize }>(data).unwrap();
+        assert_eq!(decoded, next);
+
+        let mut tampered = next;
+        tampered.extensions = 0;
+        assert_eq!(
+            stock.export_contract_delta(id, tampered).unwrap_err(),
+            ExportError::CursorMismatch
+        );
+        let other = ExportCursor::new(strict_dumb!());
+        assert_eq!(
+            stock.export_contract_delta(id, other).unwrap_err(),
+            ExportError::ContractMismatch(other.contract_id())
+        );
+    }
+
+    #[test]
+    fn integrity() {
+        let contract = issuable().issue_contract().unwrap();
+        let id = contract.contract_id();
+        let schema_id = contract.schema_id();
+        let IfacePair { iface, iimpl } = contract.ifaces.values().next().unwrap().clone();
+        let iface_id = iface.iface_id();
+        let impl_id = iimpl.impl_id();
+
+        let mut stock = Stock::new(Chain::Bitcoin);
+        stock.import_schema(contract.schema.clone()).unwrap();
+        stock.import_iface(iface).unwrap();
+        stock.import_iface_impl(iimpl.clone()).unwrap();
+        let history = contract.build_history(&mut NoHeights).unwrap();
+        stock.history.insert(id, history).unwrap();
+        stock.contracts.insert(id, contract).unwrap();
+        assert!(stock.check_integrity().is_consistent());
+
+        let unknown: ContractId = strict_dumb!();
+        let history = remove_item(&mut stock.history, &id).unwrap();
+        stock.history.insert(unknown, history).unwrap();
+        remove_item(&mut stock.schemata, &schema_id).unwrap();
+        let preferred = tiny_bmap! { iface_id => impl_id };
+        stock.preferred_impls.insert(unknown, preferred).unwrap();
+        let mut orphan = iimpl;
+        orphan.version = 1;
+        orphan.iface_id = strict_dumb!();
+        let orphan_id = orphan.impl_id();
+        stock.alt_impls.insert(orphan_id, orphan).unwrap();
+
+        let data_loss = IntegrityIssue::IfaceAbsent(orphan_id, strict_dumb!());
+        let report = stock.check_integrity();
+        assert_eq!(report.issues, vec![
+            data_loss.clone(),
+            IntegrityIssue::SchemaAbsent(id, schema_id),
+            IntegrityIssue::HistoryInvalid(id),
+            IntegrityIssue::StaleHistory(unknown),
+            IntegrityIssue::StalePreferredImpl(unknown, impl_id),
+        ]);
+        assert_eq!(report.repairable().count(), 4);
+
+        let report = stock.repair(&mut NoHeights).unwrap();
+        assert_eq!(report.issues, vec![data_loss]);
+        assert_eq!(report.data_loss().count(), 1);
+        assert!(stock.schemata.contains_key(&schema_id));
+        assert!(stock.history.contains_key(&id));
+        assert!(stock.preferred_impls.is_empty());
+    }
+
+    #[test]
+    fn import_many() {
+        let contract = validated(fixtures::issue(&[(outpoint(0), 10)]));
+        let other = validated(fixtures::issue(&[(outpoint(1), 10)]));
+        let testnet = issuable()
+            .set_chain(Chain::Testnet3)
+            .issue_contract()
+            .unwrap();
+
+        let mut stock = Stock::new(Chain::Bitcoin);
+        assert!(matches!(
+            stock.import_many([contract.clone(), testnet], &mut NoHeights),
+            Err(Error::ChainMismatch { .. })
+        ));
+        assert!(stock.contracts.is_empty());
+        assert!(stock.schemata.is_empty());
+
+        let statuses = stock
+            .import_many([contract.clone(), other, contract], &mut NoHeights)
+            .unwrap();
+        assert_eq!(statuses.len(), 3);
+        assert!(statuses[0].warnings.is_empty());
+        assert_eq!(statuses[2].warnings.len(), 1);
+        assert_eq!(stock.contracts.len(), 2);
+        assert_eq!(stock.schemata.len(), 1);
+        assert!(stock.check_integrity().is_consistent());
+    }
+
+    #[test]
+    fn import_many_statuses() {
+        let contract = validated(fixtures::issue(&[(outpoint(0), 10)]));
+        let newer = IfaceImpl {
+            version: 1,
+            ..iimpl()
+        };
+        let mut stock = Stock::new(Chain::Bitcoin);
+        stock.import_schema(schema()).unwrap();
+        stock.import_iface(iface()).unwrap();
+        stock.import_iface_impl(newer.clone()).unwrap();
+
+        let statuses = stock.import_many([contract], &mut NoHeights).unwrap();
+        let warnings = statuses[0]
+            .warnings
+            .iter()
+            .map(|warning| warning.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].starts_with("schema "));
+        assert!(warnings[1].starts_with("interface "));
+        assert!(warnings[2].contains(&newer.impl_id().to_string()));
+        assert!(warnings[2].contains("will require explicit choice of the implementation"));
+    }
+
+    #[test]
+    fn import_many_atomic() {
+        let contract = validated(fixtures::issue(&[(outpoint(0), 10)]));
+        let mut broken = validated(fixtures::issue(&[(outpoint(1), 10)]));
+        let unknown: SchemaId = strict_dumb!();
+        broken.ifaces.get_mut(&iface().iface_id()).unwrap().iimpl.schema_id = unknown;
+
+        let mut stock = Stock::new(Chain::Bitcoin);
+        assert!(matches!(
+            stock.import_many([contract, broken], &mut NoHeights),
+            Err(Error::IfaceImpl(IfaceImplError::UnknownSchema(id))) if id == unknown
+        ));
+        assert!(stock.contracts.is_empty());
+        assert!(stock.history.is_empty());
+        assert!(stock.schemata.is_empty());
+        assert!(stock.ifaces.is_empty());
+    }
+
+    #[test]
+    fn iimpl_bindle_without_iface() {
+        use std::str::FromStr;
+
+        let armored = iimpl().bindle().to_string();
+        let parse = || Bindle::<IfaceImpl>::from_str(&armored).unwrap();
+        assert!(parse().is_consistent());
+        assert_eq!(parse().unbindle(), iimpl());
+
+        let mut stock = Stock::new(Chain::Bitcoin);
+        stock.import_schema(schema()).unwrap();
+        assert!(matches!(
+            stock.import_iface_impl(parse()),
+            Err(Error::IfaceImpl(IfaceImplError::UnknownIface(id))) if id == iface().iface_id()
+        ));
+        stock.import_iface(iface()).unwrap();
+        stock.import_iface_impl(parse()).unwrap();
+        assert_eq!(stock.find_impl(schema().schema_id(), iimpl().impl_id()), Some(&iimpl()));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn import_contract_async() {
+        use crate::fixtures::block_on;
+        use crate::resolvers::Blocking;
+
+        let contract = validated(fixtures::issue(&[(outpoint(0), 10)]));
+        let id = contract.contract_id();
+        let mut stock = Stock::new(Chain::Bitcoin);
+        let status = block_on(stock.import_contract_async(contract, &mut Blocking(NoHeights)))
+            .unwrap();
+        assert!(status.warnings.is_empty());
+        assert!(stock.contracts.contains_key(&id));
+        assert!(stock.history.contains_key(&id));
+    }
+
+    #[test]
+    fn transfer() {
+        let contract =
+            validated(fixtures::issue(&[(outpoint(0), 60), (outpoint(1), 30), (outpoint(2), 10)]));
+        let other = validated(fixtures::issue(&[(outpoint(0), 5)]));
+        let id = contract.contract_id();
+        let mut stock = Stock::new(Chain::Bitcoin);
+        stock.import_many([contract.clone(), other.clone()], &mut NoHeights).unwrap();
+
+        let change = GraphSeal::from(VoutSeal::new_tapret(1));
+        let blinded = GraphSeal::new(CloseMethod::TapretFirst, outpoint(4).txid, 4).conceal();
+        let prepared = stock
+            .transfer(id, Beneficiary::ConcealedUtxo(blinded), 80, change, true)
+            .unwrap();
+        assert_eq!(prepared.inputs, bset! { outpoint(0), outpoint(1) });
+        assert!(prepared.transition.inputs.contains_key(&contract.genesis.id()));
+        let Some(TypedAssigns::Fungible(assigns)) = prepared.transition.assignments.get(&OS_ASSETS)
+        else {
+            panic!("transfer must assign fungible state");
+        };
+        let assigned = assigns
+            .iter()
+            .map(|assign| match assign {
+                Assign::ConfidentialSeal { seal, state } if *seal == blinded => {
+                    (None, state.value.as_u64())
+                }
+                Assign::Revealed { seal, state } => (Some(*seal), state.value.as_u64()),
+                _ => panic!("unexpected assignment"),
+            })
+            .collect::<BTreeSet<_>>();
+        assert_eq!(assigned, bset! { (None, 80), (Some(change), 10) });
+
+        let blank = prepared.blanks.get(&other.contract_id()).expect("blank must be composed");
+        assert!(blank.inputs.contains_key(&other.genesis.id()));
+        assert_eq!(prepared.blanks.len(), 1);
+
+        let beneficiary = Beneficiary::WitnessVout(VoutSeal::new_tapret(0));
+        let prepared = stock.transfer(id, beneficiary, 10, change, false).unwrap();
+        assert_eq!(prepared.inputs, bset! { outpoint(0) });
+        assert!(prepared.blanks.is_empty());
+        assert!(matches!(
+            stock.transfer(id, beneficiary, 101, change, true),
+            Err(TransferError::Builder(BuilderError::InsufficientInputs { deficit: 1, .. }))
+        ));
+    }
+
+    #[test]
+    fn transfer_conservation() {
+        let contract = validated(fixtures::issue(&[(outpoint(0), 60), (outpoint(1), 40)]));
+        let id = contract.contract_id();
+        let genesis_id = contract.genesis.id();
+        let mut stock = Stock::new(Chain::Bitcoin);
+        stock.import_contract(contract, &mut NoHeights).unwrap();
+        let builder = || stock.transition_builder(id, "RGB20", "Transfer").unwrap();
+        let complete = |builder: TransitionBuilder| {
+            builder.complete_transition().map_err(TransferError::from)
+        };
+
+        let known = Opout::new(genesis_id, OS_ASSETS, 0);
+        let unknown = Opout::new(genesis_id, OS_ASSETS, 1);
+        let spend = builder()
+            .add_fungible_input(known, 60)
+            .unwrap()
+            .add_input(unknown)
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(2), 100)
+            .unwrap();
+        assert!(matches!(
+            complete(spend),
+            Err(TransferError::Builder(BuilderError::UnknownInputAmount(opout))) if opout == unknown
+        ));
+
+        let under = builder()
+            .add_fungible_input(known, 60)
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(2), 50)
+            .unwrap();
+        assert!(matches!(complete(under), Err(TransferError::ValueNotConserved {
+            inputs: 60,
+            outputs: 50
+        })));
+        let over = builder()
+            .add_fungible_input(known, 60)
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(2), 70)
+            .unwrap();
+        assert!(matches!(complete(over), Err(TransferError::ValueNotConserved {
+            inputs: 60,
+            outputs: 70
+        })));
+    }
+
+    #[test]
+    #[ignore = "benchmark"]
+    fn import_many_bench() {
+        const CONTRACTS: u32 = 250;
+        const ALLOCATIONS: u32 = 200;
+
+        let contracts = (0..CONTRACTS)
+            .map(|no| {
+                let allocations = (0..ALLOCATIONS)
+                    .map(|vout| (outpoint(no * ALLOCATIONS + vout), 1))
+                    .collect::<Vec<_>>();
+                validated(fixtures::issue(&allocations))
+            })
+            .collect::<Vec<_>>();
+
+        let mut stock = Stock::new(Chain::Bitcoin);
+        let start = Instant::now();
+        let statuses = stock.import_many(contracts, &mut NoHeights).unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(statuses.len(), CONTRACTS as usize);
+        assert_eq!(stock.contracts.len(), CONTRACTS as usize);
+        assert!(elapsed < Duration::from_secs(10), "import took {elapsed:?}");
+    }
 }
@@ -0,0 +1,264 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! File system storage of schemata, interfaces and interface implementations.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use rgb::{SchemaId, SubSchema};
+use strict_encoding::{StrictSerialize, TypeName};
+
+use crate::containers::{Bindle, BindleContent, ContentId, LoadError};
+use crate::interface::{Iface, IfaceId, IfaceImpl};
+use crate::resolvers::SchemaProvider;
+
+/// Problem with a specific file detected during the directory scan.
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[display(doc_comments)]
+pub enum ScanIssue {
+    /// unable to load the file: {0}
+    Load(LoadError),
+
+    /// id stored in the file doesn't match its content.
+    IdMismatch,
+
+    /// file contains {0:?} which is already known with a different content.
+    Conflict(ContentId),
+}
+
+/// Report on a directory scan.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ScanReport {
+    /// Ids of data which were newly added to the index during the scan.
+    pub added: BTreeSet<ContentId>,
+    /// Files which failed to load or conflicting with the already known data.
+    pub issues: Vec<(PathBuf, ScanIssue)>,
+}
+
+/// Index over schemata, interfaces and interface implementations stored in
+/// the bindle files inside some directory.
+///
+/// The store does not watch the directory for changes; to index files added
+/// after the store was opened call [`SchemaStore::rescan`].
+#[derive(Clone, Debug)]
+pub struct SchemaStore {
+    dir: PathBuf,
+    schemata: BTreeMap<SchemaId, SubSchema>,
+    ifaces: BTreeMap<IfaceId, Iface>,
+    iface_names: BTreeMap<TypeName, BTreeSet<IfaceId>>,
+    iimpls: BTreeMap<(SchemaId, IfaceId), IfaceImpl>,
+}
+
+impl SchemaStore {
+    /// Opens the store and scans the directory for the bindle files.
+    ///
+    /// Files failing to decode do not abort the scan and are listed in the
+    /// returned report.
+    pub fn open(dir: impl AsRef<Path>) -> Result<(Self, ScanReport), io::Error> {
+        let mut store = SchemaStore {
+            dir: dir.as_ref().to_owned(),
+            schemata: none!(),
+            ifaces: none!(),
+            iface_names: none!(),
+            iimpls: none!(),
+        };
+        let report = store.rescan()?;
+        Ok((store, report))
+    }
+
+    /// Directory indexed by the store.
+    pub fn dir(&self) -> &Path { &self.dir }
+
+    /// Scans the directory for the bindle files not yet known to the store.
+    pub fn rescan(&mut self) -> Result<ScanReport, io::Error> {
+        let mut report = ScanReport::default();
+        let mut paths = fs::read_dir(&self.dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.sort();
+        for path in paths.into_iter().filter(|path| path.is_file()) {
+            match self.index_file(&path) {
+                Ok(Some(id)) => {
+                    report.added.insert(id);
+                }
+                Ok(None) => {}
+                Err(issue) => report.issues.push((path, issue)),
+            }
+        }
+        Ok(report)
+    }
+
+    pub fn schema(&self, schema_id: SchemaId) -> Option<&SubSchema> {
+        self.schemata.get(&schema_id)
+    }
+
+    pub fn iface_by_id(&self, iface_id: IfaceId) -> Option<&Iface> { self.ifaces.get(&iface_id) }
+
+    /// Returns interfaces having the provided name. There might be multiple
+    /// interfaces (for instance, of different versions) under the same name.
+    pub fn iface(&self, name: &TypeName) -> Vec<&Iface> {
+        self.iface_names
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.ifaces.get(id))
+            .collect()
+    }
+
+    pub fn iimpl_for(&self, schema_id: SchemaId, iface_id: IfaceId) -> Option<&IfaceImpl> {
+        self.iimpls.get(&(schema_id, iface_id))
+    }
+
+    /// Returns `Ok(None)` for files which are not schema, interface or
+    /// interface implementation bindles, or which content is already known.
+    fn index_file(&mut self, path: &Path) -> Result<Option<ContentId>, ScanIssue> {
+        let mut magic = [0u8; 7];
+        let mut file = fs::File::open(path).map_err(|err| ScanIssue::Load(err.into()))?;
+        if file.read_exact(&mut magic).is_err() || magic[..3] != *b"RGB" {
+            return Ok(None);
+        }
+        let magic = &magic[3..];
+        let (id, added) = if magic == SubSchema::MAGIC {
+            let schema = load::<SubSchema>(path)?;
+            let id = schema.schema_id();
+            (ContentId::Schema(id), insert(&mut self.schemata, id, schema, ContentId::Schema(id))?)
+        } else if magic == Iface::MAGIC {
+            let iface = load::<Iface>(path)?;
+            let id = iface.iface_id();
+            let name = iface.name.clone();
+            let added = insert(&mut self.ifaces, id, iface, ContentId::Iface(id))?;
+            self.iface_names.entry(name).or_default().insert(id);
+            (ContentId::Iface(id), added)
+        } else if magic == IfaceImpl::MAGIC {
+            let iimpl = load::<IfaceImpl>(path)?;
+            let id = ContentId::IfaceImpl(iimpl.impl_id());
+            let key = (iimpl.schema_id, iimpl.iface_id);
+            (id.clone(), insert(&mut self.iimpls, key, iimpl, id)?)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(id).filter(|_| added))
+    }
+}
+
+impl SchemaProvider for SchemaStore {
+    fn schema(&self, schema_id: SchemaId) -> Option<&SubSchema> {
+        SchemaStore::schema(self, schema_id)
+    }
+}
+
+fn load<C: BindleContent>(path: &Path) -> Result<C, ScanIssue>
+where C::Id: PartialEq {
+    let bindle = Bindle::<C>::load(path).map_err(ScanIssue::Load)?;
+    if !bindle.is_consistent() {
+        return Err(ScanIssue::IdMismatch);
+    }
+    Ok(bindle.unbindle())
+}
+
+/// Returns whether the content was added to the index.
+fn insert<K: Ord, C: StrictSerialize>(
+    index: &mut BTreeMap<K, C>,
+    key: K,
+    content: C,
+    id: ContentId,
+) -> Result<bool, ScanIssue> {
+    match index.get(&key) {
+        Some(known)
+            if known.to_strict_serialized::<{ usize::MAX }>().ok() !=
+                content.to_strict_serialized::<{ usize::MAX }>().ok() =>
+        {
+            Err(ScanIssue::Conflict(id))
+        }
+        Some(_) => Ok(false),
+        None => {
+            index.insert(key, content);
+            Ok(true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::{self, test_dir};
+
+    #[test]
+    fn open_rescan() {
+        let dir = test_dir("schema-store");
+        let (schema, iface, iimpl) = (fixtures::schema(), fixtures::iface(), fixtures::iimpl());
+        schema.clone().bindle().save(dir.join("schema.rgb")).unwrap();
+        iface.clone().bindle().save(dir.join("iface.rgb")).unwrap();
+        fs::write(dir.join("readme.txt"), b"not a bindle").unwrap();
+
+        let (mut store, report) = SchemaStore::open(&dir).unwrap();
+        assert_eq!(store.dir(), dir);
+        assert_eq!(report.added, bset! {
+            ContentId::Schema(schema.schema_id()),
+            ContentId::Iface(iface.iface_id())
+        });
+        assert!(report.issues.is_empty());
+        assert_eq!(store.schema(schema.schema_id()), Some(&schema));
+        assert_eq!(SchemaProvider::schema(&store, schema.schema_id()), Some(&schema));
+        assert_eq!(store.iface_by_id(iface.iface_id()), Some(&iface));
+        assert_eq!(store.iface(&iface.name), vec![&iface]);
+        assert!(store.iface(&tn!("Unknown")).is_empty());
+        assert_eq!(store.iimpl_for(iimpl.schema_id, iimpl.iface_id), None);
+
+        iimpl.clone().bindle().save(dir.join("iimpl.rgb")).unwrap();
+        let report = store.rescan().unwrap();
+        assert_eq!(report.added, bset! { ContentId::IfaceImpl(iimpl.impl_id()) });
+        assert!(report.issues.is_empty());
+        assert_eq!(store.iimpl_for(iimpl.schema_id, iimpl.iface_id), Some(&iimpl));
+
+        assert_eq!(store.rescan().unwrap(), ScanReport::default());
+    }
+
+    #[test]
+    fn scan_issues() {
+        let dir = test_dir("schema-store-issues");
+        let iimpl = fixtures::iimpl();
+        let mut update = iimpl.clone();
+        update.version += 1;
+        iimpl.bindle().save(dir.join("a.rgb")).unwrap();
+        update.clone().bindle().save(dir.join("b.rgb")).unwrap();
+        let mut broken = b"RGB".to_vec();
+        broken.extend(SubSchema::MAGIC);
+        broken.extend([0xFF; 8]);
+        fs::write(dir.join("c.rgb"), broken).unwrap();
+
+        let (store, report) = SchemaStore::open(&dir).unwrap();
+        assert_eq!(report.added.len(), 1);
+        assert_eq!(report.issues.len(), 2);
+        assert_eq!(
+            report.issues[0],
+            (dir.join("b.rgb"), ScanIssue::Conflict(ContentId::IfaceImpl(update.impl_id())))
+        );
+        assert_eq!(report.issues[1].0, dir.join("c.rgb"));
+        assert!(matches!(report.issues[1].1, ScanIssue::Load(LoadError::Migration(_))));
+        assert_eq!(store.iimpl_for(update.schema_id, update.iface_id).unwrap().version, 0);
+
+        assert!(SchemaStore::open(dir.join("missing")).is_err());
+    }
+}
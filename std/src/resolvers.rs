@@ -19,10 +19,151 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use bp::Txid;
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "async")]
+use std::future::{self, Future};
+#[cfg(feature = "async")]
+use std::pin::Pin;
+
+use bp::{Tx, Txid};
+use rgb::validation::{ResolveTx, TxResolverError};
+use rgb::{OpId, OpRef, SchemaId, SubSchema};
 
 pub trait ResolveHeight {
     type Error: std::error::Error;
 
     fn resolve_height(&mut self, txid: Txid) -> Result<u32, Self::Error>;
 }
+
+/// Provider of the schemata trusted by the host, which are used instead of
+/// the schemata embedded into the containers.
+pub trait SchemaProvider {
+    fn schema(&self, schema_id: SchemaId) -> Option<&SubSchema>;
+}
+
+impl SchemaProvider for BTreeMap<SchemaId, SubSchema> {
+    fn schema(&self, schema_id: SchemaId) -> Option<&SubSchema> { self.get(&schema_id) }
+}
+
+/// Provider of the contract operations by their ids.
+pub trait OperationProvider {
+    fn operation(&self, opid: OpId) -> Option<OpRef>;
+}
+
+/// Errors of the data resolution with [`WitnessFetch`].
+#[derive(Clone, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum FetchError<E: std::error::Error> {
+    /// witness transaction {0} was not fetched before processing the data.
+    NotFetched(Txid),
+
+    /// {0}
+    Resolver(E),
+}
+
+/// Sans-io state of resolving the witness transactions of a consignment, or
+/// their heights, before the data are processed.
+///
+/// The same processing code is used with blocking and async resolvers: the
+/// driver takes the witnesses with [`Self::next_txid`], resolves them in its
+/// own way and provides the results with [`Self::provide`]. Once there are no
+/// more witnesses to resolve, the fetched data are used as a resolver for the
+/// data processing. Witnesses which were not fetched are reported as unknown
+/// by the resolver.
+#[derive(Clone, Debug)]
+pub struct WitnessFetch<T, E> {
+    pending: BTreeSet<Txid>,
+    resolved: BTreeMap<Txid, Result<T, E>>,
+}
+
+impl<T: Clone, E: Clone> WitnessFetch<T, E> {
+    /// Starts fetching data for the witness transactions.
+    pub fn new(txids: impl IntoIterator<Item = Txid>) -> Self {
+        WitnessFetch {
+            pending: txids.into_iter().collect(),
+            resolved: none!(),
+        }
+    }
+
+    /// Returns next witness transaction which must be resolved, or `None` if
+    /// all of them are already resolved.
+    pub fn next_txid(&self) -> Option<Txid> { self.pending.first().copied() }
+
+    /// Provides result of resolving the witness transaction.
+    pub fn provide(&mut self, txid: Txid, result: Result<T, E>) {
+        self.pending.remove(&txid);
+        self.resolved.insert(txid, result);
+    }
+
+    /// Pumps the fetch with a blocking resolver until all the witnesses are
+    /// resolved.
+    pub fn resolve_with(mut self, mut resolve: impl FnMut(Txid) -> Result<T, E>) -> Self {
+        while let Some(txid) = self.next_txid() {
+            let result = resolve(txid);
+            self.provide(txid, result);
+        }
+        self
+    }
+
+    fn get(&self, txid: Txid) -> Option<Result<T, E>> { self.resolved.get(&txid).cloned() }
+}
+
+impl ResolveTx for WitnessFetch<Tx, TxResolverError> {
+    fn resolve_tx(&self, txid: Txid) -> Result<Tx, TxResolverError> {
+        self.get(txid).unwrap_or(Err(TxResolverError::Unknown(txid)))
+    }
+}
+
+impl<E: std::error::Error + Clone> ResolveHeight for WitnessFetch<u32, E> {
+    type Error = FetchError<E>;
+
+    fn resolve_height(&mut self, txid: Txid) -> Result<u32, Self::Error> {
+        self.get(txid)
+            .ok_or(FetchError::NotFetched(txid))?
+            .map_err(FetchError::Resolver)
+    }
+}
+
+/// Future returned by the async resolvers.
+#[cfg(feature = "async")]
+pub type ResolveFuture<'a, T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'a>>;
+
+/// Async counterpart of [`ResolveTx`].
+#[cfg(feature = "async")]
+pub trait AsyncResolveTx {
+    fn resolve_tx_async(&self, txid: Txid) -> ResolveFuture<'_, Tx, TxResolverError>;
+}
+
+/// Async counterpart of [`ResolveHeight`].
+#[cfg(feature = "async")]
+pub trait AsyncResolveHeight {
+    type Error: std::error::Error;
+
+    fn resolve_height_async(&mut self, txid: Txid) -> ResolveFuture<'_, u32, Self::Error>;
+}
+
+/// Adapter allowing to use a blocking resolver where an async one is
+/// required. The resolver is called directly from the future, thus it should
+/// be used only with resolvers which don't block for long, like the ones
+/// working with local data.
+#[cfg(feature = "async")]
+#[derive(Clone, Debug, Default)]
+pub struct Blocking<R>(pub R);
+
+#[cfg(feature = "async")]
+impl<R: ResolveTx> AsyncResolveTx for Blocking<R> {
+    fn resolve_tx_async(&self, txid: Txid) -> ResolveFuture<'_, Tx, TxResolverError> {
+        Box::pin(future::ready(self.0.resolve_tx(txid)))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: ResolveHeight> AsyncResolveHeight for Blocking<R>
+where R::Error: Send
+{
+    type Error = R::Error;
+
+    fn resolve_height_async(&mut self, txid: Txid) -> ResolveFuture<'_, u32, Self::Error> {
+        Box::pin(future::ready(self.0.resolve_height(txid)))
+    }
+}
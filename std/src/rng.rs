@@ -0,0 +1,133 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Source of randomness for the blinding factors generated by the library.
+//!
+//! By default the blinding factors are produced by `thread_rng`. Tests may
+//! replace the generator for the current thread with a deterministic one
+//! using [`set_override`], such that the issued contracts and operations are
+//! reproducible.
+
+use std::cell::RefCell;
+
+use bp::secp256k1::rand::{self, thread_rng, RngCore};
+
+thread_local! {
+    static OVERRIDE: RefCell<Option<Box<dyn RngCore>>> = RefCell::new(None);
+}
+
+/// Replaces the random number generator used by the library in the current
+/// thread until [`clear_override`] is called.
+pub fn set_override(rng: impl RngCore + 'static) {
+    OVERRIDE.with(|cell| *cell.borrow_mut() = Some(Box::new(rng)));
+}
+
+/// Restores use of `thread_rng` by the library in the current thread.
+pub fn clear_override() { OVERRIDE.with(|cell| *cell.borrow_mut() = None); }
+
+/// Random number generator used by the library for all blinding factors. It
+/// delegates to the generator set with [`set_override`] for the current
+/// thread, or to `thread_rng` otherwise.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct BlindingRng;
+
+impl BlindingRng {
+    fn with<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        OVERRIDE.with(|cell| match cell.borrow_mut().as_mut() {
+            Some(rng) => f(rng.as_mut()),
+            None => f(&mut thread_rng()),
+        })
+    }
+}
+
+impl RngCore for BlindingRng {
+    fn next_u32(&mut self) -> u32 { Self::with(|rng| rng.next_u32()) }
+
+    fn next_u64(&mut self) -> u64 { Self::with(|rng| rng.next_u64()) }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) { Self::with(|rng| rng.fill_bytes(dest)) }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        Self::with(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bp::Chain;
+    use rgb::ContractId;
+
+    use super::*;
+    use crate::containers::ContractBuilder;
+    use crate::fixtures::{iface, iimpl, outpoint, schema};
+    use crate::stl::{ContractText, Nominal, Precision};
+
+    struct CounterRng(u64);
+
+    impl RngCore for CounterRng {
+        fn next_u32(&mut self) -> u32 { self.next_u64() as u32 }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 += 1;
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                let bytes = self.next_u64().to_le_bytes();
+                chunk.copy_from_slice(&bytes[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    fn issue() -> ContractId {
+        ContractBuilder::with(iface(), schema(), iimpl())
+            .unwrap()
+            .set_chain(Chain::Bitcoin)
+            .add_global_state("Nominal", Nominal::new("TEST", "Test asset", Precision::CentiMicro))
+            .unwrap()
+            .add_global_state("ContractText", ContractText::default())
+            .unwrap()
+            .add_fungible_state("Assets", outpoint(0), 100)
+            .unwrap()
+            .issue_contract()
+            .unwrap()
+            .contract_id()
+    }
+
+    fn issue_with_override() -> ContractId {
+        set_override(CounterRng(0));
+        let contract_id = issue();
+        clear_override();
+        contract_id
+    }
+
+    #[test]
+    fn deterministic_override() {
+        assert_eq!(issue_with_override(), issue_with_override());
+        assert_ne!(issue(), issue());
+    }
+}
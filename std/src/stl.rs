@@ -22,11 +22,11 @@
 #![allow(unused_braces)] // caused by rustc unable to understand strict_dumb
 
 use std::fmt;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
 
 use amplify::ascii::AsciiString;
-use amplify::confinement::{Confined, SmallString};
+use amplify::confinement::{self, Confined, SmallString, TinyBlob};
 use amplify::IoError;
 use strict_encoding::{InvalidIdent, StrictDeserialize, StrictDumb, StrictSerialize};
 use strict_types::typelib::{LibBuilder, TranslateError};
@@ -67,6 +67,54 @@ pub enum Precision {
 impl StrictSerialize for Precision {}
 impl StrictDeserialize for Precision {}
 
+impl Precision {
+    /// Number of digits in the fractional part of the amounts.
+    pub const fn decimals(self) -> u8 { self as u8 }
+
+    /// Parses decimal amount, like `12.5`, into the number of atomic units of
+    /// an asset with this precision.
+    pub fn parse_amount(self, s: &str) -> Result<u64, AmountParseError> {
+        let invalid = || AmountParseError::InvalidNumber(s.to_owned());
+        let (int, fract) = match s.split_once('.') {
+            Some((int, fract)) if !fract.is_empty() => (int, fract),
+            Some(_) => return Err(invalid()),
+            None => (s, ""),
+        };
+        if int.is_empty() || !int.bytes().chain(fract.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let fract = fract.trim_end_matches('0');
+        let decimals = self.decimals() as u32;
+        let Some(scale) = decimals.checked_sub(fract.len() as u32) else {
+            return Err(AmountParseError::TooPrecise(s.to_owned()));
+        };
+        let fract = match fract {
+            "" => 0,
+            fract => fract.parse::<u64>().expect("at most 18 digits") * 10u64.pow(scale),
+        };
+        int.parse::<u64>()
+            .ok()
+            .and_then(|int| int.checked_mul(10u64.pow(decimals)))
+            .and_then(|int| int.checked_add(fract))
+            .ok_or_else(|| AmountParseError::Overflow(s.to_owned()))
+    }
+}
+
+/// Errors parsing decimal amounts with [`Precision::parse_amount`].
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum AmountParseError {
+    /// '{0}' is not a valid decimal amount.
+    InvalidNumber(String),
+
+    /// amount '{0}' has more fractional digits than the asset precision
+    /// allows.
+    TooPrecise(String),
+
+    /// amount '{0}' exceeds the maximal value of 64-bit integer.
+    Overflow(String),
+}
+
 #[derive(Wrapper, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, From)]
 #[wrapper(Deref, Display)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
@@ -77,6 +125,8 @@ impl StrictDeserialize for Precision {}
     serde(crate = "serde_crate", transparent)
 )]
 pub struct Ticker(Confined<AsciiString, 1, 8>);
+impl StrictSerialize for Ticker {}
+impl StrictDeserialize for Ticker {}
 
 impl FromStr for Ticker {
     type Err = InvalidIdent;
@@ -136,6 +186,8 @@ impl Debug for Ticker {
     serde(crate = "serde_crate", transparent)
 )]
 pub struct ContractName(Confined<String, 5, 40>);
+impl StrictSerialize for ContractName {}
+impl StrictDeserialize for ContractName {}
 
 impl StrictDumb for ContractName {
     fn strict_dumb() -> Self { Self(Confined::try_from(s!("Dumb contract name")).unwrap()) }
@@ -251,6 +303,11 @@ impl Nominal {
             precision,
         })
     }
+
+    pub fn ticker(&self) -> &Ticker { &self.ticker }
+    pub fn name(&self) -> &ContractName { &self.name }
+    pub fn details(&self) -> Option<&ContractDetails> { self.details.as_ref() }
+    pub fn precision(&self) -> Precision { self.precision }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
@@ -265,6 +322,224 @@ pub struct ContractText(SmallString);
 impl StrictSerialize for ContractText {}
 impl StrictDeserialize for ContractText {}
 
+impl TryFrom<String> for ContractText {
+    type Error = confinement::Error;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        SmallString::try_from(text).map(Self)
+    }
+}
+
+impl ContractText {
+    pub fn as_str(&self) -> &str { self.0.as_str() }
+}
+
+/// Detached signature over the contract terms, made by the contract issuer.
+#[derive(Wrapper, Clone, Eq, PartialEq, Debug, Default, From)]
+#[wrapper(Deref)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_CONTRACT)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", transparent)
+)]
+pub struct TermsSig(TinyBlob);
+
+/// Contract terms document, optionally signed by the contract issuer.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[derive(StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_CONTRACT)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct ContractTerms {
+    pub text: ContractText,
+    pub sig: Option<TermsSig>,
+}
+impl StrictSerialize for ContractTerms {}
+impl StrictDeserialize for ContractTerms {}
+
+/// Moment after which the contract expires, which is expressed either as a
+/// block height or as a UNIX timestamp, in seconds.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_CONTRACT, tags = order, dumb = { Expiry::Height(0) })]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub enum Expiry {
+    #[display("block height {0}")]
+    Height(u32),
+    #[display("timestamp {0}")]
+    Timestamp(i64),
+}
+impl StrictSerialize for Expiry {}
+impl StrictDeserialize for Expiry {}
+
+impl Expiry {
+    /// Checks whether the moment has passed at the given block height and
+    /// time.
+    pub fn has_passed(self, height: u32, timestamp: i64) -> bool {
+        match self {
+            Expiry::Height(expiry) => height >= expiry,
+            Expiry::Timestamp(expiry) => timestamp >= expiry,
+        }
+    }
+}
+
+/// Registered name of a media type component (type, subtype or a parameter
+/// value) as defined by RFC 6838, or a `*` wildcard.
+#[derive(Wrapper, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, From)]
+#[wrapper(Deref, Display)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_CONTRACT, dumb = { MediaRegName::from("dumb") })]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", transparent)
+)]
+pub struct MediaRegName(Confined<AsciiString, 1, 64>);
+
+impl FromStr for MediaRegName {
+    type Err = InvalidIdent;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = AsciiString::from_ascii(s.as_bytes())?;
+        Self::try_from(s)
+    }
+}
+
+impl From<&'static str> for MediaRegName {
+    fn from(s: &'static str) -> Self { Self::from_str(s).expect("invalid media type name") }
+}
+
+impl TryFrom<AsciiString> for MediaRegName {
+    type Error = InvalidIdent;
+
+    fn try_from(ascii: AsciiString) -> Result<Self, InvalidIdent> {
+        let Some(first) = ascii.first() else {
+            return Err(InvalidIdent::Empty);
+        };
+        // Wildcard used in `*/*` media type
+        if ascii.as_str() == "*" {
+            return Ok(Self(Confined::try_from(ascii)?));
+        }
+        if !first.is_alphanumeric() {
+            return Err(InvalidIdent::NonAlphabetic(first));
+        }
+        if let Some(ch) = ascii
+            .as_slice()
+            .iter()
+            .copied()
+            .find(|ch| !ch.is_alphanumeric() && !b"!#$&-^_.+".contains(&ch.as_byte()))
+        {
+            return Err(InvalidIdent::InvalidChar(ch));
+        }
+        let s = Confined::try_from(ascii)?;
+        Ok(Self(s))
+    }
+}
+
+impl Debug for MediaRegName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MediaRegName")
+            .field(&self.as_str())
+            .finish()
+    }
+}
+
+/// Media type of the data (MIME type), like `image/png` or `text/plain;
+/// charset=utf-8`. Media type without subtype means any subtype of the type
+/// (`image/*`).
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_CONTRACT)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct MediaType {
+    #[strict_type(rename = "type")]
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub ty: MediaRegName,
+    pub subtype: Option<MediaRegName>,
+    pub charset: Option<MediaRegName>,
+}
+impl StrictSerialize for MediaType {}
+impl StrictDeserialize for MediaType {}
+
+impl MediaType {
+    pub const PNG: &'static str = "image/png";
+    pub const JPEG: &'static str = "image/jpeg";
+    pub const GIF: &'static str = "image/gif";
+    pub const PDF: &'static str = "application/pdf";
+    pub const JSON: &'static str = "application/json";
+    pub const ZIP: &'static str = "application/zip";
+
+    pub fn with(ty: &'static str, subtype: &'static str) -> Self {
+        MediaType {
+            ty: MediaRegName::from(ty),
+            subtype: Some(MediaRegName::from(subtype)),
+            charset: None,
+        }
+    }
+
+    /// Checks whether the media type covers other media type, either by being
+    /// equal to it, or by not restricting the subtype of the same type.
+    /// Charset is not taken into account.
+    pub fn covers(&self, other: &MediaType) -> bool {
+        self.ty.as_str() == "*" ||
+            (self.ty == other.ty && (self.subtype.is_none() || self.subtype == other.subtype))
+    }
+}
+
+impl From<&'static str> for MediaType {
+    fn from(s: &'static str) -> Self { Self::from_str(s).expect("invalid media type") }
+}
+
+impl FromStr for MediaType {
+    type Err = InvalidIdent;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mime, params) = s.split_once(';').unwrap_or((s, ""));
+        let (ty, subtype) = mime.trim().split_once('/').unwrap_or((mime.trim(), "*"));
+        let charset = params
+            .split(';')
+            .filter_map(|param| param.trim().split_once('='))
+            .find(|(name, _)| name.eq_ignore_ascii_case("charset"))
+            .map(|(_, value)| MediaRegName::from_str(value.trim_matches('"')))
+            .transpose()?;
+        Ok(MediaType {
+            ty: MediaRegName::from_str(ty)?,
+            subtype: match subtype {
+                "*" => None,
+                subtype => Some(MediaRegName::from_str(subtype)?),
+            },
+            charset,
+        })
+    }
+}
+
+impl Display for MediaType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/", self.ty)?;
+        match &self.subtype {
+            Some(subtype) => write!(f, "{subtype}")?,
+            None => f.write_str("*")?,
+        }
+        if let Some(charset) = &self.charset {
+            write!(f, ";charset={charset}")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Default)]
 pub struct StandardTypes(TypeSystem);
 
@@ -286,6 +561,9 @@ impl StandardTypes {
             let lib = LibBuilder::new(libname!(LIB_NAME_RGB_CONTRACT))
                 .process::<Nominal>()?
                 .process::<ContractText>()?
+                .process::<ContractTerms>()?
+                .process::<MediaType>()?
+                .process::<Expiry>()?
                 .compile(none!())?;
             let sys = SystemBuilder::new().import(lib)?.finalize()?;
             Ok(sys)
@@ -302,3 +580,45 @@ impl StandardTypes {
             .expect("type is absent in standard RGBContract type library")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn media_type_display_parse() {
+        for s in ["image/png", "image/*", "*/*", "text/plain;charset=utf-8"] {
+            assert_eq!(MediaType::from_str(s).unwrap().to_string(), s);
+        }
+        assert_eq!(
+            MediaType::from_str("text/plain; charset=\"UTF-8\"").unwrap(),
+            MediaType {
+                ty: MediaRegName::from("text"),
+                subtype: Some(MediaRegName::from("plain")),
+                charset: Some(MediaRegName::from("UTF-8")),
+            }
+        );
+        assert_eq!(MediaType::from(MediaType::PNG), MediaType::with("image", "png"));
+        assert!(MediaType::from_str("image/p ng").is_err());
+        assert!(MediaType::from_str("/png").is_err());
+        assert!(MediaRegName::from_str(&"a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn media_type_covers() {
+        let png = MediaType::from(MediaType::PNG);
+        assert!(MediaType::from("*/*").covers(&png));
+        assert!(MediaType::from("image/*").covers(&png));
+        assert!(png.covers(&png));
+        assert!(!MediaType::from(MediaType::JPEG).covers(&png));
+        assert!(!png.covers(&MediaType::from("image/*")));
+    }
+
+    #[test]
+    fn media_type_strict() {
+        let media_type = MediaType::from("text/plain;charset=utf-8");
+        let data = media_type.to_strict_serialized::<0xFF>().unwrap();
+        assert_eq!(MediaType::from_strict_serialized::<0xFF>(data).unwrap(), media_type);
+        StandardTypes::new().get("RGBContract.MediaType");
+    }
+}
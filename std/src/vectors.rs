@@ -0,0 +1,188 @@
+// RGB standard library for working with smart contracts on Bitcoin & Lightning
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2023 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2023 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Canonical test vectors, allowing other implementations and older versions
+//! of the library to check byte-exact compatibility of the serialization.
+//!
+//! The vectors are produced from the deterministic [`crate::fixtures`]: a
+//! contract, both transitions of a two-hop transfer, the transfer consignment,
+//! an ASCII-armored contract bindle and, with the `serde` feature, the JSON
+//! invoice template of the contract. Each vector is stored as a file with
+//! the serialized data and a file with the `.id` suffix containing the
+//! expected id of the serialized object. The vectors of the library are kept
+//! in the `tests/vectors` directory of the crate.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rgb::Operation;
+use strict_encoding::{StrictEncode, StrictWriter};
+
+use crate::containers::Bindle;
+use crate::fixtures::{self, TwoHopTransfer};
+
+/// Serialized object with its expected id.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct TestVector {
+    /// Name of the file containing the serialized data.
+    pub name: &'static str,
+    /// Id of the object: contract id (also for invoice templates), operation
+    /// id, transfer id or bindle id, depending on the type of the object.
+    pub id: String,
+    /// Strict-serialized object, the text of the ASCII armor for bindles or
+    /// the JSON text for invoice templates.
+    pub data: Vec<u8>,
+}
+
+fn serialize(value: &impl StrictEncode) -> Vec<u8> {
+    value
+        .strict_encode(StrictWriter::in_memory(usize::MAX))
+        .expect("in-memory")
+        .unbox()
+}
+
+/// Generates all canonical test vectors.
+pub fn generate() -> Vec<TestVector> {
+    let TwoHopTransfer {
+        contract,
+        first,
+        second,
+    } = fixtures::two_hop_transfer();
+    let bindle = Bindle::new(contract.clone());
+    let transfer = fixtures::transfer_consignment();
+    #[allow(unused_mut)]
+    let mut vectors = vec![
+        TestVector {
+            name: "contract.rgb",
+            id: contract.contract_id().to_string(),
+            data: serialize(&contract),
+        },
+        TestVector {
+            name: "transition-1.rgb",
+            id: first.id().to_string(),
+            data: serialize(&first),
+        },
+        TestVector {
+            name: "transition-2.rgb",
+            id: second.id().to_string(),
+            data: serialize(&second),
+        },
+        TestVector {
+            name: "transfer.rgb",
+            id: transfer.transfer_id().to_string(),
+            data: serialize(&transfer),
+        },
+        TestVector {
+            name: "contract.rgba",
+            id: bindle.id().to_string(),
+            data: bindle.to_string().into_bytes(),
+        },
+    ];
+    // Invoice template has no strict encoding: it is exchanged with wallets in
+    // its JSON form
+    #[cfg(feature = "serde")]
+    vectors.push(TestVector {
+        name: "invoice.json",
+        id: contract.contract_id().to_string(),
+        data: serde_json::to_vec_pretty(&contract.invoice_template())
+            .expect("invoice template serialization can't fail"),
+    });
+    vectors
+}
+
+/// Writes all canonical test vectors into the directory, creating it if
+/// necessary and overwriting existing vectors.
+pub fn write(dir: impl AsRef<Path>) -> io::Result<()> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    for vector in generate() {
+        fs::write(dir.join(vector.name), &vector.data)?;
+        fs::write(dir.join(format!("{}.id", vector.name)), &vector.id)?;
+    }
+    Ok(())
+}
+
+/// Compares the vectors stored in the directory with the freshly generated
+/// ones, returning names of the vectors which data or id differ or which are
+/// absent.
+pub fn mismatches(dir: impl AsRef<Path>) -> io::Result<Vec<&'static str>> {
+    let dir = dir.as_ref();
+    let mut mismatches = vec![];
+    for vector in generate() {
+        let read = |name: &str| match fs::read(dir.join(name)) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        };
+        let data = read(vector.name)?;
+        let id = read(&format!("{}.id", vector.name))?;
+        if data.as_ref() != Some(&vector.data) || id.as_deref() != Some(vector.id.as_bytes()) {
+            mismatches.push(vector.name);
+        }
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Setting `RGB_UPDATE_VECTORS` regenerates the vectors after an intended
+    /// change of the serialization; otherwise any absent or different vector
+    /// fails the test.
+    #[test]
+    fn canonical() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/vectors");
+        if env::var_os("RGB_UPDATE_VECTORS").is_some() {
+            write(&dir).unwrap();
+        }
+        assert_eq!(mismatches(&dir).unwrap(), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn ids() {
+        #[allow(unused_mut)]
+        let mut expected = vec![
+            ("contract.rgb", s!("DPyPzvHy9QBvEjDy6N5rFLDccMWtMpRXFj6tmZbaVGDE")),
+            (
+                "transition-1.rgb",
+                s!("9d9bee4dcd9b20391a3964f659b008ed817c7a8bde47ee2812d5aafeb7c9a9f7")
+            ),
+            (
+                "transition-2.rgb",
+                s!("e9870eb00544df4088866ea6e5b6061d789454a0fa2efc0422f0c2d549560d62")
+            ),
+            ("transfer.rgb", s!("FXFagcQoXzwZaqjETd3uPMo52zourtNPSVta3nqvZdfA")),
+            ("contract.rgba", s!("DPyPzvHy9QBvEjDy6N5rFLDccMWtMpRXFj6tmZbaVGDE")),
+        ];
+        #[cfg(feature = "serde")]
+        expected.push(("invoice.json", s!("DPyPzvHy9QBvEjDy6N5rFLDccMWtMpRXFj6tmZbaVGDE")));
+        let ids = generate()
+            .into_iter()
+            .map(|vector| (vector.name, vector.id))
+            .collect::<Vec<_>>();
+        assert_eq!(ids, expected);
+    }
+}